@@ -9,7 +9,7 @@ use std::{
 /// Debug information for a Koto program
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct DebugInfo {
-    source_map: Vec<(u32, Span)>,
+    pub(crate) source_map: Vec<(u32, Span)>,
     /// The source of the program that the debug info was derived from
     pub source: String,
 }
@@ -46,6 +46,17 @@ impl DebugInfo {
         }
         result
     }
+
+    /// Returns the instruction pointer of the first instruction with a span starting on the line
+    ///
+    /// The line is zero-based, matching the lines used in [Span]s.
+    pub fn first_ip_on_line(&self, line: u32) -> Option<u32> {
+        self.source_map
+            .iter()
+            .filter(|(_, span)| span.start.line == line)
+            .map(|(ip, _)| *ip)
+            .min()
+    }
 }
 
 /// A compiled chunk of bytecode, along with its associated constants and metadata
@@ -103,6 +114,16 @@ impl Chunk {
         result
     }
 
+    /// Returns a [String] displaying the instructions contained in the compiled [Chunk],
+    /// annotated with the lines from the chunk's source
+    ///
+    /// See [Chunk::instructions_as_string].
+    pub fn annotated_instructions_as_string(chunk: Ptr<Chunk>) -> String {
+        let source = chunk.debug_info.source.clone();
+        let source_lines = source.lines().collect::<Vec<_>>();
+        Self::instructions_as_string(chunk, &source_lines)
+    }
+
     /// Returns a [String] displaying the annotated instructions contained in the compiled [Chunk]
     pub fn instructions_as_string(chunk: Ptr<Chunk>, source_lines: &[&str]) -> String {
         let mut result = String::new();
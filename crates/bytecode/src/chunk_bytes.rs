@@ -0,0 +1,362 @@
+use crate::{Chunk, DebugInfo};
+use koto_parser::{Constant, ConstantPool, Position, Span};
+use std::path::PathBuf;
+use thiserror::Error;
+
+// The bytes that are found at the start of every serialized chunk
+const MAGIC: &[u8; 4] = b"KOTO";
+
+// Tags used to identify the type of each serialized constant
+const CONSTANT_F64: u8 = 0;
+const CONSTANT_I64: u8 = 1;
+const CONSTANT_STR: u8 = 2;
+
+/// Errors that can be returned from [Chunk::from_bytes]
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum ChunkBytesError {
+    #[error("The data doesn't contain a serialized Koto chunk")]
+    MissingHeader,
+    #[error(
+        "Unsupported chunk format (version {found}, produced by Koto {compiler_version}), \
+         expected format version {expected}"
+    )]
+    UnsupportedFormatVersion {
+        found: u32,
+        expected: u32,
+        compiler_version: String,
+    },
+    #[error("The chunk was produced by Koto {found}, expected Koto {expected}")]
+    MismatchedCompilerVersion { found: String, expected: String },
+    #[error("The chunk data is corrupted")]
+    Corrupted,
+}
+
+impl Chunk {
+    /// The version of the format produced by [Chunk::to_bytes]
+    ///
+    /// The version is increased whenever the serialization format changes, and only chunks with
+    /// a matching format version can be loaded by [Chunk::from_bytes].
+    ///
+    /// The bytecode can change between releases without the format version changing, so chunks
+    /// also need to have been produced by the same version of Koto, see
+    /// [Chunk::COMPILER_VERSION].
+    pub const FORMAT_VERSION: u32 = 2;
+
+    /// The version of Koto that's included in the header produced by [Chunk::to_bytes]
+    ///
+    /// Only chunks produced by a matching version of Koto can be loaded by [Chunk::from_bytes].
+    pub const COMPILER_VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
+    /// Serializes the chunk into a sequence of bytes
+    ///
+    /// The chunk's bytecode, constants, and debug information are included in the output,
+    /// which can be deserialized with [Chunk::from_bytes], skipping the parsing and compilation
+    /// of the chunk's script.
+    ///
+    /// The output starts with a header that contains the format version, along with the version
+    /// of the compiler that produced the chunk.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+
+        write_bytes(&mut payload, &self.bytes);
+
+        write_u32(&mut payload, self.constants.size() as u32);
+        for constant in self.constants.iter() {
+            match constant {
+                Constant::F64(n) => {
+                    payload.push(CONSTANT_F64);
+                    write_u64(&mut payload, n.to_bits());
+                }
+                Constant::I64(n) => {
+                    payload.push(CONSTANT_I64);
+                    write_u64(&mut payload, n as u64);
+                }
+                Constant::Str(s) => {
+                    payload.push(CONSTANT_STR);
+                    write_bytes(&mut payload, s.as_bytes());
+                }
+            }
+        }
+
+        match &self.source_path {
+            Some(path) => {
+                payload.push(1);
+                write_bytes(&mut payload, path.to_string_lossy().as_bytes());
+            }
+            None => payload.push(0),
+        }
+
+        write_u32(&mut payload, self.debug_info.source_map.len() as u32);
+        for (ip, span) in self.debug_info.source_map.iter() {
+            write_u32(&mut payload, *ip);
+            for n in [
+                span.start.line,
+                span.start.column,
+                span.end.line,
+                span.end.column,
+            ] {
+                write_u32(&mut payload, n);
+            }
+        }
+        write_bytes(&mut payload, self.debug_info.source.as_bytes());
+
+        // The header's layout needs to stay the same across format versions,
+        // so that mismatched versions can be reported.
+        let mut result = Vec::with_capacity(payload.len() + 32);
+        result.extend_from_slice(MAGIC);
+        write_u32(&mut result, Self::FORMAT_VERSION);
+        write_bytes(&mut result, Self::COMPILER_VERSION.as_bytes());
+        write_u64(&mut result, checksum(&payload));
+        result.extend_from_slice(&payload);
+        result
+    }
+
+    /// Deserializes a chunk that was serialized with [Chunk::to_bytes]
+    ///
+    /// An error is returned if the chunk was produced with a different format version or by a
+    /// different version of Koto, or if the data has been corrupted.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ChunkBytesError> {
+        let mut reader = ByteReader::new(bytes);
+
+        if reader.take(MAGIC.len()).ok() != Some(MAGIC.as_slice()) {
+            return Err(ChunkBytesError::MissingHeader);
+        }
+
+        let format_version = reader.u32()?;
+        let compiler_version = reader.string()?;
+        if format_version != Self::FORMAT_VERSION {
+            return Err(ChunkBytesError::UnsupportedFormatVersion {
+                found: format_version,
+                expected: Self::FORMAT_VERSION,
+                compiler_version,
+            });
+        }
+        if compiler_version != Self::COMPILER_VERSION {
+            return Err(ChunkBytesError::MismatchedCompilerVersion {
+                found: compiler_version,
+                expected: Self::COMPILER_VERSION.into(),
+            });
+        }
+
+        let expected_checksum = reader.u64()?;
+        if checksum(reader.remaining()) != expected_checksum {
+            return Err(ChunkBytesError::Corrupted);
+        }
+
+        let bytecode = reader.take_sized()?.into();
+
+        let constant_count = reader.u32()?;
+        let mut constants = Vec::new();
+        for _ in 0..constant_count {
+            let constant = match reader.u8()? {
+                CONSTANT_F64 => Constant::F64(f64::from_bits(reader.u64()?)),
+                CONSTANT_I64 => Constant::I64(reader.u64()? as i64),
+                CONSTANT_STR => Constant::Str(reader.str()?),
+                _ => return Err(ChunkBytesError::Corrupted),
+            };
+            constants.push(constant);
+        }
+        let constants =
+            ConstantPool::from_constants(constants).ok_or(ChunkBytesError::Corrupted)?;
+
+        let source_path = match reader.u8()? {
+            0 => None,
+            1 => Some(PathBuf::from(reader.str()?)),
+            _ => return Err(ChunkBytesError::Corrupted),
+        };
+
+        let mut debug_info = DebugInfo::default();
+        let source_map_len = reader.u32()?;
+        for _ in 0..source_map_len {
+            let ip = reader.u32()?;
+            let start = Position {
+                line: reader.u32()?,
+                column: reader.u32()?,
+            };
+            let end = Position {
+                line: reader.u32()?,
+                column: reader.u32()?,
+            };
+            debug_info.source_map.push((ip, Span { start, end }));
+        }
+        debug_info.source = reader.string()?;
+
+        if !reader.remaining().is_empty() {
+            return Err(ChunkBytesError::Corrupted);
+        }
+
+        Ok(Self {
+            bytes: bytecode,
+            constants,
+            source_path,
+            debug_info,
+        })
+    }
+}
+
+fn write_u32(output: &mut Vec<u8>, n: u32) {
+    output.extend_from_slice(&n.to_le_bytes());
+}
+
+fn write_u64(output: &mut Vec<u8>, n: u64) {
+    output.extend_from_slice(&n.to_le_bytes());
+}
+
+// Writes the length of the bytes followed by the bytes themselves
+fn write_bytes(output: &mut Vec<u8>, bytes: &[u8]) {
+    write_u32(output, bytes.len() as u32);
+    output.extend_from_slice(bytes);
+}
+
+// A 64 bit FNV-1a hash, used to detect corrupted chunk data
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+// Reads values from serialized chunk data, with any unexpected data treated as corruption
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn remaining(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], ChunkBytesError> {
+        if count > self.bytes.len() {
+            return Err(ChunkBytesError::Corrupted);
+        }
+        let (result, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(result)
+    }
+
+    fn take_sized(&mut self) -> Result<&'a [u8], ChunkBytesError> {
+        let len = self.u32()?;
+        self.take(len as usize)
+    }
+
+    fn u8(&mut self) -> Result<u8, ChunkBytesError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, ChunkBytesError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, ChunkBytesError> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn str(&mut self) -> Result<&'a str, ChunkBytesError> {
+        std::str::from_utf8(self.take_sized()?).map_err(|_| ChunkBytesError::Corrupted)
+    }
+
+    fn string(&mut self) -> Result<String, ChunkBytesError> {
+        self.str().map(String::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Compiler, CompilerSettings};
+    use koto_parser::Parser;
+
+    fn compile(script: &str) -> Chunk {
+        let ast = Parser::parse(script).unwrap();
+        let (bytes, mut debug_info) = Compiler::compile(&ast, CompilerSettings::default()).unwrap();
+        debug_info.source = script.into();
+        Chunk::new(bytes, ast.consume_constants(), None, debug_info)
+    }
+
+    #[test]
+    fn round_trip() {
+        let chunk = compile(
+            "\
+x = 'hello'
+y = [1, 2.5, x]
+f = |n| n * 2
+f y[0]
+",
+        );
+
+        let deserialized = Chunk::from_bytes(&chunk.to_bytes()).unwrap();
+
+        assert_eq!(chunk.bytes, deserialized.bytes);
+        assert_eq!(
+            chunk.constants.iter().collect::<Vec<_>>(),
+            deserialized.constants.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(chunk.debug_info, deserialized.debug_info);
+        assert_eq!(chunk, deserialized);
+    }
+
+    #[test]
+    fn missing_header() {
+        assert_eq!(
+            Chunk::from_bytes(b"x = 1"),
+            Err(ChunkBytesError::MissingHeader)
+        );
+    }
+
+    #[test]
+    fn mismatched_format_version() {
+        let mut bytes = compile("1 + 1").to_bytes();
+        let version_offset = MAGIC.len();
+        bytes[version_offset..version_offset + 4]
+            .copy_from_slice(&(Chunk::FORMAT_VERSION + 1).to_le_bytes());
+
+        assert!(matches!(
+            Chunk::from_bytes(&bytes),
+            Err(ChunkBytesError::UnsupportedFormatVersion { found, .. })
+                if found == Chunk::FORMAT_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn mismatched_compiler_version() {
+        let mut bytes = compile("1 + 1").to_bytes();
+        // The compiler version follows the format version, prefixed with its length
+        let version_offset = MAGIC.len() + 4 + 4;
+        bytes[version_offset] = b'X';
+
+        let mut found_version = Chunk::COMPILER_VERSION.to_string();
+        found_version.replace_range(0..1, "X");
+        assert_eq!(
+            Chunk::from_bytes(&bytes),
+            Err(ChunkBytesError::MismatchedCompilerVersion {
+                found: found_version,
+                expected: Chunk::COMPILER_VERSION.into(),
+            })
+        );
+    }
+
+    #[test]
+    fn corrupted_bytes() {
+        let bytes = compile("x = 'hello'").to_bytes();
+
+        let mut modified = bytes.clone();
+        *modified.last_mut().unwrap() ^= 0xff;
+        assert_eq!(
+            Chunk::from_bytes(&modified),
+            Err(ChunkBytesError::Corrupted)
+        );
+
+        let truncated = &bytes[..bytes.len() - 1];
+        assert_eq!(
+            Chunk::from_bytes(truncated),
+            Err(ChunkBytesError::Corrupted)
+        );
+    }
+}
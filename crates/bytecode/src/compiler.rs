@@ -326,6 +326,7 @@ impl Compiler {
             }
             Node::Map(entries) => self.compile_make_map(entries, false, ctx)?,
             Node::Self_ => {
+                self.frame_mut().accesses_self = true;
                 // self is always in register 0
                 match ctx.result_register {
                     ResultRegister::None => CompileNodeOutput::none(),
@@ -562,7 +563,8 @@ impl Compiler {
         Ok(result)
     }
 
-    fn compile_frame(&mut self, params: FrameParameters, ctx: CompileNodeContext) -> Result<()> {
+    // Compiles a frame's body, returning the frame once compilation is complete
+    fn compile_frame(&mut self, params: FrameParameters, ctx: CompileNodeContext) -> Result<Frame> {
         let FrameParameters {
             local_count,
             expressions,
@@ -641,9 +643,7 @@ impl Compiler {
             self.pop_register()?;
         }
 
-        self.frame_stack.pop();
-
-        Ok(())
+        Ok(self.frame_stack.pop().expect("Frame stack is empty"))
     }
 
     fn compile_return(
@@ -1584,6 +1584,16 @@ impl Compiler {
 
         let result = self.assign_result_register(ctx)?;
 
+        // If there's a finally block, then errors thrown in the catch block are placed in a
+        // register so that they can be rethrown after the finally block has been executed.
+        let pending_error_register = if finally_block.is_some() {
+            let register = self.push_register()?;
+            self.push_op(SetNull, &[register]);
+            Some(register)
+        } else {
+            None
+        };
+
         // The argument register for the catch block needs to be assigned now
         // so that it can be included in the TryStart op.
         let (catch_register, pop_catch_register) = match ctx.node(*catch_arg) {
@@ -1629,6 +1639,14 @@ impl Compiler {
         //   are errors thrown in the catch block.
         self.push_op(TryEnd, &[]);
 
+        let catch_error_offset = match pending_error_register {
+            Some(pending_error_register) => {
+                self.push_op(TryStart, &[pending_error_register]);
+                Some(self.push_offset_placeholder())
+            }
+            None => None,
+        };
+
         self.compile_node(*catch_block, ctx.with_register(try_result_register))?;
         self.pop_span();
 
@@ -1636,17 +1654,38 @@ impl Compiler {
             self.pop_register()?;
         }
 
+        if let Some(catch_error_offset) = catch_error_offset {
+            // Clear the catch point at the end of the catch block, and then jump to the finally
+            // block, skipping over the catch point removal for errors thrown in the catch block.
+            self.push_op_without_span(TryEnd, &[]);
+            self.push_op_without_span(Jump, &[]);
+            let catch_end_offset = self.push_offset_placeholder();
+            self.update_offset_placeholder(catch_error_offset)?;
+            self.push_op_without_span(TryEnd, &[]);
+            self.update_offset_placeholder(catch_end_offset)?;
+        }
+
         self.update_offset_placeholder(finally_offset)?;
-        if let Some(finally_block) = finally_block {
+        if let (Some(finally_block), Some(pending_error_register)) =
+            (finally_block, pending_error_register)
+        {
             // If there's a finally block then the result of the expression is derived from there
             let finally_result_register = match result.register {
                 Some(result_register) => ResultRegister::Fixed(result_register),
                 _ => ResultRegister::None,
             };
-            self.compile_node(*finally_block, ctx.with_register(finally_result_register))
-        } else {
-            Ok(result)
+            self.compile_node(*finally_block, ctx.with_register(finally_result_register))?;
+
+            // Rethrow any error that was thrown in the catch block
+            self.push_op_without_span(JumpIfFalse, &[pending_error_register]);
+            let no_error_offset = self.push_offset_placeholder();
+            self.push_op(Throw, &[pending_error_register]);
+            self.update_offset_placeholder(no_error_offset)?;
+
+            self.pop_register()?; // pending_error_register
         }
+
+        Ok(result)
     }
 
     fn compile_unary_op(
@@ -2267,6 +2306,8 @@ impl Compiler {
                 variadic: function.is_variadic,
                 generator: function.is_generator,
                 arg_is_unpacked_tuple,
+                // Updated below once the function's body has been compiled
+                accesses_self: false,
             }
             .as_byte();
 
@@ -2292,7 +2333,7 @@ impl Compiler {
                 Node::Block(expressions) => expressions.as_slice(),
                 _ => &body_as_slice,
             };
-            self.compile_frame(
+            let function_frame = self.compile_frame(
                 FrameParameters {
                     local_count,
                     expressions: function_body,
@@ -2305,6 +2346,11 @@ impl Compiler {
                 ctx,
             )?;
 
+            if function_frame.accesses_self {
+                // The flags byte immediately precedes the function size placeholder
+                self.bytes[function_size_ip - 1] |= FunctionFlags::ACCESSES_SELF;
+            }
+
             self.update_offset_placeholder(function_size_ip)?;
 
             for (i, capture) in captures.iter().enumerate() {
@@ -88,6 +88,8 @@ pub(crate) struct Frame {
     // Used to decide if return types should be checked (output type hints only apply to yield
     // expressions in genertors).
     pub is_generator: bool,
+    // True if `self` is accessed in the frame, used to identify instance functions.
+    pub accesses_self: bool,
 }
 
 impl Frame {
@@ -114,6 +114,7 @@ pub enum Instruction {
         variadic: bool,
         generator: bool,
         arg_is_unpacked_tuple: bool,
+        accesses_self: bool,
         size: u16,
     },
     Capture {
@@ -349,6 +350,8 @@ pub struct FunctionFlags {
     pub generator: bool,
     /// True if the function has a single argument which is an unpacked tuple
     pub arg_is_unpacked_tuple: bool,
+    /// True if the function accesses `self`
+    pub accesses_self: bool,
 }
 
 impl FunctionFlags {
@@ -358,6 +361,8 @@ impl FunctionFlags {
     pub const GENERATOR: u8 = 1 << 1;
     /// Corresponding to [FunctionFlags::arg_is_unpacked_tuple]
     pub const ARG_IS_UNPACKED_TUPLE: u8 = 1 << 2;
+    /// Corresponding to [FunctionFlags::accesses_self]
+    pub const ACCESSES_SELF: u8 = 1 << 3;
 
     /// Initializes a flags struct from a byte
     pub fn from_byte(byte: u8) -> Self {
@@ -365,6 +370,7 @@ impl FunctionFlags {
             variadic: byte & Self::VARIADIC != 0,
             generator: byte & Self::GENERATOR != 0,
             arg_is_unpacked_tuple: byte & Self::ARG_IS_UNPACKED_TUPLE != 0,
+            accesses_self: byte & Self::ACCESSES_SELF != 0,
         }
     }
 
@@ -380,6 +386,9 @@ impl FunctionFlags {
         if self.arg_is_unpacked_tuple {
             result |= Self::ARG_IS_UNPACKED_TUPLE;
         }
+        if self.accesses_self {
+            result |= Self::ACCESSES_SELF;
+        }
         result
     }
 }
@@ -538,13 +547,15 @@ impl fmt::Debug for Instruction {
                 variadic,
                 generator,
                 arg_is_unpacked_tuple,
+                accesses_self,
                 size,
             } => write!(
                 f,
                 "Function\tresult: {register}\targs: {arg_count}\
                  \t\tcaptures: {capture_count}
                  \t\t\tsize: {size} \tgenerator: {generator}
-                 \t\t\tvariadic: {variadic}\targ_is_unpacked_tuple: {arg_is_unpacked_tuple}",
+                 \t\t\tvariadic: {variadic}\targ_is_unpacked_tuple: {arg_is_unpacked_tuple}
+                 \t\t\taccesses_self: {accesses_self}",
             ),
             Capture {
                 function,
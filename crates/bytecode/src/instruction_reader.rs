@@ -215,6 +215,7 @@ impl Iterator for InstructionReader {
                     variadic: flags.variadic,
                     generator: flags.generator,
                     arg_is_unpacked_tuple: flags.arg_is_unpacked_tuple,
+                    accesses_self: flags.accesses_self,
                     size,
                 })
             }
@@ -3,6 +3,7 @@
 #![warn(missing_docs)]
 
 mod chunk;
+mod chunk_bytes;
 mod compiler;
 mod frame;
 mod instruction;
@@ -12,6 +13,7 @@ mod op;
 
 pub use crate::{
     chunk::{Chunk, DebugInfo},
+    chunk_bytes::ChunkBytesError,
     compiler::{Compiler, CompilerError, CompilerSettings},
     instruction::{FunctionFlags, Instruction, StringFormatFlags},
     instruction_reader::InstructionReader,
@@ -89,6 +89,16 @@ impl LoaderError {
             _ => false,
         }
     }
+
+    /// Returns true if the error was caused by the input ending before parsing was complete
+    ///
+    /// See [koto_parser::Error::is_unexpected_end_of_input].
+    pub fn is_unexpected_end_of_input(&self) -> bool {
+        match self.error.deref() {
+            LoaderErrorKind::Parser(e) => e.is_unexpected_end_of_input(),
+            _ => false,
+        }
+    }
 }
 
 impl fmt::Display for LoaderError {
@@ -193,6 +203,36 @@ impl Loader {
         load_module_from_path(module_path)
     }
 
+    /// Compiles a module from a script that was provided from somewhere other than the file system
+    ///
+    /// The path is used to identify the module in the cache, and in error messages,
+    /// but it doesn't need to exist. If the module was previously compiled with the same script,
+    /// then the cached chunk is returned.
+    pub fn compile_module_with_script(
+        &mut self,
+        module_path: &Path,
+        script: &str,
+    ) -> Result<CompileModuleResult, LoaderError> {
+        if let Some(chunk) = self.chunks.get(module_path) {
+            if chunk.debug_info.source == script {
+                return Ok(CompileModuleResult {
+                    chunk: chunk.clone(),
+                    path: module_path.to_path_buf(),
+                    loaded_from_cache: true,
+                });
+            }
+        }
+
+        let chunk = self.compile_script(script, Some(module_path), CompilerSettings::default())?;
+        self.chunks.insert(module_path.to_path_buf(), chunk.clone());
+
+        Ok(CompileModuleResult {
+            chunk,
+            path: module_path.to_path_buf(),
+            loaded_from_cache: false,
+        })
+    }
+
     /// Clears the compiled module cache
     pub fn clear_cache(&mut self) {
         self.chunks.clear();
@@ -29,6 +29,7 @@ FLAGS:
     -b, --show_bytecode      Show the script's compiled bytecode
     -t, --tests              Run the script's tests before running the script
     -T, --import_tests       Run the script's tests, along with any tests in imported modules
+    -p, --profile            Profile the script, and print a report when the script finishes
     -c, --config PATH        Config file to load when using the REPL
     -v, --version            Prints version information
     -h, --help               Prints help information
@@ -72,6 +73,7 @@ struct KotoArgs {
     run_import_tests: bool,
     show_bytecode: bool,
     show_instructions: bool,
+    profile: bool,
     script: Option<String>,
     script_args: Vec<String>,
     config_file: Option<String>,
@@ -85,6 +87,7 @@ fn parse_arguments() -> Result<KotoArgs> {
     let show_bytecode = args.contains(["-b", "--show_bytecode"]);
     let run_tests = args.contains(["-t", "--tests"]);
     let run_import_tests = args.contains(["-T", "--import_tests"]);
+    let profile = args.contains(["-p", "--profile"]);
     let help = args.contains(["-h", "--help"]);
     let version = args.contains(["-v", "--version"]);
     let config_file = args.opt_value_from_str(["-c", "--config"])?;
@@ -109,6 +112,7 @@ fn parse_arguments() -> Result<KotoArgs> {
         run_import_tests,
         show_bytecode,
         show_instructions,
+        profile,
         script,
         script_args,
         config_file,
@@ -137,6 +141,7 @@ fn main() -> Result<()> {
         run_tests: args.run_tests || args.run_import_tests,
         vm_settings: KotoVmSettings {
             run_import_tests: args.run_import_tests,
+            profiling: args.profile,
             ..Default::default()
         },
         ..Default::default()
@@ -180,15 +185,17 @@ fn main() -> Result<()> {
                 }
                 if args.show_instructions {
                     println!("Constants\n---------\n{}\n", chunk.constants);
-
-                    let script_lines = script.lines().collect::<Vec<_>>();
                     println!(
                         "Instructions\n------------\n{}",
-                        Chunk::instructions_as_string(chunk, &script_lines)
+                        Chunk::annotated_instructions_as_string(chunk)
                     );
                 }
                 koto.set_args(&args.script_args)?;
-                match koto.run() {
+                let result = koto.run();
+                if let Some(report) = koto.profile_report() {
+                    eprintln!("\n{report}");
+                }
+                match result {
                     Ok(_) => {}
                     Err(error) if error.source().is_some() => {
                         bail!("{error}\n{}", error.source().unwrap())
@@ -163,12 +163,10 @@ impl Repl {
                     }
                     if self.settings.show_instructions {
                         print_wrapped!(self.stdout, "Constants\n---------\n{}\n", chunk.constants)?;
-
-                        let script_lines = input.lines().collect::<Vec<_>>();
                         print_wrapped!(
                             self.stdout,
                             "Instructions\n------------\n{}",
-                            Chunk::instructions_as_string(chunk, &script_lines)
+                            Chunk::annotated_instructions_as_string(chunk)
                         )?;
                     }
                     match self.koto.run() {
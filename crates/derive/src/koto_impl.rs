@@ -206,6 +206,11 @@ fn insert_wrapped_function(
                     let s: LitStr = value.parse()?;
                     fn_names.push(s.value());
                     Ok(())
+                } else if meta.path.is_ident("name") {
+                    let value = meta.value()?;
+                    let s: LitStr = value.parse()?;
+                    fn_names[0] = s.value();
+                    Ok(())
                 } else {
                     Err(meta.error("unsupported attribute"))
                 }
@@ -74,6 +74,16 @@ pub fn derive_koto_copy(input: TokenStream) -> TokenStream {
 /// (e.g. if you want to implement chainable setters), then you can accept a `MethodContext<Self`>
 /// as the function argument and then return `MethodContext::instance_result()`.
 ///
+/// ## `name` and `alias` attributes
+///
+/// By default the method is made available in Koto using the function's name.
+/// `#[koto_method(name = "other_name")]` can be used to make the method available using a
+/// different name, which is useful when a function with the same name is already part of the
+/// type's Rust API.
+///
+/// Additional names for the method can be provided with `alias`,
+/// e.g. `#[koto_method(alias = "other_name")]`.
+///
 /// ## `runtime` attribute
 ///
 /// The macro generates code assuming that the top-level `koto` crate is being used,
@@ -1,5 +1,5 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use koto::Koto;
+use koto::prelude::*;
 use std::{fs::read_to_string, path::PathBuf};
 
 #[global_allocator]
@@ -11,6 +11,10 @@ struct BenchmarkRunner {
 
 impl BenchmarkRunner {
     fn setup(script_path: &str, args: &[String]) -> Self {
+        Self::setup_with_settings(script_path, args, KotoSettings::default())
+    }
+
+    fn setup_with_settings(script_path: &str, args: &[String], settings: KotoSettings) -> Self {
         let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         path.push("..");
         path.push("..");
@@ -19,7 +23,7 @@ impl BenchmarkRunner {
         path.push(script_path);
         let script = read_to_string(path).expect("Unable to load path");
 
-        let mut runtime = Koto::new();
+        let mut runtime = Koto::with_settings(settings);
         let prelude = runtime.prelude();
         prelude.insert("geometry", koto_geometry::make_module());
 
@@ -54,6 +58,16 @@ pub fn koto_benchmark(c: &mut Criterion) {
             runner.run();
         })
     });
+    c.bench_function("fib_with_trace_hook", |b| {
+        let mut runner = BenchmarkRunner::setup_with_settings(
+            "fib_recursive.koto",
+            &[],
+            KotoSettings::default().with_trace_hook(|_| TraceControl::Continue),
+        );
+        b.iter(|| {
+            runner.run();
+        })
+    });
     c.bench_function("enumerate", |b| {
         let mut runner = BenchmarkRunner::setup("enumerate.koto", &[]);
         b.iter(|| {
@@ -85,6 +99,18 @@ pub fn koto_benchmark(c: &mut Criterion) {
             runner.run();
         })
     });
+    c.bench_function("list_slicing", |b| {
+        let mut runner = BenchmarkRunner::setup("list_slicing.koto", &[]);
+        b.iter(|| {
+            runner.run();
+        })
+    });
+    c.bench_function("string_splitting", |b| {
+        let mut runner = BenchmarkRunner::setup("string_splitting.koto", &[]);
+        b.iter(|| {
+            runner.run();
+        })
+    });
     c.bench_function("n_body", |b| {
         let mut runner =
             BenchmarkRunner::setup("n_body.koto", &["10".to_string(), "quiet".to_string()]);
@@ -92,6 +118,24 @@ pub fn koto_benchmark(c: &mut Criterion) {
             runner.run();
         })
     });
+    c.bench_function("transform_points_loop", |b| {
+        let mut runner = BenchmarkRunner::setup(
+            "transform_points.koto",
+            &["1000".to_string(), "loop".to_string()],
+        );
+        b.iter(|| {
+            runner.run();
+        })
+    });
+    c.bench_function("transform_points_batch", |b| {
+        let mut runner = BenchmarkRunner::setup(
+            "transform_points.koto",
+            &["1000".to_string(), "batch".to_string()],
+        );
+        b.iter(|| {
+            runner.run();
+        })
+    });
 }
 
 criterion_group!(benches, koto_benchmark);
@@ -0,0 +1,68 @@
+use koto::{prelude::*, PtrMut, Result};
+
+fn main() {
+    let script = "
+print 'Hello'
+io.stdout().write 'Hello, '
+io.stdout().write_line 'World!'
+io.stderr().write_line 'Uh oh'
+";
+
+    let output = OutputCapture::default();
+
+    let mut koto = Koto::with_settings(
+        KotoSettings::default()
+            .with_stdout(output.clone())
+            .with_stderr(output.clone()),
+    );
+
+    koto.compile_and_run(script).unwrap();
+
+    for (i, line) in output.lines.borrow().iter().enumerate() {
+        println!("{i}: {line}");
+    }
+}
+
+// Captures output from Koto as a list of lines
+#[derive(Clone, Default)]
+struct OutputCapture {
+    lines: PtrMut<Vec<String>>,
+    current_line: PtrMut<String>,
+}
+
+impl KotoFile for OutputCapture {
+    fn id(&self) -> KString {
+        "_output_capture_".into()
+    }
+}
+
+impl KotoRead for OutputCapture {}
+
+impl KotoWrite for OutputCapture {
+    fn write(&self, bytes: &[u8]) -> Result<()> {
+        let text = std::str::from_utf8(bytes).map_err(|e| e.to_string())?;
+        let mut current_line = self.current_line.borrow_mut();
+
+        // Complete lines are moved to `lines`, with any remaining text kept for the next write
+        for c in text.chars() {
+            if c == '\n' {
+                self.lines
+                    .borrow_mut()
+                    .push(std::mem::take(&mut current_line));
+            } else {
+                current_line.push(c);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_line(&self, text: &str) -> Result<()> {
+        self.write(text.as_bytes())?;
+        self.write(b"\n")
+    }
+
+    fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+}
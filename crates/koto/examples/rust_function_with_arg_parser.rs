@@ -0,0 +1,25 @@
+use koto::prelude::*;
+
+fn main() {
+    let script = "
+print lerp 0, 10
+print lerp 0, 10, 0.25
+print try
+  lerp 0, 'ten'
+catch error
+  error
+";
+    let mut koto = Koto::default();
+
+    koto.prelude().add_fn("lerp", |ctx| {
+        let mut args = ArgParser::new("lerp", ctx.args());
+        let a = args.number("a")?;
+        let b = args.number("b")?;
+        let t = args.optional_number("t", 0.5)?;
+        args.finish()?;
+
+        Ok((a + (b - a) * t).into())
+    });
+
+    koto.compile_and_run(script).unwrap();
+}
@@ -0,0 +1,37 @@
+use koto::{prelude::*, PtrMut};
+
+fn main() {
+    let script = "
+record 'apples', 3
+record 'pears', 5
+record 'apples', 1
+";
+    let mut koto = Koto::default();
+
+    // Host state can be shared with a function by capturing a pointer in a closure.
+    let totals = PtrMut::from(Vec::<(String, f64)>::new());
+
+    koto.prelude().add_fn("record", {
+        let totals = totals.clone();
+        move |ctx| match ctx.args() {
+            [KValue::Str(name), KValue::Number(n)] => {
+                let mut totals = totals.borrow_mut();
+                match totals
+                    .iter_mut()
+                    .find(|(existing, _)| existing == name.as_str())
+                {
+                    Some((_, total)) => *total += f64::from(n),
+                    None => totals.push((name.to_string(), n.into())),
+                }
+                Ok(KValue::Null)
+            }
+            unexpected => type_error_with_slice("a string and a number", unexpected),
+        }
+    });
+
+    koto.compile_and_run(script).unwrap();
+
+    for (name, total) in totals.borrow().iter() {
+        println!("{name}: {total}");
+    }
+}
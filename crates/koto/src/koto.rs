@@ -1,8 +1,11 @@
 use crate::{prelude::*, Error, Ptr, Result};
 use dunce::canonicalize;
 use koto_bytecode::CompilerSettings;
-use koto_runtime::ModuleImportedCallback;
+use koto_parser::Parser;
+use koto_runtime::{ModuleImportedCallback, ModuleLoader, TraceHook};
 use std::{
+    collections::HashMap,
+    fs,
     path::{Path, PathBuf},
     time::Duration,
 };
@@ -34,8 +37,10 @@ pub struct Koto {
     run_tests: bool,
     export_top_level_ids: bool,
     enable_type_checks: bool,
+    check_call_arg_counts: bool,
     script_path: Option<PathBuf>,
     chunk: Option<Ptr<Chunk>>,
+    named_chunks: HashMap<String, Ptr<Chunk>>,
 }
 
 impl Default for Koto {
@@ -57,11 +62,41 @@ impl Koto {
             run_tests: settings.run_tests,
             export_top_level_ids: settings.export_top_level_ids,
             enable_type_checks: settings.enable_type_checks,
+            check_call_arg_counts: settings.check_call_arg_counts,
             chunk: None,
+            named_chunks: HashMap::new(),
             script_path: None,
         }
     }
 
+    /// Returns a new Koto instance with an isolated copy of this instance's exports
+    ///
+    /// The new instance shares the runtime context of this instance, i.e. the prelude, settings,
+    /// and cache of imported modules are shared, along with the chunk to be used by
+    /// [run](Koto::run) and any [named chunks](Koto::compile_named).
+    ///
+    /// The exports are deep copied so that changes to exported containers made by either instance
+    /// aren't visible to the other. Note that this isn't free: every exported list, map, and tuple
+    /// is copied, so the cost grows with the amount of exported data. Values captured by exported
+    /// functions aren't copied.
+    pub fn clone_isolated(&self) -> Result<Self> {
+        let mut runtime = self.runtime.spawn_shared_vm();
+        if let KValue::Map(exports) = KValue::Map(self.runtime.exports().clone()).deep_copy()? {
+            *runtime.exports_mut() = exports;
+        }
+
+        Ok(Self {
+            runtime,
+            run_tests: self.run_tests,
+            export_top_level_ids: self.export_top_level_ids,
+            enable_type_checks: self.enable_type_checks,
+            check_call_arg_counts: self.check_call_arg_counts,
+            script_path: self.script_path.clone(),
+            chunk: self.chunk.clone(),
+            named_chunks: self.named_chunks.clone(),
+        })
+    }
+
     /// Returns a reference to the runtime's prelude
     pub fn prelude(&self) -> &KMap {
         self.runtime.prelude()
@@ -77,23 +112,95 @@ impl Koto {
         self.runtime.exports_mut()
     }
 
+    /// Returns a clone of the exported value with the given ID, if it exists
+    ///
+    /// Values are exported from the top level of a script with `export`, or when
+    /// [KotoSettings::export_top_level_ids] is enabled, by assigning to a top-level ID.
+    pub fn get_export(&self, id: &str) -> Option<KValue> {
+        self.runtime.exports().get(id)
+    }
+
     /// Compiles a Koto script, returning the complied chunk if successful
     ///
     /// On success, the chunk is cached as the current chunk for subsequent calls to [Koto::run].
     pub fn compile(&mut self, script: &str) -> Result<Ptr<Chunk>> {
-        let chunk = self.runtime.loader().borrow_mut().compile_script(
-            script,
-            self.script_path.as_deref(),
-            CompilerSettings {
-                export_top_level_ids: self.export_top_level_ids,
-                enable_type_checks: self.enable_type_checks,
-            },
-        )?;
+        let chunk = self.compile_chunk(script)?;
+        self.chunk = Some(chunk.clone());
+        Ok(chunk)
+    }
 
+    /// Loads a chunk that was serialized with [Chunk::to_bytes]
+    ///
+    /// The loaded chunk becomes the chunk that will be run by [run](Koto::run), in the same way
+    /// as a chunk compiled by [compile](Koto::compile), without the script needing to be parsed
+    /// and compiled.
+    ///
+    /// An error is returned if the chunk was serialized using a different format version or by a
+    /// different version of Koto, or if the data is corrupted.
+    pub fn load_chunk_bytes(&mut self, bytes: &[u8]) -> Result<Ptr<Chunk>> {
+        let chunk: Ptr<Chunk> = Chunk::from_bytes(bytes)
+            .map_err(|error| Error::from(format!("Failed to load chunk: {error}")))?
+            .into();
         self.chunk = Some(chunk.clone());
         Ok(chunk)
     }
 
+    /// Compiles a Koto script, and stores the compiled chunk with the given name
+    ///
+    /// The chunk can then be run with [run_named](Koto::run_named). Any number of named chunks can
+    /// be stored, with each chunk being run in the same environment, i.e. exported values are
+    /// shared between chunks. Compiling a script with a name that's already in use replaces the
+    /// previously compiled chunk.
+    ///
+    /// Compiling a named chunk doesn't change the chunk that will be run by [run](Koto::run).
+    pub fn compile_named(&mut self, name: &str, script: &str) -> Result<Ptr<Chunk>> {
+        let chunk = self.compile_chunk(script)?;
+        self.named_chunks.insert(name.into(), chunk.clone());
+        Ok(chunk)
+    }
+
+    /// Checks whether or not the given script is complete
+    ///
+    /// This is useful when implementing an interactive interface like a REPL, where an incomplete
+    /// script (e.g. `if x` without a body, or an unclosed parenthesis) indicates that more input
+    /// should be requested before the script is compiled.
+    ///
+    /// The script is parsed but not compiled, so some errors will only be reported when calling
+    /// [Koto::compile].
+    ///
+    /// ```
+    /// use koto::{CompletionStatus, Koto};
+    ///
+    /// assert_eq!(Koto::is_complete("x = 1 + 2"), CompletionStatus::Complete);
+    /// assert_eq!(Koto::is_complete("if x"), CompletionStatus::Incomplete);
+    /// assert_eq!(Koto::is_complete("x = 1 2"), CompletionStatus::Error);
+    /// ```
+    pub fn is_complete(script: &str) -> CompletionStatus {
+        match Parser::parse(script) {
+            Ok(_) => CompletionStatus::Complete,
+            Err(error) if error.is_unexpected_end_of_input() => CompletionStatus::Incomplete,
+            Err(_) => CompletionStatus::Error,
+        }
+    }
+
+    /// Loads and compiles the Koto script at the given path
+    ///
+    /// The script path is set via [set_script_path](Koto::set_script_path) before compiling, so
+    /// that compilation errors refer to the script's path.
+    ///
+    /// On success, the chunk is cached as the current chunk for subsequent calls to [Koto::run].
+    pub fn compile_from_path(&mut self, path: &Path) -> Result<Ptr<Chunk>> {
+        let script = fs::read_to_string(path).map_err(|e| {
+            Error::from(format!(
+                "Failed to load script '{}': {e}",
+                path.to_string_lossy()
+            ))
+        })?;
+
+        self.set_script_path(Some(path))?;
+        self.compile(&script)
+    }
+
     /// Runs the chunk last compiled with [compile](Koto::compile)
     pub fn run(&mut self) -> Result<KValue> {
         let chunk = self.chunk.clone();
@@ -103,6 +210,58 @@ impl Koto {
         }
     }
 
+    /// Runs the chunk last compiled with [compile](Koto::compile), and returns the exports map
+    ///
+    /// This is useful for config-style scripts, where the values defined by the script are of
+    /// interest rather than the script's result. When [KotoSettings::export_top_level_ids] is
+    /// enabled then each top-level assignment is included in the map, with reassigned IDs having
+    /// their final value.
+    ///
+    /// The returned map is shared with the runtime, see [exports](Koto::exports).
+    pub fn run_exports(&mut self) -> Result<KMap> {
+        self.run()?;
+        Ok(self.exports().clone())
+    }
+
+    /// Runs the chunk that was compiled with the given name by [compile_named](Koto::compile_named)
+    pub fn run_named(&mut self, name: &str) -> Result<KValue> {
+        match self.named_chunks.get(name).cloned() {
+            Some(chunk) => self.run_chunk(chunk),
+            None => runtime_error!("No script named '{name}' has been compiled"),
+        }
+    }
+
+    /// Runs a previously compiled chunk
+    ///
+    /// Chunks are self-contained, so a chunk compiled by one Koto instance can be run by any
+    /// other instance, with each instance having its own independent exports.
+    ///
+    /// Running a chunk doesn't change the chunk that will be run by [run](Koto::run).
+    pub fn run_chunk(&mut self, chunk: Ptr<Chunk>) -> Result<KValue> {
+        self.prepare_for_execution();
+        let result = self.runtime.run(chunk)?;
+
+        if self.run_tests {
+            let maybe_tests = self.runtime.exports().get_meta_value(&MetaKey::Tests);
+            match maybe_tests {
+                Some(KValue::Map(tests)) => {
+                    self.runtime.run_tests(tests)?;
+                }
+                Some(other) => {
+                    return type_error("test map", &other);
+                }
+                None => {}
+            }
+        }
+
+        let maybe_main = self.runtime.exports().get_meta_value(&MetaKey::Main);
+        if let Some(main) = maybe_main {
+            self.runtime.call_function(main, &[])
+        } else {
+            Ok(result)
+        }
+    }
+
     /// Compiles and runs a Koto script, and returns the script's result
     ///
     /// This is equivalent to calling [compile](Self::compile) followed by [run](Self::run).
@@ -111,30 +270,187 @@ impl Koto {
         self.run()
     }
 
+    /// Loads, compiles, and runs the Koto script at the given path, and returns the script's result
+    ///
+    /// This is equivalent to calling [compile_from_path](Self::compile_from_path) followed by
+    /// [run](Self::run), with both compilation and runtime errors referring to the script's path.
+    pub fn run_script_from_path(&mut self, path: &Path) -> Result<KValue> {
+        self.compile_from_path(path)?;
+        self.run()
+    }
+
     /// Calls a function with the given arguments
     ///
     /// If the provided value isn't [callable](KValue::is_callable) then an error will be returned.
+    ///
+    /// If [KotoSettings::check_call_arg_counts] is enabled, then an error will be returned when
+    /// the number of arguments doesn't match the function's arguments,
+    /// see [FunctionInfo::check_arg_count].
+    ///
+    /// Lists and maps can be conveniently prepared as arguments by collecting Rust values into a
+    /// [KList] or [KMap].
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use koto::prelude::*;
+    ///
+    /// fn main() -> koto::Result<()> {
+    ///     let mut koto = Koto::default();
+    ///     koto.compile_and_run(
+    ///         "
+    /// export main = |name, scores, options|
+    ///   total = scores.sum()
+    ///   name = if options.shout then name.to_uppercase() else name
+    ///   '{name}: {total}'
+    /// ",
+    ///     )?;
+    ///
+    ///     let main = koto.get_export("main").unwrap();
+    ///     let scores = KList::from_iter([1, 2, 3]);
+    ///     let options = KMap::from_iter([("shout", true)]);
+    ///     let result = koto.call_function(main, &["Alice".into(), scores.into(), options.into()])?;
+    ///
+    ///     assert_eq!(koto.value_to_string(result)?, "ALICE: 6");
+    ///     Ok(())
+    /// }
+    /// ```
     pub fn call_function<'a>(
         &mut self,
         function: KValue,
         args: impl Into<CallArgs<'a>>,
     ) -> Result<KValue> {
+        let args = args.into();
+        self.check_arg_count(&function, &args)?;
+        self.prepare_for_execution();
         self.runtime.call_function(function, args)
     }
 
+    /// Calls a function with the given arguments, deserializing the result as a `T`
+    ///
+    /// See [Koto::call_function] for details on how the function is called, and
+    /// [koto_serialize::from_koto_value] for details on how the result is deserialized.
+    ///
+    /// An error is returned if the result doesn't match `T`, e.g. if a field is missing from a map
+    /// that's being deserialized as a struct.
+    ///
+    /// Requires the `serde` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use koto::prelude::*;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Point {
+    ///     x: i64,
+    ///     y: i64,
+    /// }
+    ///
+    /// fn main() -> koto::Result<()> {
+    ///     let mut koto = Koto::default();
+    ///     koto.compile_and_run("export make_point = |x, y| {x, y}")?;
+    ///
+    ///     let make_point = koto.get_export("make_point").unwrap();
+    ///     let point: Point = koto.call_function_typed(make_point, &[1.into(), 2.into()])?;
+    ///
+    ///     assert_eq!((point.x, point.y), (1, 2));
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn call_function_typed<'a, T: serde::de::DeserializeOwned>(
+        &mut self,
+        function: KValue,
+        args: impl Into<CallArgs<'a>>,
+    ) -> Result<T> {
+        let result = self.call_function(function, args)?;
+        koto_serialize::from_koto_value(&result).map_err(|error| {
+            Error::from(format!(
+                "Failed to deserialize the function's result: {error}"
+            ))
+        })
+    }
+
     /// Calls an instance function with the given arguments
     ///
     /// If the provided value isn't [callable](KValue::is_callable) then an error will be returned.
+    ///
+    /// See [Koto::call_function] for details on argument count checks.
     pub fn call_instance_function<'a>(
         &mut self,
         instance: KValue,
         function: KValue,
         args: impl Into<CallArgs<'a>>,
     ) -> Result<KValue> {
+        let args = args.into();
+        self.check_arg_count(&function, &args)?;
+        self.prepare_for_execution();
         self.runtime
             .call_instance_function(instance, function, args)
     }
 
+    /// Returns a handle that can be used to interrupt execution from another thread
+    ///
+    /// Calling [interrupt](KotoInterrupt::interrupt) on the handle causes the active call into the
+    /// runtime to end with an [Interrupted](crate::ErrorKind::Interrupted) error.
+    /// The interruption is cleared at the start of the next call, so the Koto instance can
+    /// continue to be used.
+    ///
+    /// ```
+    /// use koto::{prelude::*, ErrorKind};
+    ///
+    /// let mut koto = Koto::default();
+    /// let interrupt = koto.interrupt_handle();
+    ///
+    /// koto.prelude().add_fn("stop", move |_| {
+    ///     interrupt.interrupt();
+    ///     Ok(KValue::Null)
+    /// });
+    ///
+    /// let error = koto.compile_and_run("stop()\nloop\n  ()").unwrap_err();
+    /// assert!(matches!(error.error, ErrorKind::Interrupted));
+    /// ```
+    pub fn interrupt_handle(&self) -> KotoInterrupt {
+        self.runtime.interrupt_handle()
+    }
+
+    /// Sets a breakpoint on a line in the chunk last compiled with [compile](Koto::compile)
+    ///
+    /// The line is one-based, matching the line numbers shown in error messages.
+    /// When execution reaches the breakpoint, the callback provided via
+    /// [KotoSettings::with_breakpoint_callback] is called.
+    ///
+    /// An error is returned if there's no compiled chunk, or if the line doesn't contain any
+    /// instructions.
+    pub fn set_breakpoint(&mut self, line: u32) -> Result<()> {
+        match self.chunk.clone() {
+            Some(chunk) => self.runtime.set_breakpoint(chunk, line),
+            None => runtime_error!("Unable to set a breakpoint, no chunk has been compiled"),
+        }
+    }
+
+    /// Removes all breakpoints that were set with [set_breakpoint](Koto::set_breakpoint)
+    pub fn clear_breakpoints(&mut self) {
+        self.runtime.clear_breakpoints();
+    }
+
+    /// Returns a report containing the profiling data collected while running scripts
+    ///
+    /// `None` is returned if profiling wasn't enabled with [KotoSettings::with_profiling].
+    ///
+    /// The report accumulates data from every script and function that has been run,
+    /// [reset_profile](Koto::reset_profile) can be used to clear the collected data.
+    pub fn profile_report(&self) -> Option<ProfileReport> {
+        self.runtime.profile_report()
+    }
+
+    /// Clears the profiling data that has been collected
+    pub fn reset_profile(&mut self) {
+        self.runtime.reset_profile();
+    }
+
     /// Converts a [KValue] into a [String] by evaluating `@display` in the runtime
     pub fn value_to_string(&mut self, value: KValue) -> Result<String> {
         self.runtime.value_to_string(&value)
@@ -149,20 +465,21 @@ impl Koto {
 
     /// Sets the arguments that can be accessed from within the script via `koto.args()`
     pub fn set_args(&mut self, args: &[String]) -> Result<()> {
-        use KValue::{Map, Str, Tuple};
-
         let koto_args = args
             .iter()
-            .map(|arg| Str(arg.as_str().into()))
+            .map(|arg| KValue::Str(arg.as_str().into()))
             .collect::<Vec<_>>();
 
-        match self.runtime.prelude().data_mut().get("koto") {
-            Some(Map(map)) => {
-                map.insert("args", Tuple(koto_args.into()));
-                Ok(())
-            }
-            _ => runtime_error!("missing koto module in the prelude"),
-        }
+        self.set_args_values(&koto_args)
+    }
+
+    /// Sets the arguments that can be accessed from within the script via `koto.args()`
+    ///
+    /// Unlike [set_args](Koto::set_args), the arguments can be any Koto values.
+    pub fn set_args_values(&mut self, args: &[KValue]) -> Result<()> {
+        self.koto_module()?
+            .insert("args", KValue::Tuple(args.into()));
+        Ok(())
     }
 
     /// Enables or disables the `run_tests` setting
@@ -175,7 +492,7 @@ impl Koto {
 
     /// Sets the path of the current script, accessible via `koto.script_dir` / `koto.script_path`
     pub fn set_script_path(&mut self, path: Option<&Path>) -> Result<()> {
-        use KValue::{Map, Null, Str};
+        use KValue::{Null, Str};
 
         let (script_dir, script_path) = match &path {
             Some(path) => {
@@ -199,41 +516,60 @@ impl Koto {
 
         self.script_path = path.map(Path::to_path_buf);
 
-        match self.runtime.prelude().data_mut().get("koto") {
-            Some(Map(map)) => {
-                map.insert("script_dir", script_dir);
-                map.insert("script_path", script_path);
-                Ok(())
-            }
-            _ => runtime_error!("missing koto module in the prelude"),
-        }
+        let koto_module = self.koto_module()?;
+        koto_module.insert("script_dir", script_dir);
+        koto_module.insert("script_path", script_path);
+        Ok(())
     }
 
-    fn run_chunk(&mut self, chunk: Ptr<Chunk>) -> Result<KValue> {
-        let result = self.runtime.run(chunk)?;
+    fn compile_chunk(&mut self, script: &str) -> Result<Ptr<Chunk>> {
+        let chunk = self.runtime.loader().borrow_mut().compile_script(
+            script,
+            self.script_path.as_deref(),
+            CompilerSettings {
+                export_top_level_ids: self.export_top_level_ids,
+                enable_type_checks: self.enable_type_checks,
+            },
+        )?;
+        Ok(chunk)
+    }
 
-        if self.run_tests {
-            let maybe_tests = self.runtime.exports().get_meta_value(&MetaKey::Tests);
-            match maybe_tests {
-                Some(KValue::Map(tests)) => {
-                    self.runtime.run_tests(tests)?;
-                }
-                Some(other) => {
-                    return type_error("test map", &other);
-                }
-                None => {}
+    // Checks the number of args provided to a function, if enabled by the settings
+    fn check_arg_count(&self, function: &KValue, args: &CallArgs) -> Result<()> {
+        if self.check_call_arg_counts {
+            if let Some(info) = function.function_info() {
+                info.check_arg_count(args.arg_count())?;
             }
         }
+        Ok(())
+    }
 
-        let maybe_main = self.runtime.exports().get_meta_value(&MetaKey::Main);
-        if let Some(main) = maybe_main {
-            self.runtime.call_function(main, &[])
-        } else {
-            Ok(result)
+    // Resets the runtime's per-call state before a call into the runtime
+    fn prepare_for_execution(&self) {
+        self.runtime.reset_memory_usage();
+        self.runtime.clear_interrupt();
+    }
+
+    // Returns the `koto` module from the prelude
+    fn koto_module(&self) -> Result<KMap> {
+        match self.runtime.prelude().get("koto") {
+            Some(KValue::Map(map)) => Ok(map),
+            _ => runtime_error!("missing koto module in the prelude"),
         }
     }
 }
 
+/// The result of checking a script with [Koto::is_complete]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompletionStatus {
+    /// The script was parsed successfully
+    Complete,
+    /// The script ended before parsing was complete, more input is needed
+    Incomplete,
+    /// The script contains an error that additional input won't resolve
+    Error,
+}
+
 /// Settings used to control the behaviour of the [Koto] runtime
 pub struct KotoSettings {
     /// Whether or not tests should be run when loading a script
@@ -251,6 +587,11 @@ pub struct KotoSettings {
     ///
     /// Enabled by default.
     pub enable_type_checks: bool,
+    /// When enabled, [Koto::call_function] and [Koto::call_instance_function] will check that
+    /// the number of provided arguments matches the called function's arguments
+    ///
+    /// Disabled by default.
+    pub check_call_arg_counts: bool,
     /// Settings that apply to the runtime
     pub vm_settings: KotoVmSettings,
 }
@@ -268,6 +609,48 @@ impl KotoSettings {
         }
     }
 
+    /// Helper for conveniently defining a limit on the memory used during execution
+    ///
+    /// See [KotoVmSettings::memory_limit].
+    #[must_use]
+    pub fn with_memory_limit(self, bytes: usize) -> Self {
+        Self {
+            vm_settings: KotoVmSettings {
+                memory_limit: Some(bytes),
+                ..self.vm_settings
+            },
+            ..self
+        }
+    }
+
+    /// Enables or disables the runtime's profiler
+    ///
+    /// See [KotoVmSettings::profiling] and [Koto::profile_report].
+    #[must_use]
+    pub fn with_profiling(self, enabled: bool) -> Self {
+        Self {
+            vm_settings: KotoVmSettings {
+                profiling: enabled,
+                ..self.vm_settings
+            },
+            ..self
+        }
+    }
+
+    /// Helper for conveniently defining a custom filesystem implementation
+    ///
+    /// See [KotoVmSettings::filesystem].
+    #[must_use]
+    pub fn with_filesystem(self, filesystem: impl KotoFilesystem + 'static) -> Self {
+        Self {
+            vm_settings: KotoVmSettings {
+                filesystem: make_ptr!(filesystem),
+                ..self.vm_settings
+            },
+            ..self
+        }
+    }
+
     /// Helper for conveniently defining a custom stdin implementation
     #[must_use]
     pub fn with_stdin(self, stdin: impl KotoFile + 'static) -> Self {
@@ -318,6 +701,46 @@ impl KotoSettings {
             ..self
         }
     }
+
+    /// Convenience function for declaring a custom module loader
+    #[must_use]
+    pub fn with_module_loader(self, loader: impl ModuleLoader + 'static) -> Self {
+        Self {
+            vm_settings: KotoVmSettings {
+                module_loader: Some(Box::new(loader)),
+                ..self.vm_settings
+            },
+            ..self
+        }
+    }
+
+    /// Convenience function for declaring a hook that's called before each instruction
+    ///
+    /// See [KotoVmSettings::trace_hook].
+    #[must_use]
+    pub fn with_trace_hook(self, hook: impl TraceHook + 'static) -> Self {
+        Self {
+            vm_settings: KotoVmSettings {
+                trace_hook: Some(Box::new(hook)),
+                ..self.vm_settings
+            },
+            ..self
+        }
+    }
+
+    /// Convenience function for declaring a callback that's called when a breakpoint is reached
+    ///
+    /// See [KotoVmSettings::breakpoint_callback] and [Koto::set_breakpoint].
+    #[must_use]
+    pub fn with_breakpoint_callback(self, callback: impl TraceHook + 'static) -> Self {
+        Self {
+            vm_settings: KotoVmSettings {
+                breakpoint_callback: Some(Box::new(callback)),
+                ..self.vm_settings
+            },
+            ..self
+        }
+    }
 }
 
 impl Default for KotoSettings {
@@ -326,6 +749,7 @@ impl Default for KotoSettings {
             run_tests: true,
             export_top_level_ids: false,
             enable_type_checks: true,
+            check_call_arg_counts: false,
             vm_settings: KotoVmSettings::default(),
         }
     }
@@ -34,6 +34,10 @@ pub mod prelude;
 pub use koto_bytecode as bytecode;
 pub use koto_parser as parser;
 pub use koto_runtime as runtime;
-pub use koto_runtime::{derive, Borrow, BorrowMut, Error, ErrorKind, Ptr, PtrMut, Result};
+pub use koto_runtime::{
+    derive, Borrow, BorrowMut, Error, ErrorKind, ErrorLocation, Ptr, PtrMut, Result,
+};
+#[cfg(feature = "serde")]
+pub use koto_serialize as serialize;
 
-pub use crate::koto::{Koto, KotoSettings};
+pub use crate::koto::{CompletionStatus, Koto, KotoSettings};
@@ -39,8 +39,14 @@ fn check_cli_run_file(
     )
 }
 
-fn check_cli_piped_input(input: &'static str, expected_stdout: &str, expected_stderr: &str) {
+fn check_cli_piped_input(
+    input: &'static str,
+    args: &[&str],
+    expected_stdout: &str,
+    expected_stderr: &str,
+) {
     let mut cli = test_bin::get_test_bin("koto")
+        .args(args)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
@@ -98,7 +104,41 @@ mod cli {
 square = |x| x * x
 print square 9
 ";
-            check_cli_piped_input(input, "81\n", "");
+            check_cli_piped_input(input, &[], "81\n", "");
+        }
+
+        #[test]
+        fn show_bytecode_and_instructions() {
+            let input = "
+x = 1 + 2
+print x
+";
+            let expected = "\
+05 02 06 03  02 23 01 02  03 0b 03 01  00 05 01 37
+02 03 04 01  38 02 
+
+Constants
+---------
+0\tString\tx
+1\tString\tprint
+
+
+Instructions
+------------
+|1| x = 1 + 2
+0\tSetNumber\tresult: 2\tvalue: 1
+2\tSetNumber\tresult: 3\tvalue: 2
+5\tAdd\t\tresult: 1\tlhs: 2\t\trhs: 3
+
+|2| print x
+9\tLoadNonLocal\tresult: 3\tconstant: 1
+12\tCopy\t\tresult: 5\tsource: 1
+15\tCall\t\tresult: 2\tfunction: 3\tframe base: 4\targs: 1
+20\tReturn\t\tresult: 2
+
+3
+";
+            check_cli_piped_input(input, &["-b", "-i"], expected, "");
         }
     }
 }
@@ -0,0 +1,1712 @@
+use koto::{prelude::*, Ptr};
+
+fn check_number(value: Option<KValue>, expected: i64) {
+    match value {
+        Some(KValue::Number(n)) if n == expected => {}
+        other => panic!("Expected {expected}, found {other:?}"),
+    }
+}
+
+mod run_chunk {
+    use super::*;
+
+    const SCRIPT: &str = "
+export total = input * 10
+total
+";
+
+    fn run_with_input(chunk: Ptr<Chunk>, input: i64) -> (KValue, KMap) {
+        let mut koto = Koto::default();
+        koto.exports().insert("input", input);
+        let result = koto.run_chunk(chunk).unwrap();
+        (result, koto.exports().clone())
+    }
+
+    #[test]
+    fn shared_chunk_with_independent_exports() {
+        let chunk = Koto::default().compile(SCRIPT).unwrap();
+
+        let (result_a, exports_a) = run_with_input(chunk.clone(), 1);
+        let (result_b, exports_b) = run_with_input(chunk, 2);
+
+        check_number(Some(result_a), 10);
+        check_number(Some(result_b), 20);
+        check_number(exports_a.get("total"), 10);
+        check_number(exports_b.get("total"), 20);
+    }
+
+    #[cfg(feature = "arc")]
+    #[test]
+    fn shared_chunk_on_multiple_threads() {
+        let chunk = Koto::default().compile(SCRIPT).unwrap();
+
+        std::thread::scope(|s| {
+            let workers = (0..4)
+                .map(|i| {
+                    let chunk = chunk.clone();
+                    s.spawn(move || {
+                        let (result, _) = run_with_input(chunk, i);
+                        check_number(Some(result), i * 10);
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            for worker in workers {
+                worker.join().unwrap();
+            }
+        });
+    }
+
+    #[test]
+    fn run_chunk_does_not_replace_the_current_chunk() {
+        let mut koto = Koto::default();
+        koto.compile("42").unwrap();
+
+        let other = Koto::default().compile("99").unwrap();
+        check_number(Some(koto.run_chunk(other).unwrap()), 99);
+        check_number(Some(koto.run().unwrap()), 42);
+    }
+}
+
+mod exports {
+    use super::*;
+
+    const SCRIPT: &str = "
+export config =
+  width: 640
+  size:
+    height: 480
+";
+
+    fn check_config(koto: &Koto) {
+        let Some(KValue::Map(config)) = koto.get_export("config") else {
+            panic!("Expected config to be exported as a map");
+        };
+        check_number(config.get("width"), 640);
+
+        let Some(KValue::Map(size)) = config.get("size") else {
+            panic!("Expected config.size to be a map");
+        };
+        check_number(size.get("height"), 480);
+    }
+
+    #[test]
+    fn get_export() {
+        let mut koto = Koto::default();
+        koto.compile_and_run(SCRIPT).unwrap();
+
+        check_config(&koto);
+        assert!(koto.get_export("missing").is_none());
+    }
+
+    #[test]
+    fn get_export_with_top_level_ids_exported() {
+        let mut koto = Koto::with_settings(KotoSettings {
+            export_top_level_ids: true,
+            ..Default::default()
+        });
+
+        // Top-level assignments are exported without needing `export`,
+        // and remain available to later scripts.
+        koto.compile_and_run(&SCRIPT.replace("export ", ""))
+            .unwrap();
+        koto.compile_and_run("x = config.width * 2").unwrap();
+
+        check_config(&koto);
+        check_number(koto.get_export("x"), 1280);
+    }
+
+    #[test]
+    fn run_exports() {
+        let mut koto = Koto::with_settings(KotoSettings {
+            export_top_level_ids: true,
+            ..Default::default()
+        });
+        koto.compile(
+            "\
+width = 800
+height = 600
+width = width * 2
+scale = width / height
+",
+        )
+        .unwrap();
+
+        let exports = koto.run_exports().unwrap();
+        check_number(exports.get("width"), 1600);
+        check_number(exports.get("height"), 600);
+        match exports.get("scale") {
+            Some(KValue::Number(n)) => assert_eq!(f64::from(n), 1600.0 / 600.0),
+            other => panic!("Expected a number, found {other:?}"),
+        }
+    }
+}
+
+mod prelude {
+    use super::*;
+    use koto::PtrMut;
+
+    #[test]
+    fn function_with_captured_state() {
+        let mut koto = Koto::default();
+        let calls = PtrMut::from(Vec::new());
+
+        koto.prelude().add_fn("record", {
+            let calls = calls.clone();
+            move |ctx| {
+                calls.borrow_mut().push(ctx.args().len());
+                Ok(KValue::Null)
+            }
+        });
+
+        koto.compile_and_run("record 1\nrecord 2, 3").unwrap();
+        assert_eq!(*calls.borrow(), [1, 2]);
+    }
+}
+
+mod compile_from_path {
+    use super::*;
+    use std::{fs, path::Path};
+
+    #[test]
+    fn valid_script() {
+        let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("compile_from_path_valid.koto");
+        fs::write(&path, "export x = koto.script_path\n40 + 2").unwrap();
+
+        let mut koto = Koto::default();
+        koto.compile_from_path(&path).unwrap();
+        check_number(Some(koto.run().unwrap()), 42);
+
+        match koto.get_export("x") {
+            Some(KValue::Str(script_path)) => {
+                assert!(script_path.ends_with("compile_from_path_valid.koto"))
+            }
+            other => panic!("Expected the script path, found {other:?}"),
+        }
+    }
+
+    #[test]
+    fn missing_script() {
+        let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("compile_from_path_missing.koto");
+
+        let error = Koto::default().compile_from_path(&path).unwrap_err();
+        assert!(!error.is_compile_error());
+        let expected = format!("Failed to load script '{}'", path.to_string_lossy());
+        assert!(error.to_string().starts_with(&expected));
+    }
+
+    #[test]
+    fn script_with_compile_error() {
+        let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("compile_from_path_error.koto");
+        fs::write(&path, "x = (1 +").unwrap();
+
+        let error = Koto::default().compile_from_path(&path).unwrap_err();
+        assert!(error.is_compile_error());
+        assert_eq!(error.source_location().unwrap().path, Some(path.as_path()));
+        assert!(error.to_string().contains("compile_from_path_error.koto"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_path() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let file_name = OsStr::from_bytes(b"compile_from_path_\xff.koto");
+        let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(file_name);
+        fs::write(&path, "export x = koto.script_path\n99").unwrap();
+
+        let mut koto = Koto::default();
+        koto.compile_from_path(&path).unwrap();
+        check_number(Some(koto.run().unwrap()), 99);
+
+        // The invalid byte is replaced in the lossy conversion to a string
+        match koto.get_export("x") {
+            Some(KValue::Str(script_path)) => {
+                assert!(script_path.ends_with("compile_from_path_\u{FFFD}.koto"))
+            }
+            other => panic!("Expected the script path, found {other:?}"),
+        }
+    }
+
+    #[test]
+    fn run_script_from_path() {
+        let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("run_script_from_path.koto");
+        fs::write(&path, "x = 40\nx + 2").unwrap();
+
+        let result = Koto::default().run_script_from_path(&path).unwrap();
+        check_number(Some(result), 42);
+    }
+
+    #[test]
+    fn run_script_from_path_with_runtime_error() {
+        let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("run_script_from_path_error.koto");
+        fs::write(&path, "x = 1\nx.foo()").unwrap();
+
+        let error = Koto::default().run_script_from_path(&path).unwrap_err();
+        assert!(!error.is_compile_error());
+        let location = error.source_location().unwrap();
+        assert_eq!(location.path, Some(path.as_path()));
+        assert_eq!(location.span.start.line, 1);
+    }
+}
+
+mod execution_limit {
+    use super::*;
+    use koto::ErrorKind;
+    use std::time::Duration;
+
+    fn make_koto() -> Koto {
+        Koto::with_settings(KotoSettings::default().with_execution_limit(Duration::from_millis(10)))
+    }
+
+    fn check_timeout(result: koto::Result<KValue>) {
+        match result {
+            Err(error) if matches!(error.error, ErrorKind::Timeout(_)) => {
+                assert!(error.source_location().is_some());
+            }
+            Err(error) => panic!("Expected a timeout, found error: {error}"),
+            Ok(_) => panic!("Expected a timeout"),
+        }
+    }
+
+    #[test]
+    fn infinite_loop() {
+        check_timeout(make_koto().compile_and_run("while true\n  ()"));
+    }
+
+    #[test]
+    fn call_function() {
+        let mut koto = make_koto();
+        koto.compile_and_run("export f = || loop\n  ()").unwrap();
+
+        let f = koto.get_export("f").unwrap();
+        check_timeout(koto.call_function(f, &[]));
+    }
+
+    #[test]
+    fn callback_from_rust_function() {
+        let mut koto = make_koto();
+        koto.prelude().add_fn("call", |ctx| match ctx.args() {
+            [f] => {
+                let f = f.clone();
+                ctx.vm.call_function(f, &[])
+            }
+            unexpected => type_error_with_slice("a function", unexpected),
+        });
+
+        check_timeout(koto.compile_and_run("call || loop\n  ()"));
+    }
+}
+
+mod nested_calls {
+    use super::*;
+
+    // Makes a Koto instance with two Rust functions that call the function they're given with
+    // the remaining args
+    fn make_koto() -> Koto {
+        let koto = Koto::default();
+
+        for name in ["rust_a", "rust_b"] {
+            koto.prelude().add_fn(name, |ctx| match ctx.args() {
+                [f, args @ ..] if f.is_callable() => {
+                    let f = f.clone();
+                    let args = args.to_vec();
+                    ctx.vm.call_function(f, args.as_slice())
+                }
+                unexpected => type_error_with_slice("a function followed by arguments", unexpected),
+            });
+        }
+
+        koto
+    }
+
+    #[test]
+    fn alternating_rust_and_script_calls() {
+        let mut koto = make_koto();
+        let script = "
+# Calls alternate between rust_a and rust_b, with the locals in each script frame checked after
+# the nested calls have completed
+f = |n|
+  x = n * 2
+  result = if n == 0
+    0
+  else if n % 2 == 0
+    rust_a f, n - 1
+  else
+    rust_b f, n - 1
+  assert_eq x, n * 2
+  result + 1
+
+rust_a f, 200
+";
+        check_number(Some(koto.compile_and_run(script).unwrap()), 201);
+    }
+
+    #[test]
+    fn nested_calls_with_multiple_args() {
+        let mut koto = make_koto();
+        let script = "
+f = |n, acc, items|
+  if n == 0
+    acc, (size items)
+  else
+    items.push n
+    rust_a f, n - 1, acc + n, items
+
+a, b = rust_b f, 100, 0, []
+assert_eq a, 5050
+assert_eq b, 100
+a
+";
+        check_number(Some(koto.compile_and_run(script).unwrap()), 5050);
+    }
+
+    #[test]
+    fn error_in_deeply_nested_call() {
+        let mut koto = make_koto();
+        let script = "
+f = |n|
+  if n == 0
+    throw 'deep error'
+  x = n
+  result = rust_a f, n - 1
+  x + result
+
+result = try
+  rust_b f, 100
+catch error
+  error
+assert_eq result, 'deep error'
+
+# The VM is still usable after the error has been caught
+rust_a (|n| rust_b (|m| m * 2), n), 21
+";
+        check_number(Some(koto.compile_and_run(script).unwrap()), 42);
+
+        // The VM can also be reused after an uncaught error from a nested call
+        assert!(koto
+            .compile_and_run("rust_a (|n| rust_b (|| throw 'x'), n), 1")
+            .is_err());
+        check_number(
+            Some(koto.compile_and_run("rust_a (|n| n + 1), 41").unwrap()),
+            42,
+        );
+    }
+
+    #[test]
+    fn nested_calls_from_core_library_functions() {
+        let mut koto = make_koto();
+        let script = "
+x = [3, 1, 2]
+# A comparison function that calls back into Rust, which calls a function that uses another
+# core library function with a callback
+x.sort_by |a, b| rust_a (|a, b| [a, b].each(|n| rust_b (|m| m), n).to_list()[0] < b), a, b
+assert_eq x, [1, 2, 3]
+size x
+";
+        check_number(Some(koto.compile_and_run(script).unwrap()), 3);
+    }
+}
+
+mod output {
+    use super::*;
+    use koto_test_utils::OutputCapture;
+
+    #[test]
+    fn stdout_and_stderr_captured_in_order() {
+        let output = OutputCapture::default();
+        let mut koto = Koto::with_settings(
+            KotoSettings::default()
+                .with_stdout(output.clone())
+                .with_stderr(output.clone()),
+        );
+
+        let script = "
+print 'a'
+io.stderr().write 'b'
+io.stdout().write_line 'c'
+print ''
+";
+        koto.compile_and_run(script).unwrap();
+        assert_eq!(output.captured_output().as_str(), "a\nbc\n\n");
+    }
+}
+
+mod prelude_modules {
+    use super::*;
+
+    #[test]
+    fn removed_module_is_unavailable() {
+        let mut koto = Koto::default();
+        koto.prelude().remove("io");
+
+        let result = koto.compile_and_run("io.read_to_string 'foo.txt'");
+        assert!(result.is_err());
+
+        // Other core library modules remain available
+        let result = koto.compile_and_run("string.to_uppercase 'abc'").unwrap();
+        assert!(matches!(result, KValue::Str(s) if s == "ABC"));
+    }
+}
+
+mod module_loader {
+    use super::*;
+    use std::path::{Path, PathBuf};
+
+    fn load_module(name: &str, importer: Option<&Path>) -> koto::Result<Option<LoadedModule>> {
+        let script = match name {
+            "foo" => {
+                "
+from bar import square
+export result = square 9
+"
+            }
+            "bar" => {
+                "
+export square = |n| n * n
+"
+            }
+            "constants" => {
+                let module = KMap::with_type("constants");
+                module.insert("answer", 42);
+                return Ok(Some(LoadedModule::Map(module)));
+            }
+            "invalid" => "x = (1 +",
+            "error" => return runtime_error!("unavailable (imported from {importer:?})"),
+            _ => return Ok(None),
+        };
+
+        Ok(Some(LoadedModule::Script {
+            path: PathBuf::from(format!("memory/{name}.koto")),
+            script: script.into(),
+        }))
+    }
+
+    fn make_koto() -> Koto {
+        Koto::with_settings(KotoSettings::default().with_module_loader(load_module))
+    }
+
+    #[test]
+    fn module_importing_another_module() {
+        let mut koto = make_koto();
+        let result = koto.compile_and_run("import foo\nfoo.result").unwrap();
+        check_number(Some(result), 81);
+    }
+
+    #[test]
+    fn module_provided_as_map() {
+        let mut koto = make_koto();
+        let result = koto
+            .compile_and_run("from constants import answer\nanswer")
+            .unwrap();
+        check_number(Some(result), 42);
+    }
+
+    #[test]
+    fn compile_error_in_loaded_module() {
+        let error = make_koto().compile_and_run("import invalid").unwrap_err();
+        assert!(error.to_string().contains("memory/invalid.koto"));
+    }
+
+    #[test]
+    fn loader_error() {
+        let error = make_koto()
+            .compile_and_run("x = 1\nimport error")
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Failed to import 'error': unavailable"));
+        assert_eq!(error.source_location().unwrap().span.start.line, 1);
+    }
+
+    #[test]
+    fn fall_back_to_file_system() {
+        let error = make_koto().compile_and_run("import missing").unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Unable to find module 'missing'"));
+    }
+}
+
+mod clone_isolated {
+    use super::*;
+
+    const SCRIPT: &str = "
+export items = [1, 2, 3]
+export config = {scale: 1}
+";
+
+    #[test]
+    fn mutating_exports_in_clone() {
+        let mut koto = Koto::default();
+        koto.compile_and_run(SCRIPT).unwrap();
+
+        let mut clone = koto.clone_isolated().unwrap();
+        clone
+            .compile_and_run("items.push 4\nconfig.scale = 2\nexport extra = 99")
+            .unwrap();
+        check_number(Some(clone.compile_and_run("size items").unwrap()), 4);
+
+        // The original instance's exports are unchanged
+        check_number(Some(koto.compile_and_run("size items").unwrap()), 3);
+        check_number(Some(koto.compile_and_run("config.scale").unwrap()), 1);
+        assert!(koto.get_export("extra").is_none());
+    }
+
+    #[cfg(feature = "arc")]
+    #[test]
+    fn clones_on_multiple_threads() {
+        let mut koto = Koto::default();
+        koto.compile_and_run(SCRIPT).unwrap();
+
+        std::thread::scope(|s| {
+            for i in 0..4 {
+                let mut clone = koto.clone_isolated().unwrap();
+                s.spawn(move || {
+                    let script = format!("for _ in 0..{i}\n  items.push 0\nsize items");
+                    let result = clone.compile_and_run(&script).unwrap();
+                    check_number(Some(result), 3 + i);
+                });
+            }
+        });
+
+        check_number(Some(koto.compile_and_run("size items").unwrap()), 3);
+    }
+}
+
+mod args {
+    use super::*;
+
+    #[test]
+    fn set_args_values() {
+        let mut koto = Koto::default();
+        let options = KMap::from_iter([("scale", 3)]);
+        koto.set_args_values(&[options.into(), KList::from_iter([1, 2, 3]).into()])
+            .unwrap();
+
+        let script = "
+options, numbers = koto.args
+options.scale * numbers.sum()
+";
+        check_number(Some(koto.compile_and_run(script).unwrap()), 18);
+    }
+}
+
+mod error_locations {
+    use super::*;
+    use koto::parser::format_source_excerpt;
+
+    fn check_location(error: &koto::Error, expected_line: u32, expected_column: u32) {
+        let location = error.source_location().unwrap();
+
+        // Spans are zero-based, while lines and columns are displayed as one-based
+        assert_eq!(location.span.start.line + 1, expected_line);
+        assert_eq!(location.span.start.column + 1, expected_column);
+
+        // The location can be used to render an excerpt that matches the error's display
+        let excerpt = format_source_excerpt(location.source, &location.span, location.path);
+        assert!(excerpt.starts_with(&format!("{expected_line}:{expected_column}")));
+        assert!(error.to_string().contains(&excerpt));
+    }
+
+    #[test]
+    fn compile_error() {
+        let error = Koto::default().compile("x = 1\ny = (1 + )").unwrap_err();
+        assert!(error.is_compile_error());
+        check_location(&error, 2, 10);
+    }
+
+    #[test]
+    fn runtime_error() {
+        let error = Koto::default()
+            .compile_and_run("x = 1\ny = 2\nz =   x + 'three'")
+            .unwrap_err();
+        assert!(!error.is_compile_error());
+        check_location(&error, 3, 7);
+    }
+
+    #[test]
+    fn comparison_of_incomparable_values() {
+        let error = Koto::default()
+            .compile_and_run("x = {}\ny =   x < 1")
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Unable to perform operation '<' with 'Map' and 'Number'"));
+        check_location(&error, 2, 7);
+    }
+
+    #[test]
+    fn sorting_incomparable_values() {
+        let mut koto = Koto::default();
+        let error = koto.compile_and_run("x = [{}, 1]\nx.sort()").unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Unable to perform operation '<' with 'Number' and 'Map'"));
+        check_location(&error, 2, 3);
+
+        // The runtime remains usable after the error
+        check_number(
+            Some(koto.compile_and_run("[3, 1, 2].sort().first()").unwrap()),
+            1,
+        );
+    }
+}
+
+mod is_complete {
+    use super::*;
+
+    fn check_status(scripts: &[&str], expected: CompletionStatus) {
+        for script in scripts {
+            assert_eq!(Koto::is_complete(script), expected, "script: {script:?}");
+        }
+    }
+
+    #[test]
+    fn complete() {
+        check_status(
+            &[
+                "",
+                "x = 1 + 2",
+                "if x then 1 else 2",
+                "f = |x|\n  x * 2\nf 3",
+                "'hello {1 + 1}'\n",
+            ],
+            CompletionStatus::Complete,
+        );
+    }
+
+    #[test]
+    fn dangling_blocks() {
+        check_status(
+            &[
+                "if x",
+                "if x\n",
+                "for x in y",
+                "f = |x|",
+                "x = 1 +",
+                "match x",
+                "try",
+            ],
+            CompletionStatus::Incomplete,
+        );
+    }
+
+    #[test]
+    fn unclosed_delimiters() {
+        check_status(
+            &["x = (1, 2", "f(1,", "[1, 2", "{a: 1", "x[0", "f = |a, b"],
+            CompletionStatus::Incomplete,
+        );
+    }
+
+    #[test]
+    fn unclosed_strings() {
+        check_status(
+            &["'abc", "x = \"abc", "'abc\ndef", "'abc {x", "x = 'abc {x}"],
+            CompletionStatus::Incomplete,
+        );
+    }
+
+    #[test]
+    fn errors() {
+        check_status(
+            &[
+                "x = 1 2",
+                "x = )",
+                "(1, 2]",
+                "x = (1, 2\ny = 3]",
+                "f(1, 2 3",
+            ],
+            CompletionStatus::Error,
+        );
+    }
+
+    #[test]
+    fn error_before_unexpected_end_of_input() {
+        check_status(&["x = )\nif y"], CompletionStatus::Error);
+    }
+}
+
+mod named_chunks {
+    use super::*;
+
+    #[test]
+    fn scripts_share_exports() {
+        let mut koto = Koto::default();
+        koto.compile_named("init", "export x = 40").unwrap();
+        koto.compile_named("update", "export x = x + 2").unwrap();
+
+        koto.run_named("init").unwrap();
+        koto.run_named("update").unwrap();
+        check_number(koto.get_export("x"), 42);
+
+        koto.run_named("update").unwrap();
+        check_number(koto.get_export("x"), 44);
+    }
+
+    #[test]
+    fn recompiling_replaces_the_chunk() {
+        let mut koto = Koto::default();
+        koto.compile_named("main", "1").unwrap();
+        koto.compile_named("main", "2").unwrap();
+        check_number(koto.run_named("main").ok(), 2);
+    }
+
+    #[test]
+    fn unnamed_chunk_is_unaffected() {
+        let mut koto = Koto::default();
+        koto.compile("1").unwrap();
+        koto.compile_named("other", "2").unwrap();
+        check_number(koto.run().ok(), 1);
+    }
+
+    #[test]
+    fn missing_chunk() {
+        let mut koto = Koto::default();
+        assert!(koto.run_named("missing").is_err());
+    }
+
+    #[test]
+    fn runtime_error_from_earlier_chunk() {
+        let mut koto = Koto::default();
+        koto.compile_named(
+            "handlers",
+            "\
+export on_update = |n|
+  n + 'oops'
+",
+        )
+        .unwrap();
+        koto.compile_named("update", "x = 1\nx = on_update x")
+            .unwrap();
+
+        koto.run_named("handlers").unwrap();
+        let error = koto.run_named("update").unwrap_err();
+
+        // The error should be reported with an excerpt from the script where it occurred
+        let location = error.source_location().unwrap();
+        assert_eq!(location.span.start.line, 1);
+        assert!(location.source.starts_with("export on_update"));
+        assert!(error.to_string().contains("n + 'oops'"));
+    }
+}
+
+mod memory_limit {
+    use super::*;
+    use koto::ErrorKind;
+
+    const LIMIT: usize = 1_000_000;
+
+    fn make_koto() -> Koto {
+        Koto::with_settings(KotoSettings::default().with_memory_limit(LIMIT))
+    }
+
+    fn check_memory_limit_error(result: koto::Result<KValue>, expected_line: u32) {
+        match result {
+            Err(error) if matches!(error.error, ErrorKind::MemoryLimit(LIMIT)) => {
+                let location = error.source_location().unwrap();
+                assert_eq!(location.span.start.line + 1, expected_line);
+            }
+            Err(error) => panic!("Expected a memory limit error, found error: {error}"),
+            Ok(_) => panic!("Expected a memory limit error"),
+        }
+    }
+
+    #[test]
+    fn list_push() {
+        let script = "\
+x = []
+loop
+  x.push 1
+";
+        check_memory_limit_error(make_koto().compile_and_run(script), 3);
+    }
+
+    #[test]
+    fn list_from_range() {
+        let script = "\
+x = 1
+y = (0..1e12).to_list()
+";
+        check_memory_limit_error(make_koto().compile_and_run(script), 2);
+    }
+
+    #[test]
+    fn tuple_from_filtered_iterator() {
+        // The iterator doesn't provide a lower size hint, so the allocation is tracked per element
+        let script = "\
+x = 1
+y = (0..1e12).keep(|n| n % 2 == 0).to_tuple()
+";
+        check_memory_limit_error(make_koto().compile_and_run(script), 2);
+    }
+
+    #[test]
+    fn map_insert() {
+        let script = "\
+m = {}
+for i in 0..1e12
+  m.insert i, i
+";
+        check_memory_limit_error(make_koto().compile_and_run(script), 3);
+    }
+
+    #[test]
+    fn map_set_path_with_large_index() {
+        let script = "\
+m = {}
+m.set_path 'a[100000000]', 1
+";
+        check_memory_limit_error(make_koto().compile_and_run(script), 2);
+    }
+
+    #[test]
+    fn map_set_path_creating_containers() {
+        let script = "\
+m = {}
+for i in 0..1e12
+  m.set_path 'a[{i}].b', i
+";
+        check_memory_limit_error(make_koto().compile_and_run(script), 3);
+    }
+
+    #[test]
+    fn string_concatenation() {
+        let script = "\
+s = 'abc'
+loop
+  s = s + s
+";
+        check_memory_limit_error(make_koto().compile_and_run(script), 3);
+    }
+
+    #[test]
+    fn string_interpolation() {
+        let script = "\
+s = 'abc'
+loop
+  s = '{s}{s}'
+";
+        check_memory_limit_error(make_koto().compile_and_run(script), 3);
+    }
+
+    #[test]
+    fn memory_limit_errors_cant_be_caught() {
+        let script = "\
+x = []
+try
+  loop
+    x.push 1
+catch _
+  'caught'
+";
+        check_memory_limit_error(make_koto().compile_and_run(script), 4);
+    }
+
+    #[test]
+    fn temporary_lists_in_a_loop() {
+        // Each list is dropped when it's replaced, so memory usage stays constant
+        let script = "\
+for i in 0..100000
+  x = [1, 2, 3]
+size x
+";
+        check_number(make_koto().compile_and_run(script).ok(), 3);
+    }
+
+    #[test]
+    fn temporary_containers_in_a_loop() {
+        let script = "\
+for i in 0..100000
+  t = (i, i)
+  s = 'i: {i}'
+  m = {i}
+  l = (0..10).to_list() + [i]
+size l
+";
+        check_number(make_koto().compile_and_run(script).ok(), 11);
+    }
+
+    #[test]
+    fn map_insert_overwriting_a_key() {
+        // Overwriting an existing entry doesn't grow the map
+        let script = "\
+m = {}
+for i in 0..100000
+  m.insert 'a', i
+m.a
+";
+        check_number(make_koto().compile_and_run(script).ok(), 99999);
+    }
+
+    #[test]
+    fn map_index_assignment_overwriting_a_key() {
+        let script = "\
+m = {}
+for i in 0..100000
+  m[42] = i
+m[42]
+";
+        check_number(make_koto().compile_and_run(script).ok(), 99999);
+    }
+
+    #[test]
+    fn list_push_and_pop() {
+        // Memory is released when a list shrinks
+        let script = "\
+x = []
+for i in 0..100000
+  x.push i
+  x.pop()
+size x
+";
+        check_number(make_koto().compile_and_run(script).ok(), 0);
+    }
+
+    #[test]
+    fn usage_is_reset_for_each_run() {
+        let mut koto = make_koto();
+        // Each run allocates around half of the limit
+        let count = LIMIT / 2 / std::mem::size_of::<KValue>();
+        koto.compile(&format!("size (0..{count}).to_list()"))
+            .unwrap();
+        for _ in 0..4 {
+            check_number(koto.run().ok(), count as i64);
+        }
+    }
+
+    #[test]
+    fn no_limit_by_default() {
+        let mut koto = Koto::default();
+        let count = LIMIT / std::mem::size_of::<KValue>() * 2;
+        let result = koto.compile_and_run(&format!("size (0..{count}).to_list()"));
+        check_number(result.ok(), count as i64);
+    }
+}
+
+mod interrupt {
+    use super::*;
+    use koto::ErrorKind;
+    use std::{
+        sync::mpsc,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    fn check_interrupted(result: koto::Result<KValue>) {
+        match result {
+            Err(error) if matches!(error.error, ErrorKind::Interrupted) => {
+                assert!(error.source_location().is_some());
+            }
+            Err(error) => panic!("Expected an interruption, found error: {error}"),
+            Ok(_) => panic!("Expected an interruption"),
+        }
+    }
+
+    #[test]
+    fn interrupt_from_another_thread() {
+        let (handle_sender, handle_receiver) = mpsc::channel();
+
+        let worker = thread::spawn(move || {
+            let mut koto = Koto::default();
+            handle_sender.send(koto.interrupt_handle()).unwrap();
+            let result = koto.compile_and_run("loop\n  ()");
+            // The instance should be reusable after being interrupted
+            let next_result = koto.compile_and_run("1 + 1");
+            (result, next_result)
+        });
+
+        let interrupt = handle_receiver.recv().unwrap();
+        thread::sleep(Duration::from_millis(10));
+        let start = Instant::now();
+        interrupt.interrupt();
+
+        let (result, next_result) = worker.join().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(1));
+        check_interrupted(result);
+        check_number(next_result.ok(), 2);
+    }
+
+    #[test]
+    fn interruptions_cant_be_caught() {
+        let mut koto = Koto::default();
+        let interrupt = koto.interrupt_handle();
+        koto.prelude().add_fn("stop", move |_| {
+            interrupt.interrupt();
+            Ok(KValue::Null)
+        });
+
+        let script = "\
+try
+  stop()
+  loop
+    ()
+catch _
+  'caught'
+";
+        check_interrupted(koto.compile_and_run(script));
+    }
+
+    #[test]
+    fn interrupt_during_callback() {
+        let mut koto = Koto::default();
+        let interrupt = koto.interrupt_handle();
+        koto.prelude().add_fn("stop", move |_| {
+            interrupt.interrupt();
+            Ok(KValue::Null)
+        });
+
+        koto.compile_and_run("export f = ||\n  stop()\n  (0..1e12).each(|n| n).consume()")
+            .unwrap();
+        let f = koto.get_export("f").unwrap();
+        check_interrupted(koto.call_function(f, &[]));
+    }
+}
+
+mod trace_hook {
+    use super::*;
+    use koto::{bytecode::InstructionReader, ErrorKind};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    fn make_koto_with_counter(abort_after: Option<usize>) -> (Koto, Arc<AtomicUsize>) {
+        let count = Arc::new(AtomicUsize::new(0));
+        let hook_count = count.clone();
+        let koto = Koto::with_settings(KotoSettings::default().with_trace_hook(move |_| {
+            let previous = hook_count.fetch_add(1, Ordering::Relaxed);
+            match abort_after {
+                Some(limit) if previous >= limit => TraceControl::Abort,
+                _ => TraceControl::Continue,
+            }
+        }));
+        (koto, count)
+    }
+
+    #[test]
+    fn instruction_count() {
+        let (mut koto, count) = make_koto_with_counter(None);
+
+        // The script doesn't contain any jumps, so each instruction is executed once
+        let chunk = koto.compile("x = 1\ny = x + 2\nz = [x, y]").unwrap();
+        let instruction_count = InstructionReader::new(chunk).count();
+
+        koto.run().unwrap();
+        assert_eq!(count.load(Ordering::Relaxed), instruction_count);
+    }
+
+    #[test]
+    fn instruction_count_scales_with_loop() {
+        let (mut koto, count) = make_koto_with_counter(None);
+
+        koto.compile_and_run("for _ in 0..10\n  ()").unwrap();
+        let count_10 = count.swap(0, Ordering::Relaxed);
+
+        koto.compile_and_run("for _ in 0..20\n  ()").unwrap();
+        let count_20 = count.load(Ordering::Relaxed);
+
+        let per_iteration = (count_20 - count_10) / 10;
+        assert!(per_iteration > 0);
+        assert_eq!(count_10 + 10 * per_iteration, count_20);
+    }
+
+    #[test]
+    fn spans() {
+        let lines = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let hook_lines = lines.clone();
+        let mut koto = Koto::with_settings(KotoSettings::default().with_trace_hook(
+            move |context: &TraceContext| {
+                let line = context.span().unwrap().start.line;
+                hook_lines.lock().unwrap().push(line);
+                TraceControl::Continue
+            },
+        ));
+
+        koto.compile_and_run("x = 1\ny = 2\nz = 3").unwrap();
+        let mut lines = lines.lock().unwrap().clone();
+        lines.dedup();
+        assert_eq!(lines, [0, 1, 2]);
+    }
+
+    #[test]
+    fn abort() {
+        let (mut koto, count) = make_koto_with_counter(Some(100));
+
+        let error = koto.compile_and_run("loop\n  ()").unwrap_err();
+        assert!(matches!(error.error, ErrorKind::Interrupted));
+        assert_eq!(count.load(Ordering::Relaxed), 101);
+    }
+}
+
+mod breakpoints {
+    use super::*;
+    use koto::ErrorKind;
+    use std::sync::{Arc, Mutex};
+
+    // Records the first numbers found in the frame's registers, and the exported 'total' value
+    type Hits = Arc<Mutex<Vec<(Vec<i64>, Option<i64>)>>>;
+
+    fn make_koto(abort_after: Option<usize>) -> (Koto, Hits) {
+        let hits = Hits::default();
+        let callback_hits = hits.clone();
+        let koto = Koto::with_settings(KotoSettings::default().with_breakpoint_callback(
+            move |context: &TraceContext| {
+                let numbers = context
+                    .frame_registers()
+                    .iter()
+                    .filter_map(|value| match value {
+                        KValue::Number(n) => Some(i64::from(n)),
+                        _ => None,
+                    })
+                    .collect();
+                let total = match context.exports().get("total") {
+                    Some(KValue::Number(n)) => Some(i64::from(n)),
+                    _ => None,
+                };
+
+                let mut hits = callback_hits.lock().unwrap();
+                hits.push((numbers, total));
+                match abort_after {
+                    Some(limit) if hits.len() >= limit => TraceControl::Abort,
+                    _ => TraceControl::Continue,
+                }
+            },
+        ));
+        (koto, hits)
+    }
+
+    const SCRIPT: &str = "\
+export total = 0
+for i in 10..13
+  export total = total + i
+total
+";
+
+    #[test]
+    fn hit_inspect_and_resume() {
+        let (mut koto, hits) = make_koto(None);
+        koto.compile(SCRIPT).unwrap();
+        koto.set_breakpoint(3).unwrap();
+
+        check_number(koto.run().ok(), 33);
+
+        let hits = hits.lock().unwrap();
+        assert_eq!(hits.len(), 3);
+        for (hit, (i, total)) in hits.iter().zip([(10, 0), (11, 10), (12, 21)]) {
+            assert!(hit.0.contains(&i), "Expected {i} in {:?}", hit.0);
+            assert_eq!(hit.1, Some(total));
+        }
+    }
+
+    #[test]
+    fn abort_from_breakpoint() {
+        let (mut koto, hits) = make_koto(Some(2));
+        koto.compile(SCRIPT).unwrap();
+        koto.set_breakpoint(3).unwrap();
+
+        let error = koto.run().unwrap_err();
+        assert!(matches!(error.error, ErrorKind::Interrupted));
+        assert_eq!(hits.lock().unwrap().len(), 2);
+        check_number(koto.get_export("total"), 10);
+    }
+
+    #[test]
+    fn clear_breakpoints() {
+        let (mut koto, hits) = make_koto(None);
+        koto.compile(SCRIPT).unwrap();
+        koto.set_breakpoint(3).unwrap();
+        koto.clear_breakpoints();
+
+        check_number(koto.run().ok(), 33);
+        assert!(hits.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_lines() {
+        let (mut koto, _) = make_koto(None);
+        assert!(koto.set_breakpoint(1).is_err());
+
+        koto.compile(SCRIPT).unwrap();
+        assert!(koto.set_breakpoint(0).is_err());
+        assert!(koto.set_breakpoint(5).is_err());
+    }
+}
+
+mod chunk_bytes {
+    use super::*;
+
+    const SCRIPT: &str = "\
+fib = |n|
+  if n < 2 then n else (fib n - 1) + (fib n - 2)
+label = 'fib: {fib 10}'
+(size label) + fib 10
+";
+
+    #[test]
+    fn round_trip() {
+        let mut koto = Koto::default();
+        let chunk = koto.compile(SCRIPT).unwrap();
+        let expected = koto.run().unwrap();
+        let bytes = chunk.to_bytes();
+
+        let mut loaded = Koto::default();
+        let loaded_chunk = loaded.load_chunk_bytes(&bytes).unwrap();
+        assert_eq!(*loaded_chunk, *chunk);
+
+        let result = loaded.run().unwrap();
+        check_number(Some(result), 62);
+        check_number(Some(expected), 62);
+    }
+
+    #[test]
+    fn runtime_errors_refer_to_the_original_source() {
+        let mut koto = Koto::default();
+        let bytes = koto.compile("x = 1\nx.foo()").unwrap().to_bytes();
+
+        let mut loaded = Koto::default();
+        loaded.load_chunk_bytes(&bytes).unwrap();
+        let error = loaded.run().unwrap_err().to_string();
+        assert!(error.contains("x.foo()"), "Unexpected error: {error}");
+    }
+
+    #[test]
+    fn corrupted_bytes() {
+        let mut koto = Koto::default();
+        let bytes = koto.compile(SCRIPT).unwrap().to_bytes();
+
+        let mut loaded = Koto::default();
+        for i in [bytes.len() / 2, bytes.len() - 1] {
+            let mut corrupted = bytes.clone();
+            corrupted[i] ^= 0xff;
+            assert!(loaded.load_chunk_bytes(&corrupted).is_err());
+        }
+        assert!(loaded.load_chunk_bytes(&bytes[..bytes.len() / 2]).is_err());
+        assert!(loaded.load_chunk_bytes(SCRIPT.as_bytes()).is_err());
+
+        // Nothing was loaded by the failed attempts
+        assert!(loaded.run().is_err());
+    }
+}
+
+#[cfg(feature = "arc")]
+mod send_values {
+    use super::*;
+
+    #[test]
+    fn nested_map_sent_to_another_thread_and_back() {
+        let mut koto = Koto::default();
+        koto.compile_and_run(
+            "\
+export data = {numbers: [1, 2, {x: 3}], name: 'koto'}
+# A cycle, the map contains a reference to itself
+data.self = data
+",
+        )
+        .unwrap();
+        let data = koto.get_export("data").unwrap();
+
+        let returned = std::thread::spawn(move || {
+            // The value is used by a separate Koto instance on the spawned thread
+            let mut thread_koto = Koto::default();
+            let f = thread_koto
+                .compile_and_run(
+                    "\
+|data|
+  data.self.numbers[2].x += 10
+  data.total = data.numbers[0] + data.numbers[1] + data.numbers[2].x
+  data",
+                )
+                .unwrap();
+            thread_koto.call_function(f, &[data]).unwrap()
+        })
+        .join()
+        .unwrap();
+
+        // The returned value is the same map, so the changes are visible in the original instance
+        koto.compile("data.total + data.self.self.numbers[2].x")
+            .unwrap();
+        check_number(koto.run().ok(), 29);
+
+        let KValue::Map(returned) = returned else {
+            panic!("Expected a map");
+        };
+        check_number(returned.get("total"), 16);
+    }
+}
+
+mod filesystem {
+    use super::*;
+    use koto::runtime::DefaultFilesystem;
+    use std::{
+        fs, io,
+        path::{Path, PathBuf},
+    };
+
+    // A filesystem that only allows access to files within a single directory
+    struct AllowedDirFilesystem {
+        allowed_dir: PathBuf,
+    }
+
+    impl AllowedDirFilesystem {
+        fn check(&self, path: &Path) -> io::Result<()> {
+            // The path's parent is canonicalized so that files can be created
+            let parent = path.parent().unwrap_or(path);
+            match fs::canonicalize(parent) {
+                Ok(dir) if dir.starts_with(&self.allowed_dir) => Ok(()),
+                _ => Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "access denied",
+                )),
+            }
+        }
+    }
+
+    impl KotoFilesystem for AllowedDirFilesystem {
+        fn open(&self, path: &Path) -> io::Result<Ptr<dyn KotoFile>> {
+            self.check(path)?;
+            DefaultFilesystem::default().open(path)
+        }
+
+        fn create(&self, path: &Path) -> io::Result<Ptr<dyn KotoFile>> {
+            self.check(path)?;
+            DefaultFilesystem::default().create(path)
+        }
+
+        fn read_to_string(&self, path: &Path) -> io::Result<String> {
+            self.check(path)?;
+            DefaultFilesystem::default().read_to_string(path)
+        }
+
+        fn read_bytes(&self, path: &Path) -> io::Result<Vec<u8>> {
+            self.check(path)?;
+            DefaultFilesystem::default().read_bytes(path)
+        }
+
+        fn exists(&self, path: &Path) -> bool {
+            self.check(path).is_ok() && DefaultFilesystem::default().exists(path)
+        }
+
+        fn remove_file(&self, path: &Path) -> io::Result<()> {
+            self.check(path)?;
+            DefaultFilesystem::default().remove_file(path)
+        }
+    }
+
+    fn setup() -> (Koto, PathBuf, PathBuf) {
+        let root = Path::new(env!("CARGO_TARGET_TMPDIR")).join("filesystem");
+        let allowed_dir = root.join("allowed");
+        fs::create_dir_all(&allowed_dir).unwrap();
+        let allowed_dir = fs::canonicalize(allowed_dir).unwrap();
+
+        let allowed = allowed_dir.join("allowed.txt");
+        let denied = root.join("denied.txt");
+        fs::write(&allowed, "hello").unwrap();
+        fs::write(&denied, "secret").unwrap();
+
+        let koto = Koto::with_settings(
+            KotoSettings::default().with_filesystem(AllowedDirFilesystem { allowed_dir }),
+        );
+
+        (koto, allowed, denied)
+    }
+
+    fn run_with_path(koto: &mut Koto, script: &str, path: &Path) -> koto::Result<KValue> {
+        koto.exports_mut()
+            .insert("path", path.to_string_lossy().as_ref());
+        koto.compile_and_run(script)
+    }
+
+    fn check_string(value: koto::Result<KValue>, expected: &str) {
+        match value {
+            Ok(KValue::Str(s)) => assert_eq!(s.as_str(), expected),
+            other => panic!("Expected '{expected}', found {other:?}"),
+        }
+    }
+
+    #[test]
+    fn reads_within_the_allowed_dir() {
+        let (mut koto, allowed, _) = setup();
+
+        check_string(
+            run_with_path(&mut koto, "io.read_to_string path", &allowed),
+            "hello",
+        );
+        check_string(
+            run_with_path(&mut koto, "io.open(path).read_to_string()", &allowed),
+            "hello",
+        );
+        assert!(matches!(
+            run_with_path(&mut koto, "io.exists path", &allowed),
+            Ok(KValue::Bool(true))
+        ));
+    }
+
+    #[test]
+    fn reads_outside_the_allowed_dir() {
+        let (mut koto, _, denied) = setup();
+
+        for script in ["io.read_to_string path", "io.open path", "io.create path"] {
+            let error = run_with_path(&mut koto, script, &denied)
+                .unwrap_err()
+                .to_string();
+            assert!(
+                error.contains(denied.to_string_lossy().as_ref()),
+                "Expected the path in the error: {error}"
+            );
+            assert!(error.contains("access denied"), "Unexpected error: {error}");
+        }
+
+        assert!(matches!(
+            run_with_path(&mut koto, "io.exists path", &denied),
+            Ok(KValue::Bool(false))
+        ));
+        assert_eq!(fs::read_to_string(&denied).unwrap(), "secret");
+    }
+
+    #[test]
+    fn denied_access_can_be_caught() {
+        let (mut koto, _, denied) = setup();
+
+        let script = "
+try
+  io.read_to_string path
+catch _
+  'denied'
+";
+        check_string(run_with_path(&mut koto, script, &denied), "denied");
+    }
+}
+
+mod profiling {
+    use super::*;
+    use koto::runtime::ProfileEntryKind;
+
+    const SCRIPT: &str = "\
+cold = |x| x + 1
+hot = |n|
+  total = 0
+  for i in 0..n
+    total += i % 7
+  total
+cold 1
+for _ in 0..10
+  hot 3000
+(cold 2) + size [1, 2, 3]
+";
+
+    #[test]
+    fn hot_function_dominates_report() {
+        let mut koto = Koto::with_settings(KotoSettings::default().with_profiling(true));
+        check_number(Some(koto.compile_and_run(SCRIPT).unwrap()), 6);
+
+        let report = koto.profile_report().unwrap();
+        let hot = &report.entries[0];
+        assert_eq!(hot.kind, ProfileEntryKind::Function);
+        assert_eq!(hot.line, Some(3));
+        assert_eq!(hot.calls, 10);
+        assert!(hot.time > report.total_time / 2, "{report}");
+
+        let cold = report
+            .entries
+            .iter()
+            .find(|entry| entry.line == Some(1))
+            .unwrap();
+        assert_eq!(cold.kind, ProfileEntryKind::Function);
+        assert_eq!(cold.calls, 2);
+
+        let external = report
+            .entries
+            .iter()
+            .find(|entry| entry.kind == ProfileEntryKind::External)
+            .unwrap();
+        assert_eq!(external.line, Some(10));
+        assert_eq!(external.calls, 1);
+
+        assert!(report.to_string().contains("function at <script>:3"));
+
+        koto.reset_profile();
+        let report = koto.profile_report().unwrap();
+        assert!(report.entries.is_empty());
+        assert!(report.total_time.is_zero());
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let mut koto = Koto::default();
+        koto.compile_and_run(SCRIPT).unwrap();
+        assert!(koto.profile_report().is_none());
+    }
+}
+
+mod function_info {
+    use super::*;
+    use koto::PtrMut;
+
+    // Registers callbacks provided by the script, returning the callbacks' info
+    fn registered_callbacks(script: &str) -> Vec<FunctionInfo> {
+        let mut koto = Koto::default();
+        let callbacks = PtrMut::from(Vec::new());
+
+        koto.prelude().add_fn("register", {
+            let callbacks = callbacks.clone();
+            move |ctx| match ctx.args() {
+                [callback] => {
+                    let info = callback.function_info().expect("Expected a Koto function");
+                    callbacks.borrow_mut().push(info);
+                    Ok(KValue::Null)
+                }
+                unexpected => type_error_with_slice("a function", unexpected),
+            }
+        });
+
+        koto.compile_and_run(script).unwrap();
+        let result = callbacks.borrow().clone();
+        result
+    }
+
+    #[test]
+    fn arg_counts() {
+        let callbacks = registered_callbacks(
+            "
+register || 42
+register |x| x
+register |a, b, c| a + b + c
+register |first, rest...| rest
+register |(a, b)| a + b
+",
+        );
+
+        let arg_counts = callbacks.iter().map(|f| f.arg_count).collect::<Vec<_>>();
+        assert_eq!(arg_counts, [0, 1, 3, 2, 1]);
+
+        let variadic = callbacks.iter().map(|f| f.variadic).collect::<Vec<_>>();
+        assert_eq!(variadic, [false, false, false, true, false]);
+
+        assert!(callbacks
+            .iter()
+            .all(|f| !f.generator && !f.is_instance_function && f.captures.is_empty()));
+    }
+
+    #[test]
+    fn instance_functions_and_generators() {
+        let callbacks = registered_callbacks(
+            "
+register |x| self.foo + x
+register || yield 1
+register ||
+  # self is only accessed in the nested function
+  || self
+",
+        );
+
+        assert!(callbacks[0].is_instance_function);
+        assert!(!callbacks[0].generator);
+
+        assert!(!callbacks[1].is_instance_function);
+        assert!(callbacks[1].generator);
+
+        assert!(!callbacks[2].is_instance_function);
+    }
+
+    #[test]
+    fn captures() {
+        let callbacks = registered_callbacks(
+            "
+x, y = 1, 'hello'
+register |n| x + n + y.size()
+",
+        );
+
+        let captures = &callbacks[0].captures;
+        assert_eq!(captures.len(), 2);
+        assert!(captures
+            .iter()
+            .any(|value| matches!(value, KValue::Number(n) if *n == 1)));
+        assert!(captures
+            .iter()
+            .any(|value| matches!(value, KValue::Str(s) if s.as_str() == "hello")));
+    }
+
+    #[test]
+    fn native_functions_have_no_info() {
+        let native = KValue::NativeFunction(KNativeFunction::new(|_| Ok(KValue::Null)));
+        assert!(native.function_info().is_none());
+        assert!(KValue::Null.function_info().is_none());
+    }
+
+    #[test]
+    fn check_arg_count() {
+        let callbacks = registered_callbacks(
+            "
+register |a, b, c| a
+register |x, xs...| x
+register |x| x
+",
+        );
+
+        let error =
+            |info: &FunctionInfo, count| info.check_arg_count(count).unwrap_err().to_string();
+
+        assert!(callbacks[0].check_arg_count(3).is_ok());
+        assert_eq!(
+            error(&callbacks[0], 2),
+            "Expected 2 args, but the function takes 3 args"
+        );
+        assert_eq!(
+            error(&callbacks[0], 4),
+            "Expected 4 args, but the function takes 3 args"
+        );
+
+        assert!(callbacks[1].check_arg_count(1).is_ok());
+        assert!(callbacks[1].check_arg_count(5).is_ok());
+        assert_eq!(
+            error(&callbacks[1], 0),
+            "Expected 0 args, but the function takes at least 1 args"
+        );
+
+        assert_eq!(
+            error(&callbacks[2], 2),
+            "Expected 2 args, but the function takes 1 arg"
+        );
+    }
+
+    #[test]
+    fn call_function_with_arg_count_checks() {
+        let script = "
+export f = |a, b, c| a + b + c
+export g = |a| self.x + a
+";
+        let args = [KValue::from(1), KValue::from(2)];
+
+        // Argument counts aren't checked by default
+        let mut koto = Koto::default();
+        koto.compile_and_run(script).unwrap();
+        let f = koto.get_export("f").unwrap();
+        assert!(koto.call_function(f, &args).is_err());
+
+        let mut koto = Koto::with_settings(KotoSettings {
+            check_call_arg_counts: true,
+            ..Default::default()
+        });
+        koto.compile_and_run(script).unwrap();
+
+        let f = koto.get_export("f").unwrap();
+        let error = koto.call_function(f.clone(), &args).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Expected 2 args, but the function takes 3 args"
+        );
+        let result = koto.call_function(f, &[1.into(), 2.into(), 3.into()]);
+        check_number(result.ok(), 6);
+
+        let g = koto.get_export("g").unwrap();
+        let instance = KMap::from_iter([("x", 10)]);
+        assert!(koto
+            .call_instance_function(instance.clone().into(), g.clone(), &[])
+            .is_err());
+        let result = koto.call_instance_function(instance.into(), g, 5);
+        check_number(result.ok(), 15);
+    }
+}
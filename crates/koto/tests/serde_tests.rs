@@ -0,0 +1,119 @@
+#![cfg(feature = "serde")]
+
+use koto::{prelude::*, serialize::to_koto_value};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Scene {
+    name: String,
+    shapes: Vec<Shape>,
+    background: Option<Color>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Shape {
+    kind: String,
+    size: f64,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Color {
+    r: u8,
+    g: u8,
+    b: u8,
+}
+
+fn make_koto() -> Koto {
+    let mut koto = Koto::default();
+    koto.compile_and_run(
+        "
+export
+  # Returns a copy of the scene with its shapes scaled by the given factor
+  scale_scene: |scene, factor|
+    name: scene.name
+    shapes: scene.shapes.each(|shape| {kind: shape.kind, size: shape.size * factor}).to_list()
+    background: scene.background
+
+  make_shape: |kind| {kind}
+",
+    )
+    .unwrap();
+    koto
+}
+
+fn make_scene(background: Option<Color>) -> Scene {
+    Scene {
+        name: "test".into(),
+        shapes: vec![
+            Shape {
+                kind: "circle".into(),
+                size: 1.5,
+            },
+            Shape {
+                kind: "square".into(),
+                size: 2.0,
+            },
+        ],
+        background,
+    }
+}
+
+fn scale_scene(scene: &Scene, factor: f64) -> koto::Result<Scene> {
+    let mut koto = make_koto();
+    let scale_scene = koto.get_export("scale_scene").unwrap();
+    let scene = to_koto_value(scene).unwrap();
+    koto.call_function_typed(scale_scene, &[scene, factor.into()])
+}
+
+#[test]
+fn round_trip_through_script_call() {
+    let scene = make_scene(Some(Color { r: 1, g: 2, b: 3 }));
+    let result = scale_scene(&scene, 2.0).unwrap();
+
+    assert_eq!(result.name, scene.name);
+    assert_eq!(result.background, scene.background);
+    assert_eq!(
+        result.shapes,
+        [
+            Shape {
+                kind: "circle".into(),
+                size: 3.0,
+            },
+            Shape {
+                kind: "square".into(),
+                size: 4.0,
+            },
+        ]
+    );
+}
+
+#[test]
+fn round_trip_with_none() {
+    let scene = make_scene(None);
+    let result = scale_scene(&scene, 1.0).unwrap();
+    assert_eq!(result, scene);
+}
+
+#[test]
+fn missing_field() {
+    let mut koto = make_koto();
+    let make_shape = koto.get_export("make_shape").unwrap();
+    let result = koto.call_function_typed::<Shape>(make_shape, &["circle".into()]);
+
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Failed to deserialize the function's result: missing field `size`"
+    );
+}
+
+#[test]
+fn unexpected_type() {
+    let mut koto = make_koto();
+    let make_shape = koto.get_export("make_shape").unwrap();
+    let result = koto.call_function_typed::<Vec<Shape>>(make_shape, &["circle".into()]);
+
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .starts_with("Failed to deserialize the function's result: invalid type: map"));
+}
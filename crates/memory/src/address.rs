@@ -4,9 +4,13 @@ use std::{
 };
 
 /// A wrapper for comparing and hashing pointer addresses
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Address(*const u8);
 
+// Safety: The address is only used for comparisons and hashing, and is never dereferenced
+unsafe impl Send for Address {}
+unsafe impl Sync for Address {}
+
 impl<T: ?Sized> From<*const T> for Address {
     fn from(pointer: *const T) -> Self {
         Self(pointer as *const u8)
@@ -3,7 +3,7 @@ use std::{
     fmt,
     hash::{Hash, Hasher},
     ops::Deref,
-    sync::Arc,
+    sync::{Arc, Weak},
 };
 
 use crate::Address;
@@ -53,6 +53,15 @@ impl<T: ?Sized> Ptr<T> {
     pub fn ref_count(this: &Self) -> usize {
         Arc::strong_count(&this.0)
     }
+
+    /// Makes a weak pointer to the allocated memory
+    ///
+    /// Weak pointers don't keep the allocated value alive, see [PtrWeak::upgrade].
+    ///
+    /// See also: [std::sync::Arc::downgrade]
+    pub fn downgrade(this: &Self) -> PtrWeak<T> {
+        PtrWeak(Arc::downgrade(&this.0))
+    }
 }
 
 impl<T: Clone> Ptr<T> {
@@ -160,3 +169,26 @@ impl<T: ?Sized + PartialOrd> PartialOrd for Ptr<T> {
         self.0.partial_cmp(&other.0)
     }
 }
+
+/// A weak pointer to a value in memory allocated by a [Ptr]
+///
+/// The pointer doesn't keep the allocated value alive, which allows reference cycles to be broken.
+#[derive(Debug)]
+pub struct PtrWeak<T: ?Sized>(Weak<T>);
+
+impl<T: ?Sized> PtrWeak<T> {
+    /// Attempts to make a [Ptr] to the allocated value
+    ///
+    /// `None` is returned if the value has been dropped.
+    ///
+    /// See also: [std::sync::Weak::upgrade]
+    pub fn upgrade(&self) -> Option<Ptr<T>> {
+        self.0.upgrade().map(Ptr)
+    }
+}
+
+impl<T: ?Sized> Clone for PtrWeak<T> {
+    fn clone(&self) -> Self {
+        Self(Weak::clone(&self.0))
+    }
+}
@@ -111,6 +111,32 @@ impl ConstantPool {
         }
     }
 
+    /// Initializes a pool from a sequence of constants
+    ///
+    /// The constants are added to the pool in order, so a pool's constants can be used to produce
+    /// an identical pool.
+    ///
+    /// `None` is returned if the sequence contains duplicate constants, or if there are too many
+    /// constants to fit in the pool.
+    pub fn from_constants<'a>(constants: impl IntoIterator<Item = Constant<'a>>) -> Option<Self> {
+        let mut builder = ConstantPoolBuilder::default();
+
+        for (expected_index, constant) in constants.into_iter().enumerate() {
+            let index = match constant {
+                Constant::F64(n) => builder.add_f64(n),
+                Constant::I64(n) => builder.add_i64(n),
+                Constant::Str(s) => builder.add_string(s),
+            }
+            .ok()?;
+
+            if usize::from(index) != expected_index {
+                return None;
+            }
+        }
+
+        Some(builder.build())
+    }
+
     /// Returns the concatenated string data stored in the pool
     pub fn string_data(&self) -> &Ptr<str> {
         &self.string_data
@@ -408,4 +434,26 @@ mod tests {
         assert_eq!(iter.next(), Some(Constant::Str("^_^")));
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn test_from_constants() {
+        let mut builder = ConstantPoolBuilder::default();
+
+        builder.add_i64(-1).unwrap();
+        builder.add_string("O_o").unwrap();
+        builder.add_f64(99.9).unwrap();
+
+        let pool = builder.build();
+        let rebuilt = ConstantPool::from_constants(pool.iter()).unwrap();
+
+        assert_eq!(pool, rebuilt);
+        assert_eq!(
+            pool.iter().collect::<Vec<_>>(),
+            rebuilt.iter().collect::<Vec<_>>()
+        );
+
+        // Duplicate constants would result in shifted indices, so they're rejected
+        let duplicated = [Constant::I64(1), Constant::I64(1)];
+        assert!(ConstantPool::from_constants(duplicated).is_none());
+    }
 }
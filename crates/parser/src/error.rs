@@ -225,18 +225,55 @@ pub struct Error {
     pub error: ErrorKind,
     /// The span in the source string where the error occurred
     pub span: Span,
+    /// True if the parser had reached the end of the input when the error occurred
+    end_of_input: bool,
 }
 
 impl Error {
     /// Initializes a parser error with the specific error type and its associated span
     pub fn new(error: ErrorKind, span: Span) -> Self {
-        Self { error, span }
+        Self {
+            error,
+            span,
+            end_of_input: false,
+        }
+    }
+
+    /// Marks the error as having occurred at the end of the input
+    pub(crate) fn at_end_of_input(mut self) -> Self {
+        self.end_of_input = true;
+        self
     }
 
     /// Returns true if the error was caused by the expectation of indentation
     pub fn is_indentation_error(&self) -> bool {
         matches!(self.error, ErrorKind::ExpectedIndentation(_))
     }
+
+    /// Returns true if the error was caused by the input ending before parsing was complete
+    ///
+    /// Examples include a missing indented block, an unclosed parenthesis, or an unterminated
+    /// string, where an interactive session could continue accepting input.
+    /// Errors that were encountered before the end of the input will return false.
+    pub fn is_unexpected_end_of_input(&self) -> bool {
+        use SyntaxError::*;
+
+        self.end_of_input
+            && matches!(
+                self.error,
+                ErrorKind::ExpectedIndentation(_)
+                    | ErrorKind::SyntaxError(
+                        ExpectedArgsEnd
+                            | ExpectedCloseParen
+                            | ExpectedFunctionArgsEnd
+                            | ExpectedIndexEnd
+                            | ExpectedListEnd
+                            | ExpectedMapEnd
+                            | ExpectedStringPlaceholderEnd
+                            | UnterminatedString
+                    )
+            )
+    }
 }
 
 /// The result type used by the [Parser](crate::Parser)
@@ -545,6 +545,8 @@ pub enum MetaKeyId {
     Negate,
     /// @size
     Size,
+    /// @hash
+    Hash,
     /// @type
     Type,
     /// @base
@@ -619,6 +621,7 @@ impl fmt::Display for MetaKeyId {
                 NextBack => "next_back",
                 Negate => "negate",
                 Size => "size",
+                Hash => "hash",
                 Type => "type",
                 Base => "base",
                 Call => "||",
@@ -239,6 +239,8 @@ pub struct Parser<'source> {
     current_token: LexedToken,
     current_line: u32,
     frame_stack: Vec<Frame>,
+    // Set to true when the parser attempts to read beyond the end of the input
+    reached_end_of_input: bool,
 }
 
 impl<'source> Parser<'source> {
@@ -253,6 +255,7 @@ impl<'source> Parser<'source> {
             current_token: LexedToken::default(),
             current_line: 0,
             frame_stack: Vec::new(),
+            reached_end_of_input: false,
         };
 
         parser.consume_main_block()?;
@@ -2027,6 +2030,7 @@ impl<'source> Parser<'source> {
                 "next_back" => MetaKeyId::NextBack,
                 "negate" => MetaKeyId::Negate,
                 "size" => MetaKeyId::Size,
+                "hash" => MetaKeyId::Hash,
                 "type" => MetaKeyId::Type,
                 "base" => MetaKeyId::Base,
                 "main" => MetaKeyId::Main,
@@ -2876,6 +2880,11 @@ impl<'source> Parser<'source> {
                         context: string_context,
                     }));
                 }
+                // The lexer produces an error token when the end of the input is reached before
+                // the string's end quote is found.
+                Token::Error => {
+                    return Err(self.make_error(UnterminatedString).at_end_of_input());
+                }
                 _ => return self.error(UnexpectedToken),
             }
         }
@@ -3029,8 +3038,10 @@ impl<'source> Parser<'source> {
     where
         E: Into<ErrorKind>,
     {
-        #[allow(clippy::let_and_return)]
-        let error = Error::new(error_type.into(), self.current_span());
+        let mut error = Error::new(error_type.into(), self.current_span());
+        if self.reached_end_of_input && self.remaining_input_is_empty() {
+            error = error.at_end_of_input();
+        }
 
         #[cfg(feature = "panic_on_parser_error")]
         panic!("{error}");
@@ -3066,6 +3077,7 @@ impl<'source> Parser<'source> {
 
             Some(self.current_token.token)
         } else {
+            self.reached_end_of_input = true;
             None
         }
     }
@@ -3075,7 +3087,23 @@ impl<'source> Parser<'source> {
     }
 
     fn peek_token_n(&mut self, n: usize) -> Option<Token> {
-        self.lexer.peek(n).map(|peeked| peeked.token)
+        let result = self.lexer.peek(n).map(|peeked| peeked.token);
+        if result.is_none() {
+            self.reached_end_of_input = true;
+        }
+        result
+    }
+
+    // Returns true if there are no more tokens to parse, other than whitespace
+    fn remaining_input_is_empty(&mut self) -> bool {
+        let mut peek_count = 0;
+        while let Some(peeked) = self.lexer.peek(peek_count) {
+            if !peeked.token.is_whitespace_including_newline() {
+                return false;
+            }
+            peek_count += 1;
+        }
+        true
     }
 
     fn current_indent(&self) -> usize {
@@ -3169,6 +3197,7 @@ impl<'source> Parser<'source> {
             peek_count += 1;
         }
 
+        self.reached_end_of_input = true;
         None
     }
 
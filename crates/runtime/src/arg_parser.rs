@@ -0,0 +1,386 @@
+use crate::{prelude::*, Borrow, Result};
+
+/// A helper for extracting the arguments of a native function
+///
+/// Arguments are taken in order by calling the parser's extraction functions, with each call
+/// providing the name of the parameter that's being extracted. If an argument is missing or has
+/// an unexpected type, then an error is returned that names the function, the argument's position
+/// and name, and the expected and received types.
+///
+/// [ArgParser::finish] should be called once all arguments have been extracted, which returns an
+/// error if more arguments were provided than expected.
+///
+/// # Example
+///
+/// ```
+/// use koto_runtime::{prelude::*, ArgParser};
+///
+/// fn lerp(ctx: &mut CallContext) -> koto_runtime::Result<KValue> {
+///     let mut args = ArgParser::new("lerp", ctx.args());
+///     let a = args.number("a")?;
+///     let b = args.number("b")?;
+///     let t = args.optional_number("t", 0.5)?;
+///     args.finish()?;
+///
+///     Ok((a + (b - a) * t).into())
+/// }
+/// ```
+pub struct ArgParser<'a> {
+    function_name: &'a str,
+    args: &'a [KValue],
+    // The index of the next argument to be extracted
+    index: usize,
+    // Added to argument positions in error messages,
+    // e.g. when an instance was taken from the start of the call's arguments.
+    position_offset: usize,
+    // Used when describing the expected number of arguments
+    has_optional_args: bool,
+}
+
+impl<'a> ArgParser<'a> {
+    /// Makes a parser for the given function name and arguments
+    pub fn new(function_name: &'a str, args: &'a [KValue]) -> Self {
+        Self {
+            function_name,
+            args,
+            index: 0,
+            position_offset: 0,
+            has_optional_args: false,
+        }
+    }
+
+    /// Makes a parser for arguments that follow an instance value
+    ///
+    /// The instance is taken either from the call's `self` value, or from the first argument,
+    /// following the same rules as [CallContext::instance_and_args]. If the instance was taken
+    /// from the arguments, then the positions of the remaining arguments are adjusted to match in
+    /// error messages.
+    ///
+    /// `extract_instance` should return the instance if the provided value is acceptable as an
+    /// instance for the function. `instance_type` is used to describe the expected instance in
+    /// error messages.
+    ///
+    /// See also the typed variants, e.g. [ArgParser::with_list_instance].
+    pub fn with_instance<T>(
+        function_name: &'a str,
+        ctx: &'a CallContext,
+        instance_type: &str,
+        extract_instance: impl Fn(&'a KValue) -> Option<T>,
+    ) -> Result<(T, Self)> {
+        let args = ctx.args();
+        if let Some(instance) = extract_instance(ctx.instance()) {
+            return Ok((instance, Self::new(function_name, args)));
+        }
+
+        let mut parser = Self::new(function_name, args);
+        let first_arg = parser.next_arg(None, instance_type)?;
+        match extract_instance(first_arg) {
+            Some(instance) => {
+                parser.args = &args[1..];
+                parser.index = 0;
+                parser.position_offset = 1;
+                Ok((instance, parser))
+            }
+            None => parser.unexpected_type(None, instance_type, first_arg),
+        }
+    }
+
+    /// Makes a parser for arguments that follow a Number instance, see [ArgParser::with_instance]
+    pub fn with_number_instance(
+        function_name: &'a str,
+        ctx: &'a CallContext,
+    ) -> Result<(KNumber, Self)> {
+        Self::with_instance(function_name, ctx, "Number", |value| match value {
+            KValue::Number(n) => Some(*n),
+            _ => None,
+        })
+    }
+
+    /// Makes a parser for arguments that follow a String instance, see [ArgParser::with_instance]
+    pub fn with_string_instance(
+        function_name: &'a str,
+        ctx: &'a CallContext,
+    ) -> Result<(&'a KString, Self)> {
+        Self::with_instance(function_name, ctx, "String", |value| match value {
+            KValue::Str(s) => Some(s),
+            _ => None,
+        })
+    }
+
+    /// Makes a parser for arguments that follow a List instance, see [ArgParser::with_instance]
+    pub fn with_list_instance(
+        function_name: &'a str,
+        ctx: &'a CallContext,
+    ) -> Result<(&'a KList, Self)> {
+        Self::with_instance(function_name, ctx, "List", |value| match value {
+            KValue::List(l) => Some(l),
+            _ => None,
+        })
+    }
+
+    /// Makes a parser for arguments that follow a Tuple instance, see [ArgParser::with_instance]
+    pub fn with_tuple_instance(
+        function_name: &'a str,
+        ctx: &'a CallContext,
+    ) -> Result<(&'a KTuple, Self)> {
+        Self::with_instance(function_name, ctx, "Tuple", |value| match value {
+            KValue::Tuple(t) => Some(t),
+            _ => None,
+        })
+    }
+
+    /// Makes a parser for arguments that follow a Range instance, see [ArgParser::with_instance]
+    pub fn with_range_instance(
+        function_name: &'a str,
+        ctx: &'a CallContext,
+    ) -> Result<(&'a KRange, Self)> {
+        Self::with_instance(function_name, ctx, "Range", |value| match value {
+            KValue::Range(r) => Some(r),
+            _ => None,
+        })
+    }
+
+    /// Extracts the next argument as a value of any type
+    pub fn value(&mut self, name: &str) -> Result<&'a KValue> {
+        self.next_arg(Some(name), "Value")
+    }
+
+    /// Extracts the next argument if it was provided
+    pub fn optional_value(&mut self) -> Option<&'a KValue> {
+        if self.next_is_provided() {
+            self.index += 1;
+            Some(&self.args[self.index - 1])
+        } else {
+            None
+        }
+    }
+
+    /// Extracts the next argument as a [KNumber]
+    pub fn number(&mut self, name: &str) -> Result<KNumber> {
+        let expected = "Number";
+        match self.next_arg(Some(name), expected)? {
+            KValue::Number(n) => Ok(*n),
+            unexpected => self.unexpected_type(Some(name), expected, unexpected),
+        }
+    }
+
+    /// Extracts the next argument as a [KNumber], or returns the default if it wasn't provided
+    pub fn optional_number(&mut self, name: &str, default: impl Into<KNumber>) -> Result<KNumber> {
+        if self.next_is_provided() {
+            self.number(name)
+        } else {
+            Ok(default.into())
+        }
+    }
+
+    /// Extracts the next argument as a [KString]
+    pub fn string(&mut self, name: &str) -> Result<&'a KString> {
+        let expected = "String";
+        match self.next_arg(Some(name), expected)? {
+            KValue::Str(s) => Ok(s),
+            unexpected => self.unexpected_type(Some(name), expected, unexpected),
+        }
+    }
+
+    /// Extracts the next argument as a Bool
+    pub fn bool(&mut self, name: &str) -> Result<bool> {
+        let expected = "Bool";
+        match self.next_arg(Some(name), expected)? {
+            KValue::Bool(b) => Ok(*b),
+            unexpected => self.unexpected_type(Some(name), expected, unexpected),
+        }
+    }
+
+    /// Extracts the next argument as a [KList]
+    pub fn list(&mut self, name: &str) -> Result<&'a KList> {
+        let expected = "List";
+        match self.next_arg(Some(name), expected)? {
+            KValue::List(l) => Ok(l),
+            unexpected => self.unexpected_type(Some(name), expected, unexpected),
+        }
+    }
+
+    /// Extracts the next argument as a [KMap]
+    pub fn map(&mut self, name: &str) -> Result<&'a KMap> {
+        let expected = "Map";
+        match self.next_arg(Some(name), expected)? {
+            KValue::Map(m) => Ok(m),
+            unexpected => self.unexpected_type(Some(name), expected, unexpected),
+        }
+    }
+
+    /// Extracts the next argument as a callable value
+    ///
+    /// See [KValue::is_callable].
+    pub fn callable(&mut self, name: &str) -> Result<&'a KValue> {
+        let expected = "Callable";
+        match self.next_arg(Some(name), expected)? {
+            value if value.is_callable() => Ok(value),
+            unexpected => self.unexpected_type(Some(name), expected, unexpected),
+        }
+    }
+
+    /// Extracts the next argument as an object of type `T`
+    pub fn object<T: KotoObject>(&mut self, name: &str) -> Result<Borrow<'a, T>> {
+        let expected = T::type_static();
+        match self.next_arg(Some(name), expected)? {
+            KValue::Object(o) if o.is_a::<T>() => o.cast::<T>(),
+            unexpected => self.unexpected_type(Some(name), expected, unexpected),
+        }
+    }
+
+    /// Extracts the next argument as an object of type `T` if it was provided
+    pub fn optional_object<T: KotoObject>(&mut self, name: &str) -> Result<Option<Borrow<'a, T>>> {
+        if self.next_is_provided() {
+            self.object(name).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Checks that all of the provided arguments have been extracted
+    pub fn finish(self) -> Result<()> {
+        if self.index >= self.args.len() {
+            return Ok(());
+        }
+
+        let expected = self.index + self.position_offset;
+        let found = self.args.len() + self.position_offset;
+        runtime_error!(
+            "{}: Expected {}{expected} argument{}, but found {found}",
+            self.function_name,
+            if self.has_optional_args {
+                "at most "
+            } else {
+                ""
+            },
+            if expected == 1 { "" } else { "s" },
+        )
+    }
+
+    // Checks if an optional argument was provided
+    fn next_is_provided(&mut self) -> bool {
+        self.has_optional_args = true;
+        self.index < self.args.len()
+    }
+
+    fn next_arg(&mut self, name: Option<&str>, expected: &str) -> Result<&'a KValue> {
+        match self.args.get(self.index) {
+            Some(arg) => {
+                self.index += 1;
+                Ok(arg)
+            }
+            None => runtime_error!(
+                "{}: Missing argument {}{}, expected '{expected}'",
+                self.function_name,
+                self.index + self.position_offset + 1,
+                describe_name(name),
+            ),
+        }
+    }
+
+    // Called after the unexpected value has been taken with `next_arg`
+    fn unexpected_type<T>(
+        &self,
+        name: Option<&str>,
+        expected: &str,
+        unexpected: &KValue,
+    ) -> Result<T> {
+        runtime_error!(
+            "{}: Expected '{expected}' for argument {}{}, but found '{}'",
+            self.function_name,
+            self.index + self.position_offset,
+            describe_name(name),
+            unexpected.type_as_string(),
+        )
+    }
+}
+
+fn describe_name(name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!(" ('{name}')"),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_error<T>(result: Result<T>, expected: &str) {
+        match result {
+            Ok(_) => panic!("Expected an error"),
+            Err(error) => assert_eq!(error.to_string(), expected),
+        }
+    }
+
+    #[test]
+    fn extracting_args() {
+        let args = [1.into(), "hello".into(), true.into()];
+        let mut parser = ArgParser::new("test", &args);
+
+        assert_eq!(parser.number("a").unwrap(), 1);
+        assert_eq!(parser.string("b").unwrap().as_str(), "hello");
+        assert!(parser.bool("c").unwrap());
+        assert_eq!(parser.optional_number("d", 42).unwrap(), 42);
+        assert!(parser.finish().is_ok());
+    }
+
+    #[test]
+    fn unexpected_type() {
+        let args = [1.into(), "hello".into()];
+        let mut parser = ArgParser::new("test", &args);
+
+        parser.number("a").unwrap();
+        check_error(
+            parser.number("b"),
+            "test: Expected 'Number' for argument 2 ('b'), but found 'String'",
+        );
+    }
+
+    #[test]
+    fn unexpected_type_for_optional_arg() {
+        let args = [1.into(), KValue::Null];
+        let mut parser = ArgParser::new("test", &args);
+
+        parser.number("a").unwrap();
+        check_error(
+            parser.optional_number("b", 0),
+            "test: Expected 'Number' for argument 2 ('b'), but found 'Null'",
+        );
+    }
+
+    #[test]
+    fn missing_arg() {
+        let args = [1.into()];
+        let mut parser = ArgParser::new("test", &args);
+
+        parser.number("a").unwrap();
+        check_error(
+            parser.list("b"),
+            "test: Missing argument 2 ('b'), expected 'List'",
+        );
+    }
+
+    #[test]
+    fn too_many_args() {
+        let args = [1.into(), 2.into(), 3.into()];
+        let mut parser = ArgParser::new("test", &args);
+
+        parser.number("a").unwrap();
+        check_error(parser.finish(), "test: Expected 1 argument, but found 3");
+    }
+
+    #[test]
+    fn too_many_args_with_optional_args() {
+        let args = [1.into(), 2.into(), 3.into()];
+        let mut parser = ArgParser::new("test", &args);
+
+        parser.number("a").unwrap();
+        parser.optional_number("b", 0).unwrap();
+        check_error(
+            parser.finish(),
+            "test: Expected at most 2 arguments, but found 3",
+        );
+    }
+}
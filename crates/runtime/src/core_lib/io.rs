@@ -2,7 +2,7 @@
 
 use crate::{derive::*, prelude::*, BufferedFile, Error, Ptr, Result};
 use std::{
-    fmt, fs,
+    fmt,
     io::{self, BufRead, Read, Seek, SeekFrom, Write},
     path::{Path, PathBuf},
 };
@@ -15,13 +15,12 @@ pub fn make_module() -> KMap {
 
     result.add_fn("create", {
         move |ctx| match ctx.args() {
-            [Str(path)] => {
-                let path = Path::new(path.as_str()).to_path_buf();
-                match fs::File::create(&path) {
-                    Ok(file) => Ok(File::system_file(file, path)),
-                    Err(error) => runtime_error!("io.create: Error while creating file: {error}"),
+            [Str(path)] => match ctx.vm.filesystem().create(Path::new(path.as_str())) {
+                Ok(file) => Ok(File(file).into()),
+                Err(error) => {
+                    runtime_error!("io.create: Error while creating file '{path}': {error}")
                 }
-            }
+            },
             unexpected => type_error_with_slice("a path String as argument", unexpected),
         }
     });
@@ -35,7 +34,7 @@ pub fn make_module() -> KMap {
     });
 
     result.add_fn("exists", |ctx| match ctx.args() {
-        [Str(path)] => Ok(Bool(fs::canonicalize(path.as_str()).is_ok())),
+        [Str(path)] => Ok(Bool(ctx.vm.filesystem().exists(Path::new(path.as_str())))),
         unexpected => type_error_with_slice("a path String as argument", unexpected),
     });
 
@@ -63,12 +62,9 @@ pub fn make_module() -> KMap {
 
     result.add_fn("open", {
         |ctx| match ctx.args() {
-            [Str(path)] => match fs::canonicalize(path.as_str()) {
-                Ok(path) => match fs::File::open(&path) {
-                    Ok(file) => Ok(File::system_file(file, path)),
-                    Err(error) => runtime_error!("io.open: Error while opening path: {error}"),
-                },
-                Err(_) => runtime_error!("io.open: Failed to canonicalize path"),
+            [Str(path)] => match ctx.vm.filesystem().open(Path::new(path.as_str())) {
+                Ok(file) => Ok(File(file).into()),
+                Err(error) => runtime_error!("io.open: Error while opening '{path}': {error}"),
             },
             unexpected => type_error_with_slice("a path String as argument", unexpected),
         }
@@ -105,8 +101,18 @@ pub fn make_module() -> KMap {
         result.map(|_| Null)
     });
 
+    result.add_fn("read_bytes", |ctx| match ctx.args() {
+        [Str(path)] => match ctx.vm.filesystem().read_bytes(Path::new(path.as_str())) {
+            Ok(result) => Ok(Bytes::from(result).into()),
+            Err(error) => {
+                runtime_error!("io.read_bytes: Unable to read file '{path}': {error}")
+            }
+        },
+        unexpected => type_error_with_slice("a path String as argument", unexpected),
+    });
+
     result.add_fn("read_to_string", |ctx| match ctx.args() {
-        [Str(path)] => match fs::read_to_string(Path::new(path.as_str())) {
+        [Str(path)] => match ctx.vm.filesystem().read_to_string(Path::new(path.as_str())) {
             Ok(result) => Ok(result.into()),
             Err(error) => {
                 runtime_error!("io.read_to_string: Unable to read file '{path}': {error}")
@@ -119,7 +125,7 @@ pub fn make_module() -> KMap {
         |ctx| match ctx.args() {
             [Str(path)] => {
                 let path = Path::new(path.as_str());
-                match fs::remove_file(path) {
+                match ctx.vm.filesystem().remove_file(path) {
                     Ok(_) => Ok(KValue::Null),
                     Err(error) => runtime_error!(
                         "io.remove_file: Error while removing file '{}': {error}",
@@ -216,6 +222,13 @@ impl File {
     #[koto_method]
     fn write(ctx: MethodContext<Self>) -> Result<KValue> {
         match ctx.args {
+            [KValue::Object(o)] if o.is_a::<Bytes>() => {
+                let bytes = o.cast::<Bytes>()?.clone();
+                ctx.instance_mut()?
+                    .0
+                    .write(bytes.as_slice())
+                    .map(|_| KValue::Null)
+            }
             [value] => {
                 let mut display_context = DisplayContext::with_vm(ctx.vm);
                 value.display(&mut display_context)?;
@@ -257,7 +270,117 @@ impl From<File> for KValue {
     }
 }
 
-struct BufferedSystemFile<T>
+/// The Bytes type used in the io module, containing a sequence of binary data
+#[derive(Clone, KotoCopy, KotoType)]
+pub struct Bytes(Ptr<[u8]>);
+
+#[koto_impl(runtime = crate)]
+impl Bytes {
+    // The maximum number of bytes that are included when displaying Bytes
+    const DISPLAY_LIMIT: usize = 16;
+
+    /// Returns the contained bytes as a slice
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+
+    #[koto_method]
+    fn to_list(&self) -> KValue {
+        let result = self.0.iter().map(|byte| KValue::from(*byte)).collect();
+        KValue::List(KList::with_data(result))
+    }
+
+    #[koto_method(name = "to_string")]
+    fn koto_to_string(&self) -> Result<KValue> {
+        match std::str::from_utf8(&self.0) {
+            Ok(s) => Ok(s.into()),
+            Err(error) => runtime_error!("Bytes.to_string: Invalid UTF-8 data ({error})"),
+        }
+    }
+}
+
+impl KotoObject for Bytes {
+    fn display(&self, ctx: &mut DisplayContext) -> Result<()> {
+        ctx.append(Self::type_static());
+        ctx.append('[');
+        for (i, byte) in self.0.iter().take(Self::DISPLAY_LIMIT).enumerate() {
+            if i > 0 {
+                ctx.append(' ');
+            }
+            ctx.append(format!("{byte:02x}"));
+        }
+        if self.0.len() > Self::DISPLAY_LIMIT {
+            ctx.append(" ...");
+        }
+        ctx.append(']');
+        Ok(())
+    }
+
+    fn index(&self, index: &KValue) -> Result<KValue> {
+        match index {
+            KValue::Number(n) => {
+                let size = self.0.len();
+                match n.as_index(size) {
+                    Some(index) => Ok(self.0[index].into()),
+                    None => runtime_error!("Index out of bounds - index: {n}, size: {size}"),
+                }
+            }
+            KValue::Range(range) => {
+                let result = &self.0[range.indices(self.0.len())];
+                Ok(Self::from(result).into())
+            }
+            unexpected => type_error("Number or Range", unexpected),
+        }
+    }
+
+    fn size(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+
+    fn add(&self, rhs: &KValue) -> Result<KValue> {
+        match rhs {
+            KValue::Object(o) if o.is_a::<Self>() => {
+                let rhs = o.cast::<Self>()?;
+                let mut result = Vec::with_capacity(self.0.len() + rhs.0.len());
+                result.extend_from_slice(&self.0);
+                result.extend_from_slice(&rhs.0);
+                Ok(Self::from(result).into())
+            }
+            unexpected => type_error(Self::type_static(), unexpected),
+        }
+    }
+
+    fn equal(&self, rhs: &KValue) -> Result<bool> {
+        match rhs {
+            KValue::Object(o) if o.is_a::<Self>() => Ok(self.0 == o.cast::<Self>()?.0),
+            _ => Ok(false),
+        }
+    }
+
+    fn not_equal(&self, rhs: &KValue) -> Result<bool> {
+        self.equal(rhs).map(|result| !result)
+    }
+}
+
+impl From<Vec<u8>> for Bytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes.into())
+    }
+}
+
+impl From<&[u8]> for Bytes {
+    fn from(bytes: &[u8]) -> Self {
+        Self(bytes.into())
+    }
+}
+
+impl From<Bytes> for KValue {
+    fn from(bytes: Bytes) -> Self {
+        KObject::from(bytes).into()
+    }
+}
+
+pub(crate) struct BufferedSystemFile<T>
 where
     T: Write + KotoSend + KotoSync,
 {
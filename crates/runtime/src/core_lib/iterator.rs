@@ -4,7 +4,12 @@ pub mod adaptors;
 pub mod generators;
 pub mod peekable;
 
-use crate::{derive::*, prelude::*, KIteratorOutput as Output, Result};
+use crate::{
+    derive::*,
+    memory_usage::{map_entries_size, values_size},
+    prelude::*,
+    KIteratorOutput as Output, Result,
+};
 
 /// Initializes the `iterator` core library module
 pub fn make_module() -> KMap {
@@ -725,9 +730,13 @@ pub fn make_module() -> KMap {
                 let iterable = iterable.clone();
                 let iterator = ctx.vm.make_iterator(iterable)?;
                 let (size_hint, _) = iterator.size_hint();
+                ctx.vm.track_value_allocation(size_hint)?;
                 let mut result = ValueVec::with_capacity(size_hint);
 
                 for output in iterator.map(collect_pair) {
+                    if result.len() >= size_hint {
+                        ctx.vm.track_value_allocation(1)?;
+                    }
                     match output {
                         Output::Value(value) => result.push(value),
                         Output::Error(error) => return Err(error),
@@ -735,7 +744,10 @@ pub fn make_module() -> KMap {
                     }
                 }
 
-                Ok(KValue::List(KList::with_data(result)))
+                let count = size_hint.max(result.len());
+                let result = KList::with_data(result);
+                ctx.vm.assign_allocation(&result, values_size(count));
+                Ok(KValue::List(result))
             }
             (_, unexpected) => type_error_with_slice(expected_error, unexpected),
         }
@@ -749,9 +761,15 @@ pub fn make_module() -> KMap {
                 let iterable = iterable.clone();
                 let iterator = ctx.vm.make_iterator(iterable)?;
                 let (size_hint, _) = iterator.size_hint();
+                ctx.vm.track_map_entry_allocation(size_hint)?;
                 let mut result = ValueMap::with_capacity(size_hint);
+                let mut count = size_hint;
 
-                for output in iterator {
+                for (i, output) in iterator.enumerate() {
+                    if i >= size_hint {
+                        ctx.vm.track_map_entry_allocation(1)?;
+                        count += 1;
+                    }
                     let (key, value) = match output {
                         Output::ValuePair(key, value) => (key, value),
                         Output::Value(KValue::Tuple(t)) if t.len() == 2 => {
@@ -763,10 +781,12 @@ pub fn make_module() -> KMap {
                         Output::Error(error) => return Err(error),
                     };
 
-                    result.insert(ValueKey::try_from(key)?, value);
+                    result.insert(ctx.vm.make_value_key(key)?, value);
                 }
 
-                Ok(KValue::Map(KMap::with_data(result)))
+                let result = KMap::with_data(result);
+                ctx.vm.assign_allocation(&result, map_entries_size(count));
+                Ok(KValue::Map(result))
             }
             (_, unexpected) => type_error_with_slice(expected_error, unexpected),
         }
@@ -790,7 +810,10 @@ pub fn make_module() -> KMap {
                     };
                 }
 
-                Ok(display_context.result().into())
+                let result = KString::from(display_context.result());
+                ctx.vm.track_allocation(result.len())?;
+                ctx.vm.assign_allocation(&result, result.len());
+                Ok(result.into())
             }
             (_, unexpected) => type_error_with_slice(expected_error, unexpected),
         }
@@ -804,9 +827,13 @@ pub fn make_module() -> KMap {
                 let iterable = iterable.clone();
                 let iterator = ctx.vm.make_iterator(iterable)?;
                 let (size_hint, _) = iterator.size_hint();
+                ctx.vm.track_value_allocation(size_hint)?;
                 let mut result = Vec::with_capacity(size_hint);
 
                 for output in iterator.map(collect_pair) {
+                    if result.len() >= size_hint {
+                        ctx.vm.track_value_allocation(1)?;
+                    }
                     match output {
                         Output::Value(value) => result.push(value),
                         Output::Error(error) => return Err(error),
@@ -814,7 +841,10 @@ pub fn make_module() -> KMap {
                     }
                 }
 
-                Ok(KValue::Tuple(result.into()))
+                let count = size_hint.max(result.len());
+                let result = KTuple::from(result);
+                ctx.vm.assign_allocation(&result, values_size(count));
+                Ok(KValue::Tuple(result))
             }
             (_, unexpected) => type_error_with_slice(expected_error, unexpected),
         }
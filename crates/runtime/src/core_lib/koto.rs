@@ -1,9 +1,10 @@
 //! The `koto` core library module
 
 use crate::prelude::*;
+use crate::types::{KListWeak, KMapWeak, KObjectWeak};
 use crate::Result;
 use koto_bytecode::CompilerSettings;
-use koto_derive::{KotoCopy, KotoType};
+use koto_derive::{koto_impl, koto_method, KotoCopy, KotoType};
 use koto_memory::Ptr;
 use std::hash::{Hash, Hasher};
 
@@ -15,7 +16,7 @@ pub fn make_module() -> KMap {
 
     result.add_fn("copy", |ctx| match ctx.args() {
         [KValue::Iterator(iter)] => Ok(iter.make_copy()?.into()),
-        [KValue::List(l)] => Ok(KList::with_data(l.data().clone()).into()),
+        [KValue::List(l)] => Ok(KList::from_slice(&l.data()).into()),
         [KValue::Map(m)] => {
             let result = KMap::with_contents(
                 m.data().clone(),
@@ -36,14 +37,26 @@ pub fn make_module() -> KMap {
     result.add_fn("exports", |ctx| Ok(KValue::Map(ctx.vm.exports().clone())));
 
     result.add_fn("hash", |ctx| match ctx.args() {
-        [value] => match ValueKey::try_from(value.clone()) {
-            Ok(key) => {
-                let mut hasher = KotoHasher::default();
-                key.hash(&mut hasher);
-                Ok(hasher.finish().into())
+        [value] => {
+            let value = value.clone();
+            let key = match &value {
+                // Errors thrown by @hash are propagated,
+                // a non-hashable result from @hash produces null like other non-hashable values.
+                KValue::Map(m) if m.contains_meta_key(&UnaryOp::Hash.into()) => {
+                    let hash = ctx.vm.run_unary_op(UnaryOp::Hash, value.clone())?;
+                    ValueKey::with_hash(value, hash)
+                }
+                _ => ValueKey::try_from(value),
+            };
+            match key {
+                Ok(key) => {
+                    let mut hasher = KotoHasher::default();
+                    key.hash(&mut hasher);
+                    Ok(hasher.finish().into())
+                }
+                Err(_) => Ok(KValue::Null),
             }
-            Err(_) => Ok(KValue::Null),
-        },
+        }
         unexpected => type_error_with_slice("a single argument", unexpected),
     });
 
@@ -77,6 +90,13 @@ pub fn make_module() -> KMap {
         unexpected => type_error_with_slice("a single String or Chunk", unexpected),
     });
 
+    result.add_fn("weak", |ctx| match ctx.args() {
+        [KValue::List(l)] => Ok(Weak(WeakRef::List(l.downgrade())).into()),
+        [KValue::Map(m)] => Ok(Weak(WeakRef::Map(m.downgrade())).into()),
+        [KValue::Object(o)] => Ok(Weak(WeakRef::Object(o.downgrade())).into()),
+        unexpected => type_error_with_slice("a List, Map, or Object", unexpected),
+    });
+
     result
 }
 
@@ -124,3 +144,38 @@ impl From<Chunk> for KValue {
         KObject::from(chunk).into()
     }
 }
+
+/// The Weak type used in the koto module, containing a weak reference to a value
+///
+/// See `koto.weak`.
+#[derive(Clone, KotoCopy, KotoType)]
+pub struct Weak(WeakRef);
+
+#[derive(Clone)]
+enum WeakRef {
+    List(KListWeak),
+    Map(KMapWeak),
+    Object(KObjectWeak),
+}
+
+#[koto_impl(runtime = crate)]
+impl Weak {
+    /// Returns the referenced value, or Null if the value has been dropped
+    #[koto_method]
+    fn get(&self) -> KValue {
+        let result = match &self.0 {
+            WeakRef::List(l) => l.upgrade().map(KValue::from),
+            WeakRef::Map(m) => m.upgrade().map(KValue::from),
+            WeakRef::Object(o) => o.upgrade().map(KValue::from),
+        };
+        result.unwrap_or_default()
+    }
+}
+
+impl KotoObject for Weak {}
+
+impl From<Weak> for KValue {
+    fn from(weak: Weak) -> Self {
+        KObject::from(weak).into()
+    }
+}
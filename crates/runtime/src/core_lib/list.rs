@@ -2,9 +2,9 @@
 
 use super::{
     iterator::collect_pair,
-    value_sort::{sort_by_key, sort_values},
+    value_sort::{sort_by_comparison, sort_by_key, sort_values},
 };
-use crate::prelude::*;
+use crate::{memory_usage::values_size, prelude::*, Result};
 use std::{cmp::Ordering, ops::DerefMut};
 
 /// Initializes the `list` core library module
@@ -30,7 +30,12 @@ pub fn make_module() -> KMap {
             (KValue::List(l), [value]) => {
                 let l = l.clone();
                 let value = value.clone();
-                for candidate in l.data().iter() {
+                // The list is re-borrowed for each candidate so that the list is accessible during
+                // comparisons.
+                for i in 0..l.len() {
+                    let Some(candidate) = l.data().get(i).cloned() else {
+                        break;
+                    };
                     match ctx
                         .vm
                         .run_binary_op(BinaryOp::Equal, value.clone(), candidate.clone())
@@ -57,10 +62,12 @@ pub fn make_module() -> KMap {
 
         match ctx.instance_and_args(is_list, expected_error)? {
             (KValue::List(l), [KValue::List(other)]) => {
+                ctx.vm.track_list_growth(l, other.len())?;
                 l.data_mut().extend(other.data().iter().cloned());
                 Ok(KValue::List(l.clone()))
             }
             (KValue::List(l), [KValue::Tuple(other)]) => {
+                ctx.vm.track_list_growth(l, other.len())?;
                 l.data_mut().extend(other.iter().cloned());
                 Ok(KValue::List(l.clone()))
             }
@@ -69,20 +76,26 @@ pub fn make_module() -> KMap {
                 let iterable = iterable.clone();
                 let iterator = ctx.vm.make_iterator(iterable)?;
 
-                {
-                    let mut list_data = l.data_mut();
-                    let (size_hint, _) = iterator.size_hint();
-                    list_data.reserve(size_hint);
-
-                    for value in iterator.map(collect_pair) {
-                        match value {
-                            KIteratorOutput::Value(value) => list_data.push(value.clone()),
-                            KIteratorOutput::Error(error) => return Err(error),
-                            _ => unreachable!(),
-                        }
+                // The iterator's values are collected before being added to the list, so that the
+                // list is accessible while the iterator is running.
+                let (size_hint, _) = iterator.size_hint();
+                ctx.vm.track_value_allocation(size_hint)?;
+                let mut new_values = Vec::with_capacity(size_hint);
+
+                for (i, value) in iterator.map(collect_pair).enumerate() {
+                    if i >= size_hint {
+                        ctx.vm.track_value_allocation(1)?;
+                    }
+                    match value {
+                        KIteratorOutput::Value(value) => new_values.push(value),
+                        KIteratorOutput::Error(error) => return Err(error),
+                        _ => unreachable!(),
                     }
                 }
 
+                let count = size_hint.max(new_values.len());
+                l.data_mut().extend(new_values);
+                ctx.vm.assign_allocation(&l, values_size(count));
                 Ok(KValue::List(l))
             }
             (_, unexpected) => type_error_with_slice(expected_error, unexpected),
@@ -142,6 +155,7 @@ pub fn make_module() -> KMap {
                     return runtime_error!("list.insert: Index out of bounds");
                 }
 
+                ctx.vm.track_list_growth(l, 1)?;
                 l.data_mut().insert(index, value.clone());
                 Ok(KValue::List(l.clone()))
             }
@@ -187,6 +201,7 @@ pub fn make_module() -> KMap {
 
         match ctx.instance_and_args(is_list, expected_error)? {
             (KValue::List(l), [value]) => {
+                ctx.vm.track_list_growth(l, 1)?;
                 l.data_mut().push(value.clone());
                 Ok(KValue::List(l.clone()))
             }
@@ -215,19 +230,21 @@ pub fn make_module() -> KMap {
     });
 
     result.add_fn("resize", |ctx| {
-        let expected_error = "a List, a non-negative Number, and an optional Value";
-
-        match ctx.instance_and_args(is_list, expected_error)? {
-            (KValue::List(l), [KValue::Number(n)]) if *n >= 0.0 => {
-                l.data_mut().resize(n.into(), KValue::Null);
-                Ok(KValue::List(l.clone()))
-            }
-            (KValue::List(l), [KValue::Number(n), value]) if *n >= 0.0 => {
-                l.data_mut().resize(n.into(), value.clone());
-                Ok(KValue::List(l.clone()))
-            }
-            (_, unexpected) => type_error_with_slice(expected_error, unexpected),
+        let (l, mut args) = ArgParser::with_list_instance("list.resize", ctx)?;
+        let new_size = args.number("new_size")?;
+        let value = args.optional_value().cloned().unwrap_or_default();
+        args.finish()?;
+
+        if new_size < 0.0 {
+            return runtime_error!(
+                "list.resize: Expected a non-negative size, but found {new_size}"
+            );
         }
+
+        ctx.vm
+            .track_list_growth(l, usize::from(new_size).saturating_sub(l.len()))?;
+        l.data_mut().resize(new_size.into(), value);
+        Ok(KValue::List(l.clone()))
     });
 
     result.add_fn("resize_with", |ctx| {
@@ -243,6 +260,7 @@ pub fn make_module() -> KMap {
                 match len.cmp(&new_size) {
                     Ordering::Greater => l.data_mut().truncate(new_size),
                     Ordering::Less => {
+                        ctx.vm.track_list_growth(&l, new_size - len)?;
                         l.data_mut().reserve(new_size);
                         for _ in 0..new_size - len {
                             let new_value = ctx.vm.call_function(f.clone(), &[])?;
@@ -269,11 +287,15 @@ pub fn make_module() -> KMap {
 
                     let mut write_index = 0;
                     for read_index in 0..l.len() {
-                        let value = l.data()[read_index].clone();
+                        let Some(value) = l.data().get(read_index).cloned() else {
+                            break;
+                        };
                         match ctx.vm.call_function(f.clone(), value.clone()) {
                             Ok(KValue::Bool(result)) => {
                                 if result {
-                                    l.data_mut()[write_index] = value;
+                                    if let Some(slot) = l.data_mut().get_mut(write_index) {
+                                        *slot = value;
+                                    }
                                     write_index += 1;
                                 }
                             }
@@ -286,40 +308,39 @@ pub fn make_module() -> KMap {
                             Err(error) => return Err(error),
                         }
                     }
-                    l.data_mut().resize(write_index, KValue::Null);
+                    l.data_mut().truncate(write_index);
                     l
                 }
                 (KValue::List(l), [value]) => {
                     let l = l.clone();
                     let value = value.clone();
 
-                    let mut error = None;
-                    l.data_mut().retain(|x| {
-                        if error.is_some() {
-                            return true;
-                        }
+                    let mut write_index = 0;
+                    for read_index in 0..l.len() {
+                        let Some(x) = l.data().get(read_index).cloned() else {
+                            break;
+                        };
                         match ctx
                             .vm
                             .run_binary_op(BinaryOp::Equal, x.clone(), value.clone())
                         {
-                            Ok(KValue::Bool(true)) => true,
-                            Ok(KValue::Bool(false)) => false,
+                            Ok(KValue::Bool(true)) => {
+                                if let Some(slot) = l.data_mut().get_mut(write_index) {
+                                    *slot = x;
+                                }
+                                write_index += 1;
+                            }
+                            Ok(KValue::Bool(false)) => {}
                             Ok(unexpected) => {
-                                error = Some(type_error_with_slice(
+                                return type_error_with_slice(
                                     "a Bool from the equality comparison",
                                     &[unexpected],
-                                ));
-                                true
-                            }
-                            Err(e) => {
-                                error = Some(Err(e));
-                                true
+                                );
                             }
+                            Err(e) => return Err(e),
                         }
-                    });
-                    if let Some(error) = error {
-                        return error;
                     }
+                    l.data_mut().truncate(write_index);
                     l
                 }
                 (_, unexpected) => return type_error_with_slice(expected_error, unexpected),
@@ -347,14 +368,20 @@ pub fn make_module() -> KMap {
         match ctx.instance_and_args(is_list, expected_error)? {
             (KValue::List(l), []) => {
                 let l = l.clone();
-                let mut data = l.data_mut();
-                sort_values(ctx.vm, &mut data)?;
-                Ok(KValue::List(l.clone()))
+                // A copy of the list's data is sorted, so that the list is accessible during
+                // comparisons.
+                let mut sorted = l.data().to_vec();
+                sort_values(ctx.vm, &mut sorted)?;
+                check_size_after_sort("list.sort", &l, sorted.len())?;
+                *l.data_mut() = sorted.into();
+                Ok(KValue::List(l))
             }
             (KValue::List(l), [f]) if f.is_callable() => {
                 let l = l.clone();
 
-                let sorted = sort_by_key(ctx.vm, l.data().as_ref(), f.clone())?;
+                let values = l.data().to_vec();
+                let sorted = sort_by_key(ctx.vm, &values, f.clone())?;
+                check_size_after_sort("list.sort", &l, values.len())?;
 
                 for (target_value, (_key, source_value)) in
                     l.data_mut().iter_mut().zip(sorted.into_iter())
@@ -368,6 +395,27 @@ pub fn make_module() -> KMap {
         }
     });
 
+    result.add_fn("sort_by", |ctx| {
+        let expected_error = "a List and a comparison function";
+
+        match ctx.instance_and_args(is_list, expected_error)? {
+            (KValue::List(l), [f]) if f.is_callable() => {
+                let l = l.clone();
+                let f = f.clone();
+
+                // A copy of the list's data is sorted, so that the comparison function is able to
+                // access the list, and so that the list is left unmodified if an error is thrown.
+                let mut sorted = l.data().to_vec();
+                sort_by_comparison(ctx.vm, &mut sorted, f)?;
+                check_size_after_sort("list.sort_by", &l, sorted.len())?;
+                *l.data_mut() = sorted.into();
+
+                Ok(KValue::List(l))
+            }
+            (_, unexpected) => type_error_with_slice(expected_error, unexpected),
+        }
+    });
+
     result.add_fn("swap", |ctx| {
         let expected_error = "two Lists";
 
@@ -384,7 +432,7 @@ pub fn make_module() -> KMap {
         let expected_error = "a List";
 
         match ctx.instance_and_args(is_list, expected_error)? {
-            (KValue::List(l), []) => Ok(KValue::Tuple(l.data().as_slice().into())),
+            (KValue::List(l), []) => Ok(KValue::Tuple(l.data()[..].into())),
             (_, unexpected) => type_error_with_slice(expected_error, unexpected),
         }
     });
@@ -397,10 +445,15 @@ pub fn make_module() -> KMap {
                 let l = l.clone();
                 let f = f.clone();
 
-                for value in l.data_mut().iter_mut() {
-                    *value = match ctx.vm.call_function(f.clone(), value.clone()) {
-                        Ok(result) => result,
-                        Err(error) => return Err(error),
+                // The list is re-borrowed for each value so that the list is accessible in the
+                // transform function.
+                for i in 0..l.len() {
+                    let Some(value) = l.data().get(i).cloned() else {
+                        break;
+                    };
+                    let result = ctx.vm.call_function(f.clone(), value)?;
+                    if let Some(value) = l.data_mut().get_mut(i) {
+                        *value = result;
                     }
                 }
 
@@ -413,6 +466,17 @@ pub fn make_module() -> KMap {
     result
 }
 
+// Sorting is performed on a copy of the list's data, so any changes made to the list by functions
+// called during sorting are discarded when the sorted data is written back to the list.
+// Changes to the list's size are reported as errors rather than being silently discarded.
+fn check_size_after_sort(function_name: &str, list: &KList, expected_size: usize) -> Result<()> {
+    if list.len() == expected_size {
+        Ok(())
+    } else {
+        runtime_error!("{function_name}: The list's size was modified during sorting")
+    }
+}
+
 fn is_list(value: &KValue) -> bool {
     matches!(value, KValue::List(_))
 }
@@ -1,7 +1,7 @@
 //! The `map` core library module
 
 use super::{iterator::adaptors, value_sort::compare_values};
-use crate::{prelude::*, Result};
+use crate::{memory_usage::map_entries_size, prelude::*, types::map_path, Result};
 use std::cmp::Ordering;
 
 /// Initializes the `map` core library module
@@ -25,7 +25,8 @@ pub fn make_module() -> KMap {
 
         match map_instance_and_args(ctx, expected_error)? {
             (KValue::Map(m), [key]) => {
-                let result = m.data().contains_key(&ValueKey::try_from(key.clone())?);
+                let (m, key) = (m.clone(), key.clone());
+                let result = m.data().contains_key(&ctx.vm.make_value_key(key)?);
                 Ok(result.into())
             }
             (_, unexpected) => type_error_with_slice(expected_error, unexpected),
@@ -37,6 +38,12 @@ pub fn make_module() -> KMap {
 
         match map_instance_and_args(ctx, expected_error)? {
             (KValue::Map(m), [KValue::Map(other)]) => {
+                let new_entries = other
+                    .data()
+                    .keys()
+                    .filter(|key| !m.data().contains_key(*key))
+                    .count();
+                ctx.vm.track_map_growth(m, new_entries)?;
                 m.data_mut().extend(
                     other
                         .data()
@@ -50,28 +57,36 @@ pub fn make_module() -> KMap {
                 let iterable = iterable.clone();
                 let iterator = ctx.vm.make_iterator(iterable)?;
 
-                {
-                    let mut map_data = m.data_mut();
-                    let (size_hint, _) = iterator.size_hint();
-                    map_data.reserve(size_hint);
-
-                    for output in iterator {
-                        use KIteratorOutput as Output;
-                        let (key, value) = match output {
-                            Output::ValuePair(key, value) => (key, value),
-                            Output::Value(KValue::Tuple(t)) if t.len() == 2 => {
-                                let key = t[0].clone();
-                                let value = t[1].clone();
-                                (key, value)
-                            }
-                            Output::Value(value) => (value, KValue::Null),
-                            Output::Error(error) => return Err(error),
-                        };
+                // The iterator's entries are collected before being added to the map, so that the
+                // map is accessible while the iterator is running.
+                let (size_hint, _) = iterator.size_hint();
+                ctx.vm.track_map_entry_allocation(size_hint)?;
+                let mut new_entries = Vec::with_capacity(size_hint);
 
-                        map_data.insert(ValueKey::try_from(key.clone())?, value);
+                for (i, output) in iterator.enumerate() {
+                    if i >= size_hint {
+                        ctx.vm.track_map_entry_allocation(1)?;
                     }
+                    use KIteratorOutput as Output;
+                    let (key, value) = match output {
+                        Output::ValuePair(key, value) => (key, value),
+                        Output::Value(KValue::Tuple(t)) if t.len() == 2 => {
+                            let key = t[0].clone();
+                            let value = t[1].clone();
+                            (key, value)
+                        }
+                        Output::Value(value) => (value, KValue::Null),
+                        Output::Error(error) => return Err(error),
+                    };
+
+                    new_entries.push((ctx.vm.make_value_key(key)?, value));
                 }
 
+                // The map's allocation is capped to its size when memory usage is checked,
+                // so overwritten entries don't need to be excluded here.
+                let count = size_hint.max(new_entries.len());
+                m.data_mut().extend(new_entries);
+                ctx.vm.assign_allocation(&m, map_entries_size(count));
                 Ok(KValue::Map(m))
             }
             (_, unexpected) => type_error_with_slice(expected_error, unexpected),
@@ -83,15 +98,13 @@ pub fn make_module() -> KMap {
             let expected_error = "a Map and a key, with an optional default value";
 
             match map_instance_and_args(ctx, expected_error)? {
-                (KValue::Map(map), [key]) => (map, key, &KValue::Null),
-                (KValue::Map(map), [key, default]) => (map, key, default),
+                (KValue::Map(map), [key]) => (map.clone(), key.clone(), KValue::Null),
+                (KValue::Map(map), [key, default]) => (map.clone(), key.clone(), default.clone()),
                 (_, unexpected) => return type_error_with_slice(expected_error, unexpected),
             }
         };
 
-        let result = map
-            .get(&ValueKey::try_from(key.clone())?)
-            .unwrap_or_else(|| default.clone());
+        let result = map.get(&ctx.vm.make_value_key(key)?).unwrap_or(default);
 
         Ok(result)
     });
@@ -133,25 +146,44 @@ pub fn make_module() -> KMap {
         }
     });
 
+    result.add_fn("get_path", |ctx| {
+        let (map, path, default) = {
+            let expected_error = "a Map and a path String, with an optional default value";
+
+            match map_instance_and_args(ctx, expected_error)? {
+                (KValue::Map(map), [KValue::Str(path)]) => (map, path, &KValue::Null),
+                (KValue::Map(map), [KValue::Str(path), default]) => (map, path, default),
+                (_, unexpected) => return type_error_with_slice(expected_error, unexpected),
+            }
+        };
+
+        let segments = map_path::parse_path(path)?;
+        let result = map_path::get_path(&map.data(), &segments).unwrap_or_else(|| default.clone());
+
+        Ok(result)
+    });
+
     result.add_fn("insert", |ctx| {
         let expected_error = "a Map and key (with optional Value to insert)";
 
         match map_instance_and_args(ctx, expected_error)? {
-            (KValue::Map(m), [key]) => match m
-                .data_mut()
-                .insert(ValueKey::try_from(key.clone())?, KValue::Null)
-            {
-                Some(old_value) => Ok(old_value),
-                None => Ok(KValue::Null),
-            },
+            (KValue::Map(m), [key]) => {
+                let (m, key) = (m.clone(), key.clone());
+                let key = ctx.vm.make_value_key(key)?;
+                if !m.data().contains_key(&key) {
+                    ctx.vm.track_map_growth(&m, 1)?;
+                }
+                let old_value = m.data_mut().insert(key, KValue::Null);
+                Ok(old_value.unwrap_or(KValue::Null))
+            }
             (KValue::Map(m), [key, value]) => {
-                match m
-                    .data_mut()
-                    .insert(ValueKey::try_from(key.clone())?, value.clone())
-                {
-                    Some(old_value) => Ok(old_value),
-                    None => Ok(KValue::Null),
+                let (m, key, value) = (m.clone(), key.clone(), value.clone());
+                let key = ctx.vm.make_value_key(key)?;
+                if !m.data().contains_key(&key) {
+                    ctx.vm.track_map_growth(&m, 1)?;
                 }
+                let old_value = m.data_mut().insert(key, value);
+                Ok(old_value.unwrap_or(KValue::Null))
             }
             (_, unexpected) => type_error_with_slice(expected_error, unexpected),
         }
@@ -183,10 +215,22 @@ pub fn make_module() -> KMap {
 
         match map_instance_and_args(ctx, expected_error)? {
             (KValue::Map(m), [key]) => {
-                match m.data_mut().shift_remove(&ValueKey::try_from(key.clone())?) {
-                    Some(old_value) => Ok(old_value),
-                    None => Ok(KValue::Null),
-                }
+                let (m, key) = (m.clone(), key.clone());
+                let key = ctx.vm.make_value_key(key)?;
+                let old_value = m.data_mut().shift_remove(&key);
+                Ok(old_value.unwrap_or(KValue::Null))
+            }
+            (_, unexpected) => type_error_with_slice(expected_error, unexpected),
+        }
+    });
+
+    result.add_fn("set_path", |ctx| {
+        let expected_error = "a Map, a path String, and a Value";
+
+        match map_instance_and_args(ctx, expected_error)? {
+            (KValue::Map(m), [KValue::Str(path), value]) => {
+                m.set_path_with_vm(path, value.clone(), Some(ctx.vm))?;
+                Ok(KValue::Map(m.clone()))
             }
             (_, unexpected) => type_error_with_slice(expected_error, unexpected),
         }
@@ -197,27 +241,8 @@ pub fn make_module() -> KMap {
 
         match map_instance_and_args(ctx, expected_error)? {
             (KValue::Map(m), []) => {
-                let mut error = None;
-                m.data_mut().sort_by(|key_a, _, key_b, _| {
-                    if error.is_some() {
-                        return Ordering::Equal;
-                    }
-
-                    match key_a.partial_cmp(key_b) {
-                        Some(ordering) => ordering,
-                        None => {
-                            // This should never happen, ValueKeys can only be made with sortable values
-                            error = Some(runtime_error!("Invalid map key encountered"));
-                            Ordering::Equal
-                        }
-                    }
-                });
-
-                if let Some(error) = error {
-                    error
-                } else {
-                    Ok(KValue::Map(m.clone()))
-                }
+                m.data_mut().sort_keys();
+                Ok(KValue::Map(m.clone()))
             }
             (KValue::Map(m), [f]) if f.is_callable() => {
                 let m = m.clone();
@@ -235,8 +260,10 @@ pub fn make_module() -> KMap {
                     Ok(value)
                 };
 
+                // A copy of the map's data is sorted, so that the map is accessible during sorting.
+                let mut sorted = m.data().clone();
                 let mut cache = ValueMap::with_capacity(m.len());
-                m.data_mut().sort_by(|key_a, value_a, key_b, value_b| {
+                sorted.sort_by(|key_a, value_a, key_b, value_b| {
                     if error.is_some() {
                         return Ordering::Equal;
                     }
@@ -273,7 +300,11 @@ pub fn make_module() -> KMap {
 
                 if let Some(error) = error {
                     error
+                } else if m.len() != sorted.len() {
+                    // Changes made to the map's size would be lost when writing the sorted entries
+                    runtime_error!("map.sort: The map's size was modified during sorting")
                 } else {
+                    *m.data_mut() = sorted;
                     Ok(KValue::Map(m))
                 }
             }
@@ -285,20 +316,16 @@ pub fn make_module() -> KMap {
         let expected_error = "a Map, key, optional default Value, and update function";
 
         match map_instance_and_args(ctx, expected_error)? {
-            (KValue::Map(m), [key, f]) if f.is_callable() => do_map_update(
-                m.clone(),
-                ValueKey::try_from(key.clone())?,
-                KValue::Null,
-                f.clone(),
-                ctx.vm,
-            ),
-            (KValue::Map(m), [key, default, f]) if f.is_callable() => do_map_update(
-                m.clone(),
-                ValueKey::try_from(key.clone())?,
-                default.clone(),
-                f.clone(),
-                ctx.vm,
-            ),
+            (KValue::Map(m), [key, f]) if f.is_callable() => {
+                let (m, key, f) = (m.clone(), key.clone(), f.clone());
+                let key = ctx.vm.make_value_key(key)?;
+                do_map_update(m, key, KValue::Null, f, ctx.vm)
+            }
+            (KValue::Map(m), [key, default, f]) if f.is_callable() => {
+                let (m, key, default, f) = (m.clone(), key.clone(), default.clone(), f.clone());
+                let key = ctx.vm.make_value_key(key)?;
+                do_map_update(m, key, default, f, ctx.vm)
+            }
             (_, unexpected) => type_error_with_slice(expected_error, unexpected),
         }
     });
@@ -339,6 +366,7 @@ fn do_map_update(
     vm: &mut KotoVm,
 ) -> Result<KValue> {
     if !map.data().contains_key(&key) {
+        vm.track_map_growth(&map, 1)?;
         map.data_mut().insert(key.clone(), default);
     }
     let value = map.get(&key).unwrap();
@@ -89,12 +89,12 @@ pub fn make_module() -> KMap {
     number_fn!(ceil);
 
     result.add_fn("clamp", |ctx| {
-        let expected_error = "three Numbers";
+        let (x, mut args) = ArgParser::with_number_instance("number.clamp", ctx)?;
+        let min = args.number("min")?;
+        let max = args.number("max")?;
+        args.finish()?;
 
-        match ctx.instance_and_args(is_number, expected_error)? {
-            (Number(x), [Number(a), Number(b)]) => Ok(Number(*a.max(b.min(x)))),
-            (_, unexpected) => type_error_with_slice(expected_error, unexpected),
-        }
+        Ok(Number(min.max(max.min(x))))
     });
 
     number_f64_fn!(cos);
@@ -129,15 +129,12 @@ pub fn make_module() -> KMap {
     });
 
     result.add_fn("lerp", |ctx| {
-        let expected_error = "three Numbers";
+        let (a, mut args) = ArgParser::with_number_instance("number.lerp", ctx)?;
+        let b = args.number("b")?;
+        let t = args.number("t")?;
+        args.finish()?;
 
-        match ctx.instance_and_args(is_number, expected_error)? {
-            (Number(a), [Number(b), Number(t)]) => {
-                let result = *a + (b - a) * *t;
-                Ok(result.into())
-            }
-            (_, unexpected) => type_error_with_slice(expected_error, unexpected),
-        }
+        Ok((a + (b - a) * t).into())
     });
 
     number_f64_fn!(ln);
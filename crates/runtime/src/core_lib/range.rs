@@ -1,6 +1,6 @@
 //! The `range` core library module
 
-use crate::prelude::*;
+use crate::{prelude::*, Result};
 
 /// Initializes the `range` core library module
 pub fn make_module() -> KMap {
@@ -123,9 +123,149 @@ pub fn make_module() -> KMap {
         }
     });
 
+    result.add_fn("with_step", |ctx| {
+        let (r, mut args) = ArgParser::with_range_instance("range.with_step", ctx)?;
+        let step = args.number("step")?;
+        args.finish()?;
+
+        if step <= 0.0 {
+            return runtime_error!("range.with_step: the step size must be greater than zero");
+        }
+        match (r.start(), r.end()) {
+            (Some(start), Some((end, inclusive))) => {
+                let result = SteppedRange::new(start, end, inclusive, step);
+                Ok(KIterator::new(result).into())
+            }
+            _ => runtime_error!("range.with_step can't be used with '{r}'"),
+        }
+    });
+
     result
 }
 
 fn is_range(value: &KValue) -> bool {
     matches!(value, KValue::Range(_))
 }
+
+// Iterates over a range with a step size
+//
+// Each value is calculated by multiplying the step by the value's position in the range, which
+// avoids the accumulation of errors that would be caused by repeatedly adding a float step.
+#[derive(Clone)]
+struct SteppedRange {
+    start: i64,
+    // The step to apply for each value, negative for descending ranges
+    step: KNumber,
+    // The position of the next value to be returned from the front of the range
+    index: u64,
+    // The position following the last value to be returned from the back of the range
+    end_index: u64,
+}
+
+impl SteppedRange {
+    // The fraction of a float step that values are allowed to overshoot the range's end by,
+    // e.g. so that `(0..=3).with_step 0.1` includes a final value that's very close to 3.
+    const FLOAT_TOLERANCE: f64 = 1.0e-9;
+
+    // The step must be greater than zero
+    fn new(start: i64, end: i64, inclusive: bool, step: KNumber) -> Self {
+        let distance = (end as i128 - start as i128).unsigned_abs();
+        let ascending = start <= end;
+
+        let (step, count) = match step {
+            KNumber::I64(step) => {
+                let step_size = step as u128;
+                let count = if inclusive {
+                    distance / step_size + 1
+                } else {
+                    distance.div_ceil(step_size)
+                };
+                let step = if ascending { step } else { -step };
+                (KNumber::I64(step), u64::try_from(count).unwrap_or(u64::MAX))
+            }
+            KNumber::F64(step) => {
+                let distance = distance as f64;
+                let tolerance = step * Self::FLOAT_TOLERANCE;
+                let in_range = |index: u64| {
+                    let offset = index as f64 * step;
+                    if inclusive {
+                        offset <= distance + tolerance
+                    } else {
+                        offset < distance - tolerance
+                    }
+                };
+
+                let mut count = if inclusive {
+                    ((distance + tolerance) / step).floor() + 1.0
+                } else {
+                    ((distance - tolerance) / step).ceil()
+                } as u64;
+                // Correct any rounding errors in the estimated count
+                if count > 0 && !in_range(count - 1) {
+                    count -= 1;
+                } else if in_range(count) {
+                    count += 1;
+                }
+
+                let step = if ascending { step } else { -step };
+                (KNumber::F64(step), count)
+            }
+        };
+
+        Self {
+            start,
+            step,
+            index: 0,
+            end_index: count,
+        }
+    }
+
+    fn value(&self, index: u64) -> KValue {
+        match self.step {
+            KNumber::I64(step) => {
+                let result = self.start as i128 + index as i128 * step as i128;
+                // The result is within the range's bounds, so it fits in an i64
+                KValue::Number((result as i64).into())
+            }
+            KNumber::F64(step) => KValue::Number((self.start as f64 + index as f64 * step).into()),
+        }
+    }
+}
+
+impl KotoIterator for SteppedRange {
+    fn make_copy(&self) -> Result<KIterator> {
+        Ok(KIterator::new(self.clone()))
+    }
+
+    fn is_bidirectional(&self) -> bool {
+        true
+    }
+
+    fn next_back(&mut self) -> Option<KIteratorOutput> {
+        if self.end_index > self.index {
+            self.end_index -= 1;
+            Some(self.value(self.end_index).into())
+        } else {
+            None
+        }
+    }
+}
+
+impl Iterator for SteppedRange {
+    type Item = KIteratorOutput;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index < self.end_index {
+            let result = self.value(self.index);
+            self.index += 1;
+            Some(result.into())
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = usize::try_from(self.end_index - self.index).unwrap_or(usize::MAX);
+        (remaining, Some(remaining))
+    }
+}
@@ -2,7 +2,7 @@
 
 pub mod iterators;
 
-use super::iterator::collect_pair;
+use super::{io::Bytes, iterator::collect_pair};
 use crate::prelude::*;
 
 /// Initializes the `string` core library module
@@ -25,7 +25,10 @@ pub fn make_module() -> KMap {
         let expected_error = "a String";
 
         match ctx.instance_and_args(is_string, expected_error)? {
-            (KValue::Str(s), []) => Ok(KValue::Iterator(KIterator::with_string(s.clone()))),
+            (KValue::Str(s), []) => {
+                let result = iterators::Chars::new(s.clone());
+                Ok(KIterator::new(result).into())
+            }
             (_, unexpected) => type_error_with_slice(expected_error, unexpected),
         }
     });
@@ -166,6 +169,15 @@ pub fn make_module() -> KMap {
         }
     });
 
+    result.add_fn("to_bytes", |ctx| {
+        let expected_error = "a String";
+
+        match ctx.instance_and_args(is_string, expected_error)? {
+            (KValue::Str(s), []) => Ok(Bytes::from(s.as_bytes()).into()),
+            (_, unexpected) => type_error_with_slice(expected_error, unexpected),
+        }
+    });
+
     result.add_fn("to_lowercase", |ctx| {
         let expected_error = "a String";
 
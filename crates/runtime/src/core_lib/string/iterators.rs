@@ -42,17 +42,67 @@ impl Iterator for Bytes {
     }
 }
 
-/// An iterator that outputs the individual bytes contained in a string
+/// An iterator that outputs the unicode scalar values (i.e. code points) contained in a string
+///
+/// Each scalar value is output as a string.
+#[derive(Clone)]
+pub struct Chars {
+    input: KString,
+    index: usize,
+}
+
+impl Chars {
+    /// Creates a new [Chars] iterator
+    pub fn new(input: KString) -> Self {
+        Self { input, index: 0 }
+    }
+}
+
+impl KotoIterator for Chars {
+    fn make_copy(&self) -> Result<KIterator> {
+        Ok(KIterator::new(self.clone()))
+    }
+}
+
+impl Iterator for Chars {
+    type Item = Output;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let c = self.input[self.index..].chars().next()?;
+        let start = self.index;
+        self.index += c.len_utf8();
+        self.input
+            .with_bounds(start..self.index)
+            .map(|result| Output::Value(result.into()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.input.len() - self.index;
+        (remaining.div_ceil(4), Some(remaining))
+    }
+}
+
+/// An iterator that outputs the index ranges of the grapheme clusters contained in a string
+///
+/// The ranges match the string's indexing behaviour, so they can be used to extract the
+/// corresponding clusters from the string.
 #[derive(Clone)]
 pub struct CharIndices {
     input: KString,
+    // The byte position of the next grapheme cluster
+    byte_index: usize,
+    // The index of the next grapheme cluster
     index: usize,
 }
 
 impl CharIndices {
     /// Creates a new [CharIndices] iterator
     pub fn new(input: KString) -> Self {
-        Self { input, index: 0 }
+        Self {
+            input,
+            byte_index: 0,
+            index: 0,
+        }
     }
 }
 
@@ -66,21 +116,17 @@ impl Iterator for CharIndices {
     type Item = Output;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.input[self.index..]
-            .grapheme_indices(true)
-            .next()
-            .map(|(start, grapheme)| {
-                let start = self.index + start;
-                let end = start + grapheme.len();
-                self.index += grapheme.len();
-                let result = KRange::from(start as i64..end as i64);
-                Output::Value(result.into())
-            })
+        let grapheme = self.input[self.byte_index..].graphemes(true).next()?;
+        self.byte_index += grapheme.len();
+        let start = self.index as i64;
+        self.index += 1;
+        Some(Output::Value(KRange::from(start..start + 1).into()))
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let remaining = self.input.len() - self.index;
-        (remaining, Some(remaining))
+        let remaining = self.input.len() - self.byte_index;
+        let lower_bound = (remaining != 0) as usize;
+        (lower_bound, Some(remaining))
     }
 }
 
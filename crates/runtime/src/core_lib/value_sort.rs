@@ -74,6 +74,73 @@ pub fn sort_by_key(
     }
 }
 
+/// Sorts values in a slice using a comparison function
+///
+/// The comparison function is called with two values, and should return `true` if the first value
+/// should be placed before the second. The sort is stable, i.e. values that compare as equal keep
+/// their original order.
+///
+/// If an error occurs during sorting then it's returned, and the slice is left in an unspecified
+/// (but valid) order.
+///
+/// Used by list.sort_by
+pub fn sort_by_comparison(
+    vm: &mut KotoVm,
+    values: &mut [KValue],
+    compare_fn: KValue,
+) -> Result<(), Error> {
+    let mut is_less = |a: &KValue, b: &KValue| match vm
+        .call_function(compare_fn.clone(), &[a.clone(), b.clone()])?
+    {
+        KValue::Bool(result) => Ok(result),
+        unexpected => runtime_error!(
+            "Expected Bool from the comparison function, found '{}'",
+            unexpected.type_as_string()
+        ),
+    };
+
+    merge_sort(values, &mut is_less)
+}
+
+// A stable merge sort that allows the comparison function to fail
+//
+// The sorting functions in std don't support fallible comparisons, and may panic if the comparison
+// function doesn't implement a total order, which can't be guaranteed for functions defined in
+// scripts.
+fn merge_sort(
+    values: &mut [KValue],
+    is_less: &mut impl FnMut(&KValue, &KValue) -> Result<bool, Error>,
+) -> Result<(), Error> {
+    if values.len() < 2 {
+        return Ok(());
+    }
+
+    let mid = values.len() / 2;
+    merge_sort(&mut values[..mid], is_less)?;
+    merge_sort(&mut values[mid..], is_less)?;
+
+    // Merge the sorted halves, taking from the right only when it's strictly less than the left,
+    // which keeps the sort stable.
+    let left = values[..mid].to_vec();
+    let (mut i, mut j) = (0, mid);
+    for k in 0..values.len() {
+        // Once the left half has been merged, the remaining values on the right are in place
+        if i == left.len() {
+            break;
+        }
+
+        if j < values.len() && is_less(&values[j], &left[i])? {
+            values[k] = values[j].clone();
+            j += 1;
+        } else {
+            values[k] = left[i].clone();
+            i += 1;
+        }
+    }
+
+    Ok(())
+}
+
 /// Compares values using Koto operators.
 pub fn compare_values(vm: &mut KotoVm, a: &KValue, b: &KValue) -> Result<Ordering, Error> {
     use KValue::Bool;
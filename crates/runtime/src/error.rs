@@ -1,7 +1,12 @@
 use crate::{prelude::*, Ptr};
 use koto_bytecode::{Chunk, LoaderError};
-use koto_parser::format_source_excerpt;
-use std::{error, fmt, time::Duration};
+use koto_parser::{format_source_excerpt, Span};
+use std::{
+    error, fmt,
+    ops::{Deref, DerefMut},
+    path::Path,
+    time::Duration,
+};
 use thiserror::Error;
 
 /// The different error types that can be thrown by the Koto runtime
@@ -23,11 +28,21 @@ pub enum ErrorKind {
     },
     #[error("Execution timed out (the limit of {} seconds was reached)", .0.as_secs_f64())]
     Timeout(Duration),
+    #[error("Memory limit exceeded (the limit of {0} bytes was reached)")]
+    MemoryLimit(usize),
+    #[error("Execution was interrupted")]
+    Interrupted,
     #[error("Expected '{expected}', but found '{}'", get_value_types(unexpected))]
     UnexpectedType {
         expected: String,
         unexpected: Vec<KValue>,
     },
+    /// A [KotoObject] function that the object doesn't implement was called
+    #[error("{method} is unimplemented for {object_type}")]
+    Unimplemented {
+        method: String,
+        object_type: KString,
+    },
     #[error("Unable to perform operation '{op}' with '{}' and '{}'", lhs.type_as_string(), rhs.type_as_string())]
     InvalidBinaryOp {
         lhs: KValue,
@@ -61,8 +76,18 @@ impl fmt::Debug for ErrorKind {
 }
 
 /// An error thrown by the Koto runtime
+///
+/// The error's contents are boxed so that the error is only the size of a pointer, keeping the
+/// size of [Result] small. This reduces the runtime's stack usage, which is important when
+/// external functions make nested calls into the runtime.
+///
+/// The error's contents can be accessed via [ErrorInner], e.g. `error.error`.
+#[derive(Clone)]
+pub struct Error(Box<ErrorInner>);
+
+/// The contents of an [Error]
 #[derive(Clone, Debug)]
-pub struct Error {
+pub struct ErrorInner {
     /// The error that was thrown
     pub error: ErrorKind,
     /// The stack trace at the point when the error was thrown
@@ -72,10 +97,10 @@ pub struct Error {
 impl Error {
     /// Initializes an error with the given internal error type
     pub(crate) fn new(error: ErrorKind) -> Self {
-        Self {
+        Self(Box::new(ErrorInner {
             error,
             trace: Vec::new(),
-        }
+        }))
     }
 
     /// Initializes an error from a thrown Koto value
@@ -93,10 +118,9 @@ impl Error {
     pub fn with_prefix(mut self, prefix: &str) -> Self {
         use ErrorKind::StringError;
 
-        self.error = match self.error {
-            StringError(message) => StringError(format!("{prefix}: {message}")),
-            other => other,
-        };
+        if let StringError(message) = &mut self.error {
+            *message = format!("{prefix}: {message}");
+        }
 
         self
     }
@@ -108,6 +132,43 @@ impl Error {
             _ => false,
         }
     }
+
+    /// Returns true if the error was caused by the script ending before parsing was complete
+    pub fn is_unexpected_end_of_input(&self) -> bool {
+        match &self.error {
+            ErrorKind::CompileError(error) => error.is_unexpected_end_of_input(),
+            _ => false,
+        }
+    }
+
+    /// Returns true if the error was thrown while compiling a script
+    pub fn is_compile_error(&self) -> bool {
+        matches!(self.error, ErrorKind::CompileError(_))
+    }
+
+    /// Returns the location in the script where the error occurred, if available
+    ///
+    /// For compilation errors this is the location reported by the compiler, and for runtime
+    /// errors it's the location of the instruction that was executing when the error was thrown.
+    pub fn source_location(&self) -> Option<ErrorLocation<'_>> {
+        if let ErrorKind::CompileError(error) = &self.error {
+            return error.source.as_ref().map(|source| ErrorLocation {
+                span: source.span,
+                source: &source.contents,
+                path: source.path.as_deref(),
+            });
+        }
+
+        let ErrorFrame { chunk, instruction } = self.trace.first()?;
+        chunk
+            .debug_info
+            .get_source_span(*instruction)
+            .map(|span| ErrorLocation {
+                span,
+                source: &chunk.debug_info.source,
+                path: chunk.source_path.as_deref(),
+            })
+    }
 }
 
 impl fmt::Display for Error {
@@ -131,6 +192,26 @@ impl fmt::Display for Error {
     }
 }
 
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl Deref for Error {
+    type Target = ErrorInner;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Error {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
 impl error::Error for Error {}
 
 impl From<String> for Error {
@@ -162,6 +243,22 @@ pub struct ErrorFrame {
     pub instruction: u32,
 }
 
+/// The location in a script where an error occurred, see [Error::source_location]
+///
+/// The location can be rendered as an annotated excerpt of the script with
+/// [koto_parser::format_source_excerpt].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorLocation<'a> {
+    /// The span in the script where the error occurred
+    ///
+    /// Note that the span's line and column numbers are zero-based.
+    pub span: Span,
+    /// The script's source
+    pub source: &'a str,
+    /// The script's path, if it was provided when the script was compiled
+    pub path: Option<&'a Path>,
+}
+
 /// The Result type used by the Koto Runtime
 pub type Result<T> = std::result::Result<T, Error>;
 
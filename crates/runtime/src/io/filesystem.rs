@@ -0,0 +1,65 @@
+use crate::{core_lib::io::BufferedSystemFile, make_ptr, KotoFile, KotoSend, KotoSync, Ptr};
+use std::{fs, io, path::Path};
+
+/// A trait that provides the filesystem operations used by the `io` core library module
+///
+/// All filesystem access made by scripts via the `io` module is routed through the runtime's
+/// filesystem, see [KotoVmSettings::filesystem](crate::KotoVmSettings::filesystem).
+///
+/// Implementations can provide a virtual filesystem, or can restrict access to the system's
+/// filesystem by returning an error (e.g. with [io::ErrorKind::PermissionDenied]) for disallowed
+/// paths, and then forwarding allowed operations to [DefaultFilesystem].
+///
+/// Errors are reported to scripts as runtime errors that include the operation's path.
+pub trait KotoFilesystem: KotoSend + KotoSync {
+    /// Opens an existing file at the given path
+    fn open(&self, path: &Path) -> io::Result<Ptr<dyn KotoFile>>;
+
+    /// Creates a file at the given path, truncating the file if it already exists
+    fn create(&self, path: &Path) -> io::Result<Ptr<dyn KotoFile>>;
+
+    /// Reads the contents of the file at the given path
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+
+    /// Reads the contents of the file at the given path as binary data
+    fn read_bytes(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Returns true if a file or directory exists at the given path
+    fn exists(&self, path: &Path) -> bool;
+
+    /// Removes the file at the given path
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+}
+
+/// The default filesystem used in Koto, providing access to the system's filesystem
+#[derive(Default)]
+pub struct DefaultFilesystem {}
+
+impl KotoFilesystem for DefaultFilesystem {
+    fn open(&self, path: &Path) -> io::Result<Ptr<dyn KotoFile>> {
+        let path = fs::canonicalize(path)?;
+        let file = fs::File::open(&path)?;
+        Ok(make_ptr!(BufferedSystemFile::new(file, path)))
+    }
+
+    fn create(&self, path: &Path) -> io::Result<Ptr<dyn KotoFile>> {
+        let file = fs::File::create(path)?;
+        Ok(make_ptr!(BufferedSystemFile::new(file, path.to_path_buf())))
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn read_bytes(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        fs::canonicalize(path).is_ok()
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+}
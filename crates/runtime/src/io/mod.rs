@@ -1,9 +1,11 @@
 mod buffered_file;
 mod file;
+mod filesystem;
 mod stdio;
 
 pub use self::{
     buffered_file::BufferedFile,
     file::{KotoFile, KotoRead, KotoWrite},
+    filesystem::{DefaultFilesystem, KotoFilesystem},
     stdio::{DefaultStderr, DefaultStdin, DefaultStdout},
 };
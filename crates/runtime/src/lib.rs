@@ -2,9 +2,12 @@
 
 #![warn(missing_docs)]
 
+mod arg_parser;
 mod display_context;
 mod error;
 mod io;
+mod memory_usage;
+mod profile;
 mod types;
 mod vm;
 
@@ -13,17 +16,28 @@ pub mod prelude;
 mod send_sync;
 
 pub use crate::{
+    arg_parser::ArgParser,
     display_context::DisplayContext,
-    error::{type_error, type_error_with_slice, Error, ErrorFrame, ErrorKind, Result},
-    io::{BufferedFile, DefaultStderr, DefaultStdin, DefaultStdout, KotoFile, KotoRead, KotoWrite},
+    error::{
+        type_error, type_error_with_slice, Error, ErrorFrame, ErrorInner, ErrorKind, ErrorLocation,
+        Result,
+    },
+    io::{
+        BufferedFile, DefaultFilesystem, DefaultStderr, DefaultStdin, DefaultStdout, KotoFile,
+        KotoFilesystem, KotoRead, KotoWrite,
+    },
+    profile::{ProfileEntry, ProfileEntryKind, ProfileReport},
     send_sync::{KotoSend, KotoSync},
     types::{
-        BinaryOp, CallContext, IsIterable, KCaptureFunction, KFunction, KIterator, KIteratorOutput,
-        KList, KMap, KNativeFunction, KNumber, KObject, KRange, KString, KTuple, KValue, KotoCopy,
-        KotoEntries, KotoFunction, KotoHasher, KotoIterator, KotoObject, KotoType, MetaKey,
-        MetaMap, MethodContext, UnaryOp, ValueKey, ValueMap, ValueVec,
+        BinaryOp, CallContext, FunctionInfo, IsIterable, KCaptureFunction, KFunction, KIterator,
+        KIteratorOutput, KList, KMap, KNativeFunction, KNumber, KObject, KRange, KString, KTuple,
+        KValue, KotoCopy, KotoEntries, KotoFunction, KotoHasher, KotoIterator, KotoObject,
+        KotoType, MetaKey, MetaMap, MethodContext, UnaryOp, ValueKey, ValueMap, ValueVec,
+    },
+    vm::{
+        CallArgs, KotoInterrupt, KotoVm, KotoVmSettings, LoadedModule, ModuleImportedCallback,
+        ModuleLoader, ReturnOrYield, TraceContext, TraceControl, TraceHook,
     },
-    vm::{CallArgs, KotoVm, KotoVmSettings, ModuleImportedCallback, ReturnOrYield},
 };
 pub use koto_derive as derive;
-pub use koto_memory::{make_ptr, make_ptr_mut, Borrow, BorrowMut, KCell, Ptr, PtrMut};
+pub use koto_memory::{make_ptr, make_ptr_mut, Borrow, BorrowMut, KCell, Ptr, PtrMut, PtrWeak};
@@ -0,0 +1,232 @@
+use crate::{
+    types::{KListWeak, KMapWeak, KStringWeak, KTupleWeak},
+    KList, KMap, KString, KTuple, KValue, ValueKey,
+};
+use koto_memory::Address;
+use rustc_hash::FxHashMap;
+use std::mem;
+
+/// Returns the approximate number of bytes used by the given number of values
+pub(crate) fn values_size(count: usize) -> usize {
+    count.saturating_mul(mem::size_of::<KValue>())
+}
+
+/// Returns the approximate number of bytes used by the given number of map entries
+pub(crate) fn map_entries_size(count: usize) -> usize {
+    count.saturating_mul(mem::size_of::<ValueKey>() + mem::size_of::<KValue>())
+}
+
+// The minimum number of owned allocations that need to be recorded before they're checked for
+// values that have been dropped
+const MIN_SWEEP_THRESHOLD: usize = 1024;
+
+/// The runtime's tracked memory usage, see [KotoVmSettings::memory_limit]
+///
+/// Allocations are either attributed to the container that owns them, or are recorded as
+/// 'pending' when the container doesn't exist yet (or for allocations made by external code).
+///
+/// Owned allocations are released once their container has been dropped, and the amount charged
+/// to a container is capped to the container's current size so that shrinking a container also
+/// releases memory. These checks are made lazily when the limit would otherwise be exceeded.
+///
+/// [KotoVmSettings::memory_limit]: crate::KotoVmSettings::memory_limit
+pub(crate) struct MemoryUsage {
+    // The total number of bytes that are currently being tracked
+    total: usize,
+    // The bytes that have been charged to each container, keyed by the container's address
+    owned: FxHashMap<Address, OwnedAllocation>,
+    // The number of owned allocations that will trigger a check for dropped containers
+    sweep_threshold: usize,
+}
+
+struct OwnedAllocation {
+    container: WeakContainer,
+    bytes: usize,
+}
+
+impl Default for MemoryUsage {
+    fn default() -> Self {
+        Self {
+            total: 0,
+            owned: FxHashMap::default(),
+            sweep_threshold: MIN_SWEEP_THRESHOLD,
+        }
+    }
+}
+
+impl MemoryUsage {
+    /// The total number of bytes that are currently being tracked
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Records an allocation, and then checks the total against the limit
+    ///
+    /// If an owner is provided then the allocation is attributed to it,
+    /// otherwise the allocation is pending until it's assigned to an owner.
+    ///
+    /// Returns false if the allocation causes the limit to be exceeded.
+    pub fn allocate(&mut self, owner: Option<Owner>, bytes: usize, limit: usize) -> bool {
+        self.total = self.total.saturating_add(bytes);
+
+        // The sweep is performed before the allocation is attributed to its owner, so that the
+        // allocation isn't released before the owner has grown to make use of it.
+        if self.total > limit || self.owned.len() >= self.sweep_threshold {
+            self.sweep();
+        }
+
+        if let Some(owner) = owner {
+            self.add_owned(owner, bytes);
+        }
+
+        self.total <= limit
+    }
+
+    /// Attributes previously allocated pending bytes to the given owner
+    pub fn assign(&mut self, owner: Owner, bytes: usize) {
+        self.add_owned(owner, bytes);
+
+        if self.owned.len() >= self.sweep_threshold {
+            self.sweep();
+        }
+    }
+
+    /// Clears all tracked allocations
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn add_owned(&mut self, owner: Owner, bytes: usize) {
+        if bytes == 0 {
+            return;
+        }
+
+        let address = owner.address;
+        match self.owned.get_mut(&address) {
+            Some(allocation) if allocation.container.is_alive() => {
+                allocation.bytes = allocation.bytes.saturating_add(bytes);
+            }
+            _ => {
+                // The address may have been used by a container that has since been dropped,
+                // in which case its allocation is replaced by the new owner's allocation.
+                let allocation = OwnedAllocation {
+                    container: owner.container,
+                    bytes,
+                };
+                if let Some(dropped) = self.owned.insert(address, allocation) {
+                    self.total = self.total.saturating_sub(dropped.bytes);
+                }
+            }
+        }
+    }
+
+    // Releases the allocations of dropped containers,
+    // and caps the allocations of live containers to their current size
+    fn sweep(&mut self) {
+        let mut released = 0;
+
+        self.owned
+            .retain(|_, allocation| match allocation.container.current_size() {
+                Size::Known(size) => {
+                    if size < allocation.bytes {
+                        released += allocation.bytes - size;
+                        allocation.bytes = size;
+                    }
+                    allocation.bytes > 0
+                }
+                // The container is currently borrowed, so its allocation is left unchanged
+                Size::Unknown => true,
+                Size::Dropped => {
+                    released += allocation.bytes;
+                    false
+                }
+            });
+
+        self.total = self.total.saturating_sub(released);
+        self.sweep_threshold = (self.owned.len() * 2).max(MIN_SWEEP_THRESHOLD);
+    }
+}
+
+/// A container that an allocation can be attributed to, see [MemoryUsage]
+pub(crate) struct Owner {
+    address: Address,
+    container: WeakContainer,
+}
+
+impl From<&KList> for Owner {
+    fn from(list: &KList) -> Self {
+        Self {
+            address: list.address(),
+            container: WeakContainer::List(list.downgrade()),
+        }
+    }
+}
+
+impl From<&KMap> for Owner {
+    fn from(map: &KMap) -> Self {
+        Self {
+            address: map.address(),
+            container: WeakContainer::Map(map.downgrade()),
+        }
+    }
+}
+
+impl From<&KString> for Owner {
+    fn from(string: &KString) -> Self {
+        Self {
+            address: string.address(),
+            container: WeakContainer::Str(string.downgrade()),
+        }
+    }
+}
+
+impl From<&KTuple> for Owner {
+    fn from(tuple: &KTuple) -> Self {
+        Self {
+            address: tuple.address(),
+            container: WeakContainer::Tuple(tuple.downgrade()),
+        }
+    }
+}
+
+enum WeakContainer {
+    List(KListWeak),
+    Map(KMapWeak),
+    Str(KStringWeak),
+    Tuple(KTupleWeak),
+}
+
+// The result of measuring a container's size, see [WeakContainer::current_size]
+enum Size {
+    Known(usize),
+    // The container is alive, but its size couldn't be measured because it's currently borrowed
+    Unknown,
+    Dropped,
+}
+
+impl WeakContainer {
+    fn is_alive(&self) -> bool {
+        match self {
+            Self::List(l) => l.upgrade().is_some(),
+            Self::Map(m) => m.upgrade().is_some(),
+            Self::Str(s) => s.upgrade().is_some(),
+            Self::Tuple(t) => t.upgrade().is_some(),
+        }
+    }
+
+    // Returns the approximate size in bytes of the container's contents
+    fn current_size(&self) -> Size {
+        let size = match self {
+            Self::List(l) => l.upgrade().map(|l| l.try_len().map(values_size)),
+            Self::Map(m) => m.upgrade().map(|m| m.try_len().map(map_entries_size)),
+            Self::Str(s) => s.upgrade().map(|s| Some(s.len())),
+            Self::Tuple(t) => t.upgrade().map(|t| Some(values_size(t.len()))),
+        };
+
+        match size {
+            Some(Some(size)) => Size::Known(size),
+            Some(None) => Size::Unknown,
+            None => Size::Dropped,
+        }
+    }
+}
@@ -2,10 +2,11 @@
 
 #[doc(inline)]
 pub use crate::{
-    make_ptr, make_ptr_mut, runtime_error, type_error, type_error_with_slice, BinaryOp, CallArgs,
-    CallContext, DisplayContext, IsIterable, KCell, KIterator, KIteratorOutput, KList, KMap,
-    KNativeFunction, KNumber, KObject, KRange, KString, KTuple, KValue, KotoCopy, KotoEntries,
-    KotoFile, KotoFunction, KotoHasher, KotoIterator, KotoObject, KotoRead, KotoSend, KotoSync,
-    KotoType, KotoVm, KotoVmSettings, KotoWrite, MetaKey, MetaMap, MethodContext, UnaryOp,
-    ValueKey, ValueMap, ValueVec,
+    make_ptr, make_ptr_mut, runtime_error, type_error, type_error_with_slice, ArgParser, BinaryOp,
+    CallArgs, CallContext, DisplayContext, FunctionInfo, IsIterable, KCell, KIterator,
+    KIteratorOutput, KList, KMap, KNativeFunction, KNumber, KObject, KRange, KString, KTuple,
+    KValue, KotoCopy, KotoEntries, KotoFile, KotoFilesystem, KotoFunction, KotoHasher,
+    KotoInterrupt, KotoIterator, KotoObject, KotoRead, KotoSend, KotoSync, KotoType, KotoVm,
+    KotoVmSettings, KotoWrite, LoadedModule, MetaKey, MetaMap, MethodContext, ProfileReport,
+    TraceContext, TraceControl, UnaryOp, ValueKey, ValueMap, ValueVec,
 };
@@ -0,0 +1,214 @@
+use crate::Ptr;
+use instant::Instant;
+use koto_bytecode::Chunk;
+use rustc_hash::FxHashMap;
+use std::{cmp::Reverse, fmt, path::PathBuf, time::Duration};
+
+/// A report produced by the runtime's profiler
+///
+/// See [KotoVmSettings::profiling](crate::KotoVmSettings::profiling).
+#[derive(Clone, Debug, Default)]
+pub struct ProfileReport {
+    /// The total time spent executing Koto functions and scripts
+    pub total_time: Duration,
+    /// The report's entries, sorted by the time spent in each entry, from highest to lowest
+    pub entries: Vec<ProfileEntry>,
+}
+
+/// An entry in a [ProfileReport]
+#[derive(Clone, Debug)]
+pub struct ProfileEntry {
+    /// The kind of code that the entry refers to
+    pub kind: ProfileEntryKind,
+    /// The path of the source file that contains the entry's code, if available
+    pub source_path: Option<PathBuf>,
+    /// The one-based line number of the entry's code, if available
+    ///
+    /// For functions this is the line where the function's body starts, and for external
+    /// functions this is the line where the function was called.
+    pub line: Option<u32>,
+    /// The number of times that the entry's code was executed
+    pub calls: u64,
+    /// The time spent executing the entry's code
+    ///
+    /// The time spent in calls to other Koto functions is excluded, while the time spent in
+    /// external functions includes any time spent in Koto functions that they call.
+    pub time: Duration,
+}
+
+/// The kinds of code that are included in a [ProfileReport]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProfileEntryKind {
+    /// The top level of a script or imported module
+    Script,
+    /// A Koto function
+    Function,
+    /// An external function, e.g. a function from the core library
+    External,
+}
+
+impl fmt::Display for ProfileReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Total time: {:.3?}", self.total_time)?;
+
+        if !self.entries.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "{:>12} {:>8} {:>10}  Location", "Time", "%", "Calls")?;
+            for entry in self.entries.iter() {
+                let percentage = if self.total_time.is_zero() {
+                    0.0
+                } else {
+                    entry.time.as_secs_f64() / self.total_time.as_secs_f64() * 100.0
+                };
+                writeln!(
+                    f,
+                    "{:>12} {:>7.1}% {:>10}  {entry}",
+                    format!("{:.3?}", entry.time),
+                    percentage,
+                    entry.calls
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for ProfileEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self
+            .source_path
+            .as_ref()
+            .map_or_else(|| "<script>".into(), |path| path.to_string_lossy());
+        let location = match self.line {
+            Some(line) => format!("{path}:{line}"),
+            None => path.to_string(),
+        };
+
+        match self.kind {
+            ProfileEntryKind::Script => write!(f, "{location}"),
+            ProfileEntryKind::Function => write!(f, "function at {location}"),
+            ProfileEntryKind::External => write!(f, "external function called at {location}"),
+        }
+    }
+}
+
+// Timing information for a call frame, used when profiling is enabled
+#[derive(Clone, Debug)]
+pub(crate) struct FrameProfile {
+    // The ip of the start of the frame's function
+    pub ip: u32,
+    // The time when the frame was entered
+    pub start: Instant,
+    // The time spent in calls made from the frame
+    pub child_time: Duration,
+}
+
+// The profiling data that's collected by the runtime
+#[derive(Default)]
+pub(crate) struct Profiler {
+    entries: FxHashMap<(usize, u32, ProfileEntryKind), ProfileData>,
+    // The number of profiled frames that are currently active
+    active_frames: usize,
+    // The time when the outermost active frame was entered
+    run_start: Option<Instant>,
+    total_time: Duration,
+}
+
+struct ProfileData {
+    chunk: Ptr<Chunk>,
+    calls: u64,
+    time: Duration,
+}
+
+impl Profiler {
+    pub fn start_frame(&mut self, ip: u32) -> FrameProfile {
+        let start = Instant::now();
+
+        if self.active_frames == 0 {
+            self.run_start = Some(start);
+        }
+        self.active_frames += 1;
+
+        FrameProfile {
+            ip,
+            start,
+            child_time: Duration::ZERO,
+        }
+    }
+
+    // Records the time spent in an exited frame, and returns the frame's total elapsed time
+    pub fn end_frame(&mut self, chunk: &Ptr<Chunk>, profile: &FrameProfile) -> Duration {
+        let elapsed = profile.start.elapsed();
+        let kind = if profile.ip == 0 {
+            ProfileEntryKind::Script
+        } else {
+            ProfileEntryKind::Function
+        };
+        self.record(
+            chunk,
+            profile.ip,
+            kind,
+            elapsed.saturating_sub(profile.child_time),
+        );
+
+        self.active_frames = self.active_frames.saturating_sub(1);
+        if self.active_frames == 0 {
+            if let Some(run_start) = self.run_start.take() {
+                self.total_time += run_start.elapsed();
+            }
+        }
+
+        elapsed
+    }
+
+    pub fn record(&mut self, chunk: &Ptr<Chunk>, ip: u32, kind: ProfileEntryKind, time: Duration) {
+        let key = (chunk_address(chunk), ip, kind);
+        let data = self.entries.entry(key).or_insert_with(|| ProfileData {
+            chunk: chunk.clone(),
+            calls: 0,
+            time: Duration::ZERO,
+        });
+        data.calls += 1;
+        data.time += time;
+    }
+
+    pub fn report(&self) -> ProfileReport {
+        let mut entries = self
+            .entries
+            .iter()
+            .map(|((_, ip, kind), data)| ProfileEntry {
+                kind: *kind,
+                source_path: data.chunk.source_path.clone(),
+                line: match kind {
+                    ProfileEntryKind::Script => None,
+                    _ => data
+                        .chunk
+                        .debug_info
+                        .get_source_span(*ip)
+                        .map(|span| span.start.line + 1),
+                },
+                calls: data.calls,
+                time: data.time,
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|entry| Reverse(entry.time));
+
+        ProfileReport {
+            total_time: self.total_time,
+            entries,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.entries.clear();
+        self.total_time = Duration::ZERO;
+        if self.run_start.is_some() {
+            self.run_start = Some(Instant::now());
+        }
+    }
+}
+
+fn chunk_address(chunk: &Ptr<Chunk>) -> usize {
+    &**chunk as *const Chunk as usize
+}
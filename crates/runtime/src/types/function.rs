@@ -1,4 +1,4 @@
-use crate::KList;
+use crate::{runtime_error, KList, KValue, Result};
 use koto_bytecode::Chunk;
 use koto_memory::Ptr;
 
@@ -28,6 +28,28 @@ pub struct KFunction {
     /// executes the function's body for each iteration step, pausing when a yield instruction is
     /// encountered. See Vm::call_generator and Iterable::Generator.
     pub generator: bool,
+    /// If the function accesses `self`, then it's expected to be called as an instance function.
+    pub accesses_self: bool,
+}
+
+impl KFunction {
+    // Describes the function's arguments, used when displaying the function
+    pub(crate) fn args_description(&self) -> String {
+        let plural = if self.arg_count == 1 { "" } else { "s" };
+        let variadic = if self.variadic { ", variadic" } else { "" };
+        format!("{} arg{plural}{variadic}", self.arg_count)
+    }
+
+    /// Returns information about the function's arguments
+    pub fn info(&self) -> FunctionInfo {
+        FunctionInfo {
+            arg_count: self.arg_count,
+            variadic: self.variadic,
+            generator: self.generator,
+            is_instance_function: self.accesses_self,
+            captures: Vec::new(),
+        }
+    }
 }
 
 /// A Koto function with captured values
@@ -52,3 +74,64 @@ pub struct KCaptureFunction {
     // A. These could be worth investigating as optimizations, but a KList will do for now.
     pub captures: KList,
 }
+
+impl KCaptureFunction {
+    /// Returns information about the function's arguments, along with its captured values
+    pub fn info(&self) -> FunctionInfo {
+        FunctionInfo {
+            captures: self.captures.data().to_vec(),
+            ..self.info.info()
+        }
+    }
+}
+
+/// Information about a Koto function, see [KValue::function_info]
+#[derive(Clone)]
+pub struct FunctionInfo {
+    /// The number of arguments declared by the function, including any variadic argument
+    pub arg_count: u8,
+    /// True if the function's last argument is variadic
+    pub variadic: bool,
+    /// True if the function is a generator
+    pub generator: bool,
+    /// True if the function accesses `self`, and so expects to be called with an instance
+    pub is_instance_function: bool,
+    /// The values that were captured by the function when it was created
+    pub captures: Vec<KValue>,
+}
+
+impl FunctionInfo {
+    /// Checks that calling the function with the given number of arguments is valid
+    ///
+    /// Koto functions can be called with any number of arguments, with missing arguments
+    /// being set to null and extra arguments being ignored. This check is stricter, requiring
+    /// that the argument count matches the number of declared arguments, with variadic
+    /// functions requiring at least the number of non-variadic arguments.
+    pub fn check_arg_count(&self, arg_count: usize) -> Result<()> {
+        let required = if self.variadic {
+            self.arg_count.saturating_sub(1)
+        } else {
+            self.arg_count
+        } as usize;
+
+        if arg_count == required || (self.variadic && arg_count > required) {
+            Ok(())
+        } else {
+            let plural = if arg_count == 1 { "" } else { "s" };
+            let expected = if self.variadic {
+                format!("at least {required}")
+            } else {
+                required.to_string()
+            };
+            let expected_plural = if required == 1 && !self.variadic {
+                ""
+            } else {
+                "s"
+            };
+            runtime_error!(
+                "Expected {arg_count} arg{plural}, but the function takes \
+                 {expected} arg{expected_plural}"
+            )
+        }
+    }
+}
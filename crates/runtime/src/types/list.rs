@@ -1,26 +1,100 @@
-use crate::{prelude::*, Borrow, BorrowMut, PtrMut, Result};
+use crate::{prelude::*, Borrow, BorrowMut, KCell, Ptr, PtrMut, PtrWeak, Result};
+use koto_memory::Address;
+use std::ops::Range;
 
 /// The underlying Vec type used by [KList]
 pub type ValueVec = smallvec::SmallVec<[KValue; 4]>;
 
 /// The Koto runtime's List type
+///
+/// Slices of a list (see [KList::slice]) share the list's values until either the slice or the
+/// original list is modified, at which point the modified list makes its own copy of its values.
 #[derive(Clone, Default)]
-pub struct KList(PtrMut<ValueVec>);
+pub struct KList(PtrMut<ListData>);
+
+/// A weak reference to a [KList], see [KList::downgrade]
+#[derive(Clone)]
+pub(crate) struct KListWeak(PtrWeak<KCell<ListData>>);
+
+impl KListWeak {
+    /// Returns the referenced list, or None if the list has been dropped
+    pub fn upgrade(&self) -> Option<KList> {
+        self.0.upgrade().map(KList)
+    }
+}
+
+#[derive(Default)]
+struct ListData {
+    // The list's values, which might be shared with slices of the list
+    values: Ptr<ValueVec>,
+    // The range of `values` that's included in the list, or None if the list contains all values
+    range: Option<Range<usize>>,
+}
+
+impl ListData {
+    fn new(values: ValueVec) -> Self {
+        Self {
+            values: values.into(),
+            range: None,
+        }
+    }
+
+    fn as_slice(&self) -> &[KValue] {
+        match &self.range {
+            Some(range) => &self.values[range.clone()],
+            None => &self.values,
+        }
+    }
+
+    // Ensures that the values aren't shared, and then returns a mutable reference to them
+    fn values_mut(&mut self) -> &mut ValueVec {
+        if let Some(range) = self.range.take() {
+            self.values = self.values[range]
+                .iter()
+                .cloned()
+                .collect::<ValueVec>()
+                .into();
+        }
+        Ptr::make_mut(&mut self.values)
+    }
+}
 
 impl KList {
     /// Creates an empty list with the given capacity
     pub fn with_capacity(capacity: usize) -> Self {
-        Self(ValueVec::with_capacity(capacity).into())
+        Self::with_data(ValueVec::with_capacity(capacity))
     }
 
     /// Creates a list containing the provided data
     pub fn with_data(data: ValueVec) -> Self {
-        Self(data.into())
+        Self(ListData::new(data).into())
     }
 
     /// Creates a list containing the provided slice of [Values](crate::KValue)
     pub fn from_slice(data: &[KValue]) -> Self {
-        Self(data.iter().cloned().collect::<ValueVec>().into())
+        Self::with_data(data.iter().cloned().collect())
+    }
+
+    /// Returns a new list containing a range of the list's values
+    ///
+    /// The new list shares the original list's values without copying them,
+    /// until either list is modified.
+    ///
+    /// The range must be within the bounds of the list, otherwise `None` is returned.
+    pub fn slice(&self, range: Range<usize>) -> Option<Self> {
+        let data = self.0.borrow();
+        let offset = data.range.as_ref().map_or(0, |existing| existing.start);
+        if range.start > range.end || range.end > data.as_slice().len() {
+            return None;
+        }
+
+        Some(Self(
+            ListData {
+                values: data.values.clone(),
+                range: Some((offset + range.start)..(offset + range.end)),
+            }
+            .into(),
+        ))
     }
 
     /// Returns the number of entries of the list
@@ -28,19 +102,44 @@ impl KList {
         self.data().len()
     }
 
+    /// Returns the number of entries in the list, or None if the list is currently borrowed
+    pub(crate) fn try_len(&self) -> Option<usize> {
+        self.0.try_borrow().map(|data| data.as_slice().len())
+    }
+
     /// Returns true if there are no entries in the list
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
     /// Returns a reference to the list's entries
-    pub fn data(&self) -> Borrow<ValueVec> {
-        self.0.borrow()
+    pub fn data(&self) -> Borrow<[KValue]> {
+        let Ok(result) = Borrow::filter_map(self.0.borrow(), |data| Some(data.as_slice())) else {
+            unreachable!()
+        };
+        result
     }
 
     /// Returns a mutable reference to the list's entries
+    ///
+    /// If the list's entries are shared with slices of the list, then a copy of the entries
+    /// will be made before returning the reference.
     pub fn data_mut(&self) -> BorrowMut<ValueVec> {
-        self.0.borrow_mut()
+        let Ok(result) = BorrowMut::filter_map(self.0.borrow_mut(), |data| Some(data.values_mut()))
+        else {
+            unreachable!()
+        };
+        result
+    }
+
+    /// Returns the address of the list's data, used to identify the list
+    pub(crate) fn address(&self) -> Address {
+        PtrMut::address(&self.0)
+    }
+
+    /// Returns a weak reference to the list's data
+    pub(crate) fn downgrade(&self) -> KListWeak {
+        KListWeak(PtrMut::downgrade(&self.0))
     }
 
     /// Renders the list to the provided display context
@@ -67,3 +166,12 @@ impl KList {
         Ok(())
     }
 }
+
+impl<T> FromIterator<T> for KList
+where
+    T: Into<KValue>,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::with_data(iter.into_iter().map(Into::into).collect())
+    }
+}
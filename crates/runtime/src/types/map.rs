@@ -1,7 +1,11 @@
-use crate::{prelude::*, Borrow, BorrowMut, Error, PtrMut, Result};
+use super::map_path::{self, parse_path};
+use crate::{prelude::*, Borrow, BorrowMut, Error, KCell, PtrMut, PtrWeak, Result};
 use indexmap::{Equivalent, IndexMap};
+use koto_lexer::{is_id_continue, is_id_start};
+use koto_memory::Address;
 use rustc_hash::FxHasher;
 use std::{
+    cmp::Ordering,
     hash::{BuildHasherDefault, Hash},
     ops::{Deref, DerefMut, RangeBounds},
 };
@@ -26,6 +30,42 @@ impl ValueMap {
         ))
     }
 
+    /// Sorts the map's entries by key
+    ///
+    /// Entries are iterated in insertion order by default, this can be used when the entries
+    /// should be in key order, e.g. with lexicographic ordering for string keys.
+    pub fn sort_keys(&mut self) {
+        self.0
+            .sort_by(|key_a, _, key_b, _| key_a.partial_cmp(key_b).unwrap_or(Ordering::Equal));
+    }
+
+    /// Returns a clone of the value found by following a path of nested keys and indices
+    ///
+    /// Keys are separated by `.`, and list or tuple indices are written in square brackets,
+    /// e.g. `a.b[2].c`. Keys containing `.`, `[`, `]`, or `\` can be escaped with `\`,
+    /// e.g. `a\.b` refers to the key `a.b`.
+    ///
+    /// `None` is returned if any part of the path is missing, or if the path is invalid.
+    pub fn get_path(&self, path: &str) -> Option<KValue> {
+        let segments = parse_path(path).ok()?;
+        map_path::get_path(self, &segments)
+    }
+
+    /// Sets the value found by following a path of nested keys and indices
+    ///
+    /// See [ValueMap::get_path] for a description of the path syntax.
+    ///
+    /// Missing (or null) intermediate values are created as maps or lists depending on the
+    /// following segment in the path. Lists are extended with nulls when setting an index
+    /// that's beyond the end of the list.
+    ///
+    /// An error is returned if the path is invalid, or if a value in the path isn't a
+    /// container matching the path's segment.
+    pub fn set_path(&mut self, path: &str, value: impl Into<KValue>) -> Result<()> {
+        let segments = parse_path(path)?;
+        map_path::set_path(self, &segments, value.into(), None)
+    }
+
     /// Makes a new ValueMap containing a slice of the map's elements
     pub fn make_data_slice(&self, range: impl RangeBounds<usize>) -> Option<Self> {
         self.get_range(range).map(|entries| {
@@ -65,6 +105,26 @@ pub struct KMap {
     meta: Option<PtrMut<MetaMap>>,
 }
 
+/// A weak reference to a [KMap], see [KMap::downgrade]
+#[derive(Clone)]
+pub(crate) struct KMapWeak {
+    data: PtrWeak<KCell<ValueMap>>,
+    meta: Option<PtrWeak<KCell<MetaMap>>>,
+}
+
+impl KMapWeak {
+    /// Returns the referenced map, or None if the map's data has been dropped
+    ///
+    /// If the map's meta map has been dropped (which can only happen if it was replaced while the
+    /// map's data was still alive), then the returned map won't have a meta map.
+    pub fn upgrade(&self) -> Option<KMap> {
+        self.data.upgrade().map(|data| KMap {
+            data,
+            meta: self.meta.as_ref().and_then(|meta| meta.upgrade()),
+        })
+    }
+}
+
 impl KMap {
     /// Creates an empty KMap
     pub fn new() -> Self {
@@ -155,6 +215,41 @@ impl KMap {
         self.data_mut().insert(key.into(), value.into());
     }
 
+    /// Returns a clone of the data value found by following a path of nested keys and indices
+    ///
+    /// See [ValueMap::get_path].
+    pub fn get_path(&self, path: &str) -> Option<KValue> {
+        self.data().get_path(path)
+    }
+
+    /// Sets the data value found by following a path of nested keys and indices
+    ///
+    /// See [ValueMap::set_path].
+    pub fn set_path(&self, path: &str, value: impl Into<KValue>) -> Result<()> {
+        self.set_path_with_vm(path, value.into(), None)
+    }
+
+    /// Sets a data value by path, with allocations tracked by the VM if one is provided
+    pub(crate) fn set_path_with_vm(
+        &self,
+        path: &str,
+        value: KValue,
+        vm: Option<&KotoVm>,
+    ) -> Result<()> {
+        let segments = parse_path(path)?;
+        map_path::set_value_path(&self.clone().into(), &segments, value, vm)
+    }
+
+    /// Removes an entry from the KMap's data, returning the removed value if it existed
+    ///
+    /// The order of the remaining entries is preserved.
+    pub fn remove<K>(&self, key: &K) -> Option<KValue>
+    where
+        K: Hash + Equivalent<ValueKey> + ?Sized,
+    {
+        self.data_mut().shift_remove(key)
+    }
+
     /// Inserts a value into the meta map, initializing the meta map if it doesn't yet exist
     pub fn insert_meta(&mut self, key: MetaKey, value: KValue) {
         self.meta
@@ -175,6 +270,11 @@ impl KMap {
         self.data().len()
     }
 
+    /// Returns the number of entries in the map, or None if the map is currently borrowed
+    pub(crate) fn try_len(&self) -> Option<usize> {
+        self.data.try_borrow().map(|data| data.len())
+    }
+
     /// Returns true if the KMap's data map contains no entries
     ///
     /// Note that this doesn't take entries in the meta map into account.
@@ -188,63 +288,121 @@ impl KMap {
         self.meta = None;
     }
 
+    /// Returns the address of the map's data, used to identify the map
+    pub(crate) fn address(&self) -> Address {
+        PtrMut::address(&self.data)
+    }
+
+    /// Returns a weak reference to the map
+    ///
+    /// Neither the map's data nor its meta map are kept alive by the weak reference.
+    pub(crate) fn downgrade(&self) -> KMapWeak {
+        KMapWeak {
+            data: PtrMut::downgrade(&self.data),
+            meta: self.meta.as_ref().map(PtrMut::downgrade),
+        }
+    }
+
     /// Returns true if the provided KMap occupies the same memory address
     pub fn is_same_instance(&self, other: &Self) -> bool {
         PtrMut::ptr_eq(&self.data, &other.data)
     }
 
     /// Renders the map to the provided display context
+    ///
+    /// If the map has a `@display` function then it will be called to produce the result. If the
+    /// map is displayed again while its `@display` function is being evaluated (e.g. with
+    /// `@display: || '{self}'`), then the map's entries will be displayed instead.
     pub fn display(&self, ctx: &mut DisplayContext) -> Result<()> {
-        if self.contains_meta_key(&UnaryOp::Display.into()) {
-            let mut vm = ctx
+        if let Some(display_op) = self.get_meta_value(&UnaryOp::Display.into()) {
+            let vm = ctx
                 .vm()
-                .ok_or_else(|| Error::from("Missing VM in map display op"))?
-                .spawn_shared_vm();
-            match vm.run_unary_op(UnaryOp::Display, self.clone().into())? {
-                KValue::Str(display_result) => {
-                    ctx.append(display_result);
+                .ok_or_else(|| Error::from("Missing VM in map display op"))?;
+            let address = self.address();
+            if vm.start_display_op(address) {
+                let result = vm.spawn_shared_vm().call_instance_function(
+                    self.clone().into(),
+                    display_op,
+                    &[],
+                );
+                vm.end_display_op(address);
+                match result? {
+                    KValue::Str(display_result) => {
+                        ctx.append(display_result);
+                        return Ok(());
+                    }
+                    unexpected => return type_error("String as @display result", &unexpected),
                 }
-                unexpected => return type_error("String as @display result", &unexpected),
             }
-        } else {
-            ctx.append('{');
+        }
 
-            let id = PtrMut::address(&self.data);
+        ctx.append('{');
 
-            if ctx.is_in_parents(id) {
-                ctx.append("...");
-            } else {
-                ctx.push_container(id);
+        let id = PtrMut::address(&self.data);
 
-                for (i, (key, value)) in self.data().iter().enumerate() {
-                    if i > 0 {
-                        ctx.append(", ");
-                    }
+        if ctx.is_in_parents(id) {
+            ctx.append("...");
+        } else {
+            ctx.push_container(id);
 
-                    let mut key_ctx = DisplayContext::default();
-                    key.value().display(&mut key_ctx)?;
-                    ctx.append(key_ctx.result());
-                    ctx.append(": ");
+            for (i, (key, value)) in self.data().iter().enumerate() {
+                if i > 0 {
+                    ctx.append(", ");
+                }
 
-                    value.display(ctx)?;
+                match key.value() {
+                    // String keys that aren't valid identifiers are quoted,
+                    // so that they can be distinguished from other key types (e.g. '1' vs 1)
+                    KValue::Str(s) if !is_identifier(s) => {
+                        ctx.append('\'');
+                        ctx.append(s);
+                        ctx.append('\'');
+                    }
+                    key => {
+                        let mut key_ctx = DisplayContext::default();
+                        key.display(&mut key_ctx)?;
+                        ctx.append(key_ctx.result());
+                    }
                 }
+                ctx.append(": ");
 
-                ctx.pop_container();
+                value.display(ctx)?;
             }
 
-            ctx.append('}');
+            ctx.pop_container();
         }
 
+        ctx.append('}');
+
         Ok(())
     }
 }
 
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(is_id_start) && chars.all(is_id_continue)
+}
+
 impl From<ValueMap> for KMap {
     fn from(value: ValueMap) -> Self {
         KMap::with_data(value)
     }
 }
 
+impl<K, V> FromIterator<(K, V)> for KMap
+where
+    K: Into<ValueKey>,
+    V: Into<KValue>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        KMap::with_data(
+            iter.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -262,4 +420,115 @@ mod tests {
         ));
         assert!(m.get("test").is_none());
     }
+
+    #[test]
+    fn remove_preserves_order() {
+        let m = KMap::default();
+        m.insert("a", 1);
+        m.insert("b", 2);
+        m.insert("c", 3);
+
+        assert!(matches!(m.remove("b"), Some(KValue::Number(n)) if n == 2));
+        assert!(m.remove("b").is_none());
+
+        let keys = m
+            .data()
+            .keys()
+            .map(|key| key.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, ["a", "c"]);
+    }
+
+    #[test]
+    fn from_iter() {
+        let m = KMap::from_iter([("a", KValue::from(1)), ("b", "hello".into())]);
+
+        assert_eq!(m.len(), 2);
+        assert!(matches!(m.get("a"), Some(KValue::Number(n)) if n == 1));
+        assert!(matches!(m.get("b"), Some(KValue::Str(s)) if s == "hello"));
+    }
+
+    #[test]
+    fn number_keys_with_different_representations() {
+        let m = KMap::default();
+        m.insert(1, "int");
+        m.insert(-0.0, "zero");
+
+        assert!(matches!(m.get(&ValueKey::from(1.0)), Some(KValue::Str(s)) if s == "int"));
+        assert!(matches!(m.get(&ValueKey::from(0)), Some(KValue::Str(s)) if s == "zero"));
+        assert!(m.get(&ValueKey::from(2)).is_none());
+    }
+
+    #[test]
+    fn sort_keys() {
+        let mut data = ValueMap::default();
+        data.insert("c".into(), 1.into());
+        data.insert("a".into(), 2.into());
+        data.insert("b".into(), 3.into());
+
+        data.sort_keys();
+
+        let keys = data.keys().map(|key| key.to_string()).collect::<Vec<_>>();
+        assert_eq!(keys, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn get_path() {
+        let inner = KMap::from_iter([("c", KValue::from(42)), ("d.e", "dotted".into())]);
+        let list = KList::from_iter([KValue::from(1), inner.into()]);
+        let mut data = ValueMap::default();
+        data.insert("a".into(), KMap::from_iter([("b", list)]).into());
+
+        assert!(matches!(data.get_path("a.b[1].c"), Some(KValue::Number(n)) if n == 42));
+        assert!(matches!(data.get_path("a.b[0]"), Some(KValue::Number(n)) if n == 1));
+        assert!(matches!(data.get_path(r"a.b[1].d\.e"), Some(KValue::Str(s)) if s == "dotted"));
+        assert!(matches!(data.get_path("a.b"), Some(KValue::List(l)) if l.len() == 2));
+
+        assert!(data.get_path("a.b[2].c").is_none());
+        assert!(data.get_path("a.x.c").is_none());
+        assert!(data.get_path("a.b.c").is_none());
+        assert!(data.get_path("[0]").is_none());
+        // Invalid paths return None
+        assert!(data.get_path("a..b").is_none());
+    }
+
+    #[test]
+    fn set_path() {
+        let mut data = ValueMap::default();
+
+        // Missing containers are created
+        data.set_path("a.b[1].c", 42).unwrap();
+        assert!(matches!(data.get_path("a.b[0]"), Some(KValue::Null)));
+        assert!(matches!(data.get_path("a.b[1].c"), Some(KValue::Number(n)) if n == 42));
+
+        // Existing values are replaced
+        data.set_path("a.b[1].c", "hello").unwrap();
+        assert!(matches!(data.get_path("a.b[1].c"), Some(KValue::Str(s)) if s == "hello"));
+        data.set_path(r"a.x\.y", 99).unwrap();
+        assert!(matches!(data.get_path("a").unwrap(), KValue::Map(m) if m.get("x.y").is_some()));
+
+        // The path must match existing containers
+        assert_eq!(
+            data.set_path("a.b.c", 0).unwrap_err().to_string(),
+            "Expected a Map for 'c', but found 'List'"
+        );
+        assert_eq!(
+            data.set_path("a.b[1][0]", 0).unwrap_err().to_string(),
+            "Expected a List for '[0]', but found 'Map'"
+        );
+        assert_eq!(
+            data.set_path("[0]", 0).unwrap_err().to_string(),
+            "Invalid path '[0]': paths must begin with a key"
+        );
+        assert!(data.set_path("a[", 0).is_err());
+    }
+
+    #[test]
+    fn kmap_set_path_with_cycle() {
+        let m = KMap::default();
+        m.insert("self", m.clone());
+
+        m.set_path("self.self.x", 1).unwrap();
+        assert!(matches!(m.get_path("x"), Some(KValue::Number(n)) if n == 1));
+    }
 }
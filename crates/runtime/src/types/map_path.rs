@@ -0,0 +1,303 @@
+//! Support for accessing nested values in maps with paths like `a.b[2].c`
+
+use crate::{prelude::*, Result};
+use std::{fmt, mem};
+
+/// A segment of a parsed path
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PathSegment {
+    /// A string key used to access an entry in a map
+    Key(String),
+    /// An index used to access an element in a list or tuple
+    Index(usize),
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key(key) => {
+                for c in key.chars() {
+                    if matches!(c, '.' | '[' | ']' | '\\') {
+                        f.write_str("\\")?;
+                    }
+                    write!(f, "{c}")?;
+                }
+                Ok(())
+            }
+            Self::Index(index) => write!(f, "[{index}]"),
+        }
+    }
+}
+
+/// Parses a path into its segments
+///
+/// Keys are separated by `.`, and indices are written in square brackets following a key or
+/// another index, e.g. `a.b[2][0].c`. A `\` escapes the following character, allowing keys to
+/// contain `.`, `[`, `]`, or `\`. Paths always begin with a key.
+pub(crate) fn parse_path(path: &str) -> Result<Vec<PathSegment>> {
+    let error = |message: &str| runtime_error!("Invalid path '{path}': {message}");
+
+    let mut segments = Vec::new();
+    let mut key = String::new();
+    // True at the start of the path and following a '.', false following an index
+    let mut expecting_key = true;
+    let mut chars = path.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '.' | '[' => {
+                if expecting_key {
+                    if !key.is_empty() {
+                        segments.push(PathSegment::Key(mem::take(&mut key)));
+                    } else if c == '[' && segments.is_empty() {
+                        return error("paths must begin with a key");
+                    } else {
+                        return error("missing key");
+                    }
+                }
+
+                if c == '.' {
+                    expecting_key = true;
+                } else {
+                    let mut index = String::new();
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some(c) => index.push(c),
+                            None => return error("missing ']'"),
+                        }
+                    }
+                    let Ok(index) = index.parse() else {
+                        return error(&format!("invalid index '{index}'"));
+                    };
+                    segments.push(PathSegment::Index(index));
+                    expecting_key = false;
+                }
+            }
+            _ if !expecting_key => return error("expected '.' or '[' following an index"),
+            '\\' => match chars.next() {
+                Some(escaped) => key.push(escaped),
+                None => return error("missing escaped character"),
+            },
+            _ => key.push(c),
+        }
+    }
+
+    if expecting_key {
+        if key.is_empty() {
+            return error("missing key");
+        }
+        segments.push(PathSegment::Key(key));
+    }
+
+    Ok(segments)
+}
+
+/// Returns the value found by following the path's segments from the map
+pub(crate) fn get_path(map: &ValueMap, segments: &[PathSegment]) -> Option<KValue> {
+    let Some((PathSegment::Key(first), rest)) = segments.split_first() else {
+        return None;
+    };
+
+    let mut value = map.get(first.as_str())?.clone();
+    for segment in rest {
+        value = get_segment(&value, segment)?;
+    }
+    Some(value)
+}
+
+fn get_segment(value: &KValue, segment: &PathSegment) -> Option<KValue> {
+    match (segment, value) {
+        (PathSegment::Key(key), KValue::Map(m)) => m.get(key.as_str()),
+        (PathSegment::Index(i), KValue::List(l)) => l.data().get(*i).cloned(),
+        (PathSegment::Index(i), KValue::Tuple(t)) => t.get(*i).cloned(),
+        _ => None,
+    }
+}
+
+/// Sets the value at the end of the path, creating missing containers along the way
+///
+/// If a VM is provided, then any allocations made while setting the path are tracked by the VM,
+/// see [KotoVmSettings::memory_limit](crate::KotoVmSettings::memory_limit).
+pub(crate) fn set_path(
+    map: &mut ValueMap,
+    segments: &[PathSegment],
+    value: KValue,
+    vm: Option<&KotoVm>,
+) -> Result<()> {
+    let Some((PathSegment::Key(first), rest)) = segments.split_first() else {
+        return runtime_error!("Paths must begin with a key");
+    };
+
+    if rest.is_empty() {
+        if !map.contains_key(first.as_str()) {
+            track_map_entries(vm)?;
+        }
+        map.insert(first.as_str().into(), value);
+        return Ok(());
+    }
+
+    let child = match map.get(first.as_str()) {
+        Some(existing) if !matches!(existing, KValue::Null) => existing.clone(),
+        existing => {
+            if existing.is_none() {
+                track_map_entries(vm)?;
+            }
+            let child = make_container(&rest[0]);
+            map.insert(first.as_str().into(), child.clone());
+            child
+        }
+    };
+
+    set_value_path(&child, rest, value, vm)
+}
+
+/// Sets the value at the end of the path, starting from a container value
+///
+/// See [set_path].
+pub(crate) fn set_value_path(
+    container: &KValue,
+    segments: &[PathSegment],
+    value: KValue,
+    vm: Option<&KotoVm>,
+) -> Result<()> {
+    let Some((segment, rest)) = segments.split_first() else {
+        return runtime_error!("Missing path");
+    };
+
+    // Each container's data is only borrowed while the child is being retrieved,
+    // so that containers that contain themselves can be safely traversed.
+    let child = match (segment, container) {
+        (PathSegment::Key(key), KValue::Map(m)) => {
+            let existing = m.get(key.as_str());
+            if existing.is_none() {
+                if let Some(vm) = vm {
+                    vm.track_map_growth(m, 1)?;
+                }
+            }
+
+            if rest.is_empty() {
+                m.insert(key.as_str(), value);
+                return Ok(());
+            }
+
+            match existing {
+                Some(existing) if !matches!(existing, KValue::Null) => existing,
+                _ => {
+                    let child = make_container(&rest[0]);
+                    m.insert(key.as_str(), child.clone());
+                    child
+                }
+            }
+        }
+        (PathSegment::Index(i), KValue::List(l)) => {
+            if let Some(vm) = vm {
+                vm.track_list_growth(l, i.saturating_add(1).saturating_sub(l.len()))?;
+            }
+
+            let mut data = l.data_mut();
+            if data.len() <= *i {
+                data.resize(i.saturating_add(1), KValue::Null);
+            }
+
+            if rest.is_empty() {
+                data[*i] = value;
+                return Ok(());
+            }
+
+            if matches!(data[*i], KValue::Null) {
+                data[*i] = make_container(&rest[0]);
+            }
+            data[*i].clone()
+        }
+        (PathSegment::Key(_), unexpected) => {
+            return runtime_error!(
+                "Expected a Map for '{segment}', but found '{}'",
+                unexpected.type_as_string()
+            )
+        }
+        (PathSegment::Index(_), unexpected) => {
+            return runtime_error!(
+                "Expected a List for '{segment}', but found '{}'",
+                unexpected.type_as_string()
+            )
+        }
+    };
+
+    set_value_path(&child, rest, value, vm)
+}
+
+// Makes an empty container that can be accessed with the given segment
+//
+// The container's entries are tracked as they're added, see KotoVmSettings::memory_limit.
+fn make_container(segment: &PathSegment) -> KValue {
+    match segment {
+        PathSegment::Key(_) => KMap::new().into(),
+        PathSegment::Index(_) => KList::default().into(),
+    }
+}
+
+// Tracks the allocation of an entry that's being inserted into a map
+fn track_map_entries(vm: Option<&KotoVm>) -> Result<()> {
+    match vm {
+        Some(vm) => vm.track_map_entry_allocation(1),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PathSegment::*;
+
+    fn key(key: &str) -> PathSegment {
+        Key(key.into())
+    }
+
+    #[test]
+    fn parse_valid_paths() {
+        let check = |path: &str, expected: &[PathSegment]| {
+            assert_eq!(parse_path(path).unwrap(), expected, "path: {path}");
+        };
+
+        check("a", &[key("a")]);
+        check("a.b.c", &[key("a"), key("b"), key("c")]);
+        check("a[2]", &[key("a"), Index(2)]);
+        check(
+            "a.b[2][0].c",
+            &[key("a"), key("b"), Index(2), Index(0), key("c")],
+        );
+        check(r"a\.b.c", &[key("a.b"), key("c")]);
+        check(r"a\[0\]\\", &[key(r"a[0]\")]);
+        check("ñ.🐢", &[key("ñ"), key("🐢")]);
+    }
+
+    #[test]
+    fn parse_invalid_paths() {
+        let check = |path: &str, expected: &str| {
+            assert_eq!(
+                parse_path(path).unwrap_err().to_string(),
+                format!("Invalid path '{path}': {expected}")
+            );
+        };
+
+        check("", "missing key");
+        check("a.", "missing key");
+        check(".a", "missing key");
+        check("a..b", "missing key");
+        check("a.[0]", "missing key");
+        check("a[0", "missing ']'");
+        check("a[x]", "invalid index 'x'");
+        check("a[-1]", "invalid index '-1'");
+        check("a[0]b", "expected '.' or '[' following an index");
+        check(r"a\", "missing escaped character");
+        check("[1].x", "paths must begin with a key");
+        check("[0]", "paths must begin with a key");
+    }
+
+    #[test]
+    fn display_segments() {
+        assert_eq!(key(r"a.b[0]\").to_string(), r"a\.b\[0\]\\");
+        assert_eq!(Index(42).to_string(), "[42]");
+    }
+}
@@ -207,6 +207,8 @@ pub enum UnaryOp {
     Negate,
     /// `@size`
     Size,
+    /// `@hash`
+    Hash,
 }
 
 /// Converts a [MetaKeyId](koto_parser::MetaKeyId) into a [MetaKey]
@@ -238,6 +240,7 @@ pub fn meta_id_to_key(id: MetaKeyId, name: Option<KString>) -> Result<MetaKey> {
         MetaKeyId::Negate => MetaKey::UnaryOp(Negate),
         MetaKeyId::Display => MetaKey::UnaryOp(Display),
         MetaKeyId::Size => MetaKey::UnaryOp(Size),
+        MetaKeyId::Hash => MetaKey::UnaryOp(Hash),
         MetaKeyId::Call => MetaKey::Call,
         MetaKeyId::Named => {
             MetaKey::Named(name.ok_or_else(|| Error::from("Missing name for named meta entry"))?)
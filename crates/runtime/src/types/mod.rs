@@ -4,6 +4,7 @@ mod function;
 mod iterator;
 mod list;
 mod map;
+pub(crate) mod map_path;
 mod meta_map;
 mod native_function;
 mod number;
@@ -15,7 +16,7 @@ pub mod value;
 mod value_key;
 
 pub use self::{
-    function::{KCaptureFunction, KFunction},
+    function::{FunctionInfo, KCaptureFunction, KFunction},
     iterator::{KIterator, KIteratorOutput, KotoIterator},
     list::{KList, ValueVec},
     map::{KMap, KotoHasher, ValueMap},
@@ -29,3 +30,7 @@ pub use self::{
     value::KValue,
     value_key::ValueKey,
 };
+
+pub(crate) use self::{
+    list::KListWeak, map::KMapWeak, object::KObjectWeak, string::KStringWeak, tuple::KTupleWeak,
+};
@@ -22,7 +22,9 @@ impl KNumber {
     pub fn abs(self) -> Self {
         match self {
             Self::F64(n) => Self::F64(n.abs()),
-            Self::I64(n) => Self::I64(n.abs()),
+            Self::I64(n) => n
+                .checked_abs()
+                .map_or(Self::F64((n as f64).abs()), Self::I64),
         }
     }
 
@@ -91,6 +93,9 @@ impl KNumber {
     ///
     /// If both inputs are i64s then the result will also be an i64,
     /// otherwise the result will be an f64.
+    ///
+    /// If the exponent is negative, or if the result would overflow an i64,
+    /// then the result is an f64.
     #[must_use]
     pub fn pow(self, other: Self) -> Self {
         use KNumber::*;
@@ -99,7 +104,10 @@ impl KNumber {
             (F64(a), F64(b)) => F64(a.powf(b)),
             (F64(a), I64(b)) => F64(a.powf(b as f64)),
             (I64(a), F64(b)) => F64((a as f64).powf(b)),
-            (I64(a), I64(b)) => I64(a.pow(b as u32)),
+            (I64(a), I64(b)) => u32::try_from(b)
+                .ok()
+                .and_then(|b| a.checked_pow(b))
+                .map_or_else(|| F64((a as f64).powf(b as f64)), I64),
         }
     }
 
@@ -118,6 +126,22 @@ impl KNumber {
             Self::I64(n) => n,
         }
     }
+
+    /// Returns the number as an index into a container with the given size
+    ///
+    /// Negative numbers count backwards from the end of the container, e.g. `-1` refers to the
+    /// container's last element.
+    ///
+    /// `None` is returned if the index is out of bounds.
+    pub fn as_index(self, size: usize) -> Option<usize> {
+        let index = i64::from(self);
+        let index = if index < 0 {
+            size.checked_sub(index.unsigned_abs().try_into().ok()?)?
+        } else {
+            usize::try_from(index).ok()?
+        };
+        (index < size).then_some(index)
+    }
 }
 
 impl fmt::Debug for KNumber {
@@ -146,7 +170,10 @@ impl fmt::Display for KNumber {
 
 impl Hash for KNumber {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        state.write_u64(self.to_bits())
+        // Numbers are compared as f64s when their representations differ (e.g. `1 == 1.0`),
+        // so they're also hashed as f64s to keep hashing consistent with equality.
+        // Adding 0.0 normalizes -0.0 to 0.0.
+        state.write_u64((f64::from(self) + 0.0).to_bits())
     }
 }
 
@@ -201,7 +228,7 @@ impl ops::Neg for KNumber {
 
         match self {
             F64(n) => F64(-n),
-            I64(n) => I64(-n),
+            I64(n) => n.checked_neg().map_or(F64(-(n as f64)), I64),
         }
     }
 }
@@ -214,7 +241,7 @@ impl ops::Neg for &KNumber {
 
         match *self {
             F64(n) => F64(-n),
-            I64(n) => I64(-n),
+            I64(n) => n.checked_neg().map_or(F64(-(n as f64)), I64),
         }
     }
 }
@@ -361,8 +388,9 @@ from_number!(u64);
 from_number!(isize);
 from_number!(usize);
 
+// Integer arithmetic that would overflow is performed with f64s instead
 macro_rules! number_op {
-    ($trait:ident, $fn:ident, $op:tt) => {
+    ($trait:ident, $fn:ident, $op:tt, $checked_fn:path) => {
         impl ops::$trait for KNumber {
             type Output = KNumber;
 
@@ -373,7 +401,8 @@ macro_rules! number_op {
                     (F64(a), F64(b)) => F64(a $op b),
                     (F64(a), I64(b)) => F64(a $op b as f64),
                     (I64(a), F64(b)) => F64(a as f64 $op b),
-                    (I64(a), I64(b)) => I64(a $op b),
+                    (I64(a), I64(b)) => $checked_fn(a, b)
+                        .map_or_else(|| F64(a as f64 $op b as f64), I64),
                 }
             }
         }
@@ -388,17 +417,28 @@ macro_rules! number_op {
                     (F64(a), F64(b)) => F64(a $op b),
                     (F64(a), I64(b)) => F64(a $op b as f64),
                     (I64(a), F64(b)) => F64(a as f64 $op b),
-                    (I64(a), I64(b)) => I64(a $op b),
+                    (I64(a), I64(b)) => $checked_fn(a, b)
+                        .map_or_else(|| F64(a as f64 $op b as f64), I64),
                 }
             }
         }
     };
 }
 
-number_op!(Add, add, +);
-number_op!(Sub, sub, -);
-number_op!(Mul, mul, *);
-number_op!(Rem, rem, %);
+number_op!(Add, add, +, i64::checked_add);
+number_op!(Sub, sub, -, i64::checked_sub);
+number_op!(Mul, mul, *, i64::checked_mul);
+number_op!(Rem, rem, %, checked_rem);
+
+// The only overflowing integer remainder is `i64::MIN % -1`, which wraps to 0,
+// so the result only needs to fall back to f64 when dividing by zero.
+fn checked_rem(a: i64, b: i64) -> Option<i64> {
+    if b == 0 {
+        None
+    } else {
+        Some(a.wrapping_rem(b))
+    }
+}
 
 impl ops::Div for KNumber {
     type Output = KNumber;
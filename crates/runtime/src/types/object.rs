@@ -1,6 +1,6 @@
-use crate::{prelude::*, Borrow, BorrowMut, PtrMut, Result};
+use crate::{prelude::*, Borrow, BorrowMut, ErrorKind, KCell, PtrMut, PtrWeak, Result};
 use downcast_rs::{impl_downcast, Downcast};
-use std::{fmt, marker::PhantomData};
+use std::{fmt, hash::Hasher, marker::PhantomData};
 
 /// A trait for specifying a Koto object's type
 ///
@@ -177,6 +177,42 @@ pub trait KotoObject: KotoType + KotoCopy + KotoEntries + KotoSend + KotoSync +
         unimplemented_error("@%", self.type_string())
     }
 
+    /// The `+` addition operator, called when the object is on the right-hand side
+    ///
+    /// This is only called when the left-hand side isn't an object, or a map that implements
+    /// the operator.
+    fn add_rhs(&self, _lhs: &KValue) -> Result<KValue> {
+        unimplemented_error("@r+", self.type_string())
+    }
+
+    /// The `-` subtraction operator, called when the object is on the right-hand side
+    ///
+    /// See [KotoObject::add_rhs]
+    fn subtract_rhs(&self, _lhs: &KValue) -> Result<KValue> {
+        unimplemented_error("@r-", self.type_string())
+    }
+
+    /// The `*` multiplication operator, called when the object is on the right-hand side
+    ///
+    /// See [KotoObject::add_rhs]
+    fn multiply_rhs(&self, _lhs: &KValue) -> Result<KValue> {
+        unimplemented_error("@r*", self.type_string())
+    }
+
+    /// The `/` division operator, called when the object is on the right-hand side
+    ///
+    /// See [KotoObject::add_rhs]
+    fn divide_rhs(&self, _lhs: &KValue) -> Result<KValue> {
+        unimplemented_error("@r/", self.type_string())
+    }
+
+    /// The `%` remainder operator, called when the object is on the right-hand side
+    ///
+    /// See [KotoObject::add_rhs]
+    fn remainder_rhs(&self, _lhs: &KValue) -> Result<KValue> {
+        unimplemented_error("@r%", self.type_string())
+    }
+
     /// The `+=` in-place addition operator
     fn add_assign(&mut self, _rhs: &KValue) -> Result<()> {
         unimplemented_error("@+=", self.type_string())
@@ -203,35 +239,94 @@ pub trait KotoObject: KotoType + KotoCopy + KotoEntries + KotoSend + KotoSync +
     }
 
     /// The `<` less-than operator
+    ///
+    /// When the object is on the right-hand side of a comparison, and the left-hand side isn't an
+    /// object or a map that implements the operator, then the comparison is performed with the
+    /// operands swapped, e.g. `1 < x` calls `x.greater(1)`, and `1 > x` calls `x.less(1)`.
     fn less(&self, _rhs: &KValue) -> Result<bool> {
         unimplemented_error("@<", self.type_string())
     }
 
     /// The `<=` less-than-or-equal operator
+    ///
+    /// Also called for `>=` comparisons when the object is on the right-hand side,
+    /// see [KotoObject::less].
     fn less_or_equal(&self, _rhs: &KValue) -> Result<bool> {
         unimplemented_error("@<=", self.type_string())
     }
 
     /// The `>` greater-than operator
+    ///
+    /// Also called for `<` comparisons when the object is on the right-hand side,
+    /// see [KotoObject::less].
     fn greater(&self, _rhs: &KValue) -> Result<bool> {
         unimplemented_error("@>", self.type_string())
     }
 
     /// The `>=` greater-than-or-equal operator
+    ///
+    /// Also called for `<=` comparisons when the object is on the right-hand side,
+    /// see [KotoObject::less].
     fn greater_or_equal(&self, _rhs: &KValue) -> Result<bool> {
         unimplemented_error("@>=", self.type_string())
     }
 
     /// The `==` equality operator
+    ///
+    /// This is also called when the object is on the right-hand side, and the left-hand side isn't
+    /// `null`, an object, or a map that implements the operator.
+    ///
+    /// If the operator is unimplemented, or if a type error is returned
+    /// (e.g. via [type_error](crate::type_error)), then the values are considered to be not equal.
+    /// Any other errors are propagated.
     fn equal(&self, _rhs: &KValue) -> Result<bool> {
         unimplemented_error("@==", self.type_string())
     }
 
     /// The `!=` inequality operator
+    ///
+    /// This is also called when the object is on the right-hand side, see [KotoObject::equal].
     fn not_equal(&self, _rhs: &KValue) -> Result<bool> {
         unimplemented_error("@!=", self.type_string())
     }
 
+    /// Called when a '.' access doesn't match any of the object's [entries](KotoEntries::entries)
+    ///
+    /// This allows objects to provide entries that are generated on demand, e.g. when the set of
+    /// supported keys is too large to declare up front. `None` is returned by default, which
+    /// results in a 'not found' error being thrown.
+    fn access_fallback(&self, _key: &KString) -> Option<KValue> {
+        None
+    }
+
+    /// Called when a value is assigned via '.' access, e.g. `x.foo = 42`
+    ///
+    /// By default the value is inserted into the object's [entries](KotoEntries::entries), which
+    /// are shared by all instances of the object's type. Objects with per-instance data that should
+    /// be assignable (e.g. the components of a vector) should override this function.
+    fn access_assign(&mut self, key: &KString, value: &KValue) -> Result<()> {
+        match self.entries() {
+            Some(entries) => {
+                entries.insert(key.clone(), value.clone());
+                Ok(())
+            }
+            None => runtime_error!("Insertion not supported for '{}'", self.type_string()),
+        }
+    }
+
+    /// Declares to the runtime whether or not the object can be used as a map key
+    ///
+    /// Objects that return `true` should implement [hash](Self::hash) and
+    /// [equal](Self::equal), with objects that are equal producing the same hash.
+    fn is_hashable(&self) -> bool {
+        false
+    }
+
+    /// Feeds the object's contents into the provided hasher
+    ///
+    /// This will only be called if `true` is returned from [is_hashable](Self::is_hashable).
+    fn hash(&self, _state: &mut dyn Hasher) {}
+
     /// Declares to the runtime whether or not the object is iterable
     fn is_iterable(&self) -> IsIterable {
         IsIterable::NotIterable
@@ -320,6 +415,22 @@ impl KObject {
     pub fn ref_count(&self) -> usize {
         PtrMut::ref_count(&self.object)
     }
+
+    /// Returns a weak reference to the object
+    pub(crate) fn downgrade(&self) -> KObjectWeak {
+        KObjectWeak(PtrMut::downgrade(&self.object))
+    }
+}
+
+/// A weak reference to a [KObject], see [KObject::downgrade]
+#[derive(Clone)]
+pub(crate) struct KObjectWeak(PtrWeak<KCell<dyn KotoObject>>);
+
+impl KObjectWeak {
+    /// Returns the referenced object, or None if the object has been dropped
+    pub fn upgrade(&self) -> Option<KObject> {
+        self.0.upgrade().map(|object| KObject { object })
+    }
 }
 
 impl<T: KotoObject> From<T> for KObject {
@@ -387,7 +498,10 @@ impl<'a, T: KotoObject> MethodContext<'a, T> {
 
 /// Creates an error that describes an unimplemented method
 fn unimplemented_error<T>(method: &str, object_type: KString) -> Result<T> {
-    runtime_error!("{method} is unimplemented for {object_type}")
+    runtime_error!(ErrorKind::Unimplemented {
+        method: method.into(),
+        object_type,
+    })
 }
 
 /// An enum that indicates to the runtime if a [KotoObject] is iterable
@@ -141,6 +141,10 @@ impl KRange {
 
     /// Returns the range translated into non-negative indices, suitable for container access
     ///
+    /// Negative boundaries count backwards from `max_index`, e.g. `0..-1` with a `max_index` of
+    /// `10` produces `0..9`. Ranges with negative boundaries are always treated as ascending,
+    /// so if the start index follows the end index then the result will be empty.
+    ///
     /// The start index will be clamped to the range `0..=max_index`.
     /// The end index will be clamped to the range `start..=max_index`
     ///
@@ -148,7 +152,26 @@ impl KRange {
     /// If the end value is `None` then the resulting end index will be `max_index`.
     pub fn indices(&self, max_index: usize) -> Range<usize> {
         let max_index = max_index as i64;
-        let range = self.as_sorted_range();
+        let start = self.start();
+        let end = self.end();
+        let range = if start.is_some_and(|start| start < 0) || end.is_some_and(|(end, _)| end < 0) {
+            let resolve = |index: i64| {
+                if index < 0 {
+                    max_index + index
+                } else {
+                    index
+                }
+            };
+            let start = start.map_or(0, resolve);
+            let end = match end {
+                Some((end, true)) => resolve(end).saturating_add(1),
+                Some((end, false)) => resolve(end),
+                None => max_index,
+            };
+            start..end
+        } else {
+            self.as_sorted_range()
+        };
         let start = range.start.clamp(0, max_index);
         let end = range.end.clamp(start, max_index);
         (start as usize)..(end as usize)
@@ -390,6 +413,20 @@ mod tests {
         assert_eq!(i64::MIN..10, KRange::from(..10).as_sorted_range(),);
     }
 
+    #[test]
+    fn indices() {
+        assert_eq!(2..5, KRange::from(2..5).indices(10));
+        assert_eq!(5..10, KRange::from(5..20).indices(10));
+        assert_eq!(3..6, KRange::from(5..=3).indices(10));
+
+        assert_eq!(0..9, KRange::from(0..-1).indices(10));
+        assert_eq!(8..10, KRange::from(-2..).indices(10));
+        assert_eq!(0..10, KRange::from(..=-1).indices(10));
+        assert_eq!(0..2, KRange::from(-20..2).indices(10));
+        assert_eq!(8..8, KRange::from(-2..-5).indices(10));
+        assert_eq!(0..0, KRange::from(..-1).indices(0));
+    }
+
     #[test]
     fn intersection() {
         assert_eq!(
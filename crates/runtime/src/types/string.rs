@@ -1,4 +1,5 @@
-use crate::{prelude::*, Ptr, Result};
+use crate::{prelude::*, Ptr, PtrWeak, Result};
+use koto_memory::Address;
 use koto_parser::StringSlice;
 use std::{
     fmt,
@@ -27,6 +28,26 @@ enum Inner {
     Slice(Ptr<StringSlice>),
 }
 
+/// A weak reference to a [KString], see [KString::downgrade]
+#[derive(Clone)]
+pub(crate) struct KStringWeak(WeakInner);
+
+#[derive(Clone)]
+enum WeakInner {
+    Full(PtrWeak<str>),
+    Slice(PtrWeak<StringSlice>),
+}
+
+impl KStringWeak {
+    /// Returns the referenced string, or None if the string has been dropped
+    pub fn upgrade(&self) -> Option<KString> {
+        match &self.0 {
+            WeakInner::Full(string) => string.upgrade().map(|string| KString(Inner::Full(string))),
+            WeakInner::Slice(slice) => slice.upgrade().map(|slice| KString(Inner::Slice(slice))),
+        }
+    }
+}
+
 impl KString {
     /// Returns the empty string
     ///
@@ -156,6 +177,22 @@ impl KString {
         }
     }
 
+    /// Returns the address of the string's data, used to identify the string
+    pub(crate) fn address(&self) -> Address {
+        match &self.0 {
+            Inner::Full(string) => Ptr::address(string),
+            Inner::Slice(slice) => Ptr::address(slice),
+        }
+    }
+
+    /// Returns a weak reference to the string's data
+    pub(crate) fn downgrade(&self) -> KStringWeak {
+        match &self.0 {
+            Inner::Full(string) => KStringWeak(WeakInner::Full(Ptr::downgrade(string))),
+            Inner::Slice(slice) => KStringWeak(WeakInner::Slice(Ptr::downgrade(slice))),
+        }
+    }
+
     /// Renders the string to the provided display context
     pub fn display(&self, ctx: &mut DisplayContext) -> Result<()> {
         if ctx.is_contained() {
@@ -241,3 +278,28 @@ impl fmt::Debug for KString {
 thread_local!(
     static EMPTY_STRING: Ptr<str> = Ptr::from("");
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slices_share_data() {
+        let s = KString::from("hello, world!");
+        let slice = s.with_bounds(7..12).unwrap();
+        assert_eq!(slice.as_str(), "world");
+        assert_eq!(slice.as_str().as_ptr(), s.as_str()[7..].as_ptr());
+
+        let sub_slice = slice.with_bounds(1..3).unwrap();
+        assert_eq!(sub_slice.as_str(), "or");
+        assert_eq!(sub_slice.as_str().as_ptr(), s.as_str()[8..].as_ptr());
+    }
+
+    #[test]
+    fn slices_must_be_on_char_boundaries() {
+        let s = KString::from("héllø");
+        assert!(s.with_bounds(0..2).is_none());
+        assert_eq!(s.with_bounds(0..3).unwrap().as_str(), "hé");
+        assert!(s.with_bounds(0..99).is_none());
+    }
+}
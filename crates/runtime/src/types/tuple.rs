@@ -1,4 +1,5 @@
-use crate::{prelude::*, Ptr, Result};
+use crate::{prelude::*, Ptr, PtrWeak, Result};
+use koto_memory::Address;
 use std::ops::{Deref, Range};
 
 /// The Tuple type used by the Koto runtime
@@ -15,6 +16,26 @@ enum Inner {
     Slice(Ptr<TupleSlice>),
 }
 
+/// A weak reference to a [KTuple], see [KTuple::downgrade]
+#[derive(Clone)]
+pub(crate) struct KTupleWeak(WeakInner);
+
+#[derive(Clone)]
+enum WeakInner {
+    Full(PtrWeak<[KValue]>),
+    Slice(PtrWeak<TupleSlice>),
+}
+
+impl KTupleWeak {
+    /// Returns the referenced tuple, or None if the tuple has been dropped
+    pub fn upgrade(&self) -> Option<KTuple> {
+        match &self.0 {
+            WeakInner::Full(data) => data.upgrade().map(|data| KTuple(Inner::Full(data))),
+            WeakInner::Slice(slice) => slice.upgrade().map(|slice| KTuple(Inner::Slice(slice))),
+        }
+    }
+}
+
 #[derive(Clone)]
 struct TupleSlice {
     data: Ptr<[KValue]>,
@@ -108,6 +129,22 @@ impl KTuple {
         }
     }
 
+    /// Returns the address of the tuple's data, used to identify the tuple
+    pub(crate) fn address(&self) -> Address {
+        match &self.0 {
+            Inner::Full(data) => Ptr::address(data),
+            Inner::Slice(slice) => Ptr::address(slice),
+        }
+    }
+
+    /// Returns a weak reference to the tuple's data
+    pub(crate) fn downgrade(&self) -> KTupleWeak {
+        match &self.0 {
+            Inner::Full(data) => KTupleWeak(WeakInner::Full(Ptr::downgrade(data))),
+            Inner::Slice(slice) => KTupleWeak(WeakInner::Slice(Ptr::downgrade(slice))),
+        }
+    }
+
     /// Renders the tuple into the provided display context
     pub fn display(&self, ctx: &mut DisplayContext) -> Result<()> {
         let id = Ptr::address(match &self.0 {
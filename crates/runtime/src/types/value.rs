@@ -1,6 +1,8 @@
 //! The core value type used in the Koto runtime
 
-use crate::{prelude::*, KCaptureFunction, KFunction, Ptr, Result};
+use crate::{prelude::*, Error, FunctionInfo, KCaptureFunction, KFunction, Ptr, Result};
+use koto_memory::Address;
+use rustc_hash::FxHashMap;
 use std::fmt::{self, Write};
 
 /// The core Value type for Koto
@@ -58,35 +60,61 @@ pub enum KValue {
 impl KValue {
     /// Returns a recursive 'deep copy' of a Value
     ///
+    /// Lists and maps that appear multiple times in the value are only copied once, so the copy
+    /// shares its contents in the same way as the original, and cyclic references are reproduced
+    /// in the copy.
+    ///
     /// This is used by koto.deep_copy.
     pub fn deep_copy(&self) -> Result<KValue> {
+        self.deep_copy_with_copies(&mut FxHashMap::default())
+    }
+
+    // Performs a deep copy, with `copies` mapping the addresses of lists and maps that have already
+    // been encountered to their copies.
+    fn deep_copy_with_copies(&self, copies: &mut FxHashMap<Address, KValue>) -> Result<KValue> {
         let result = match &self {
             KValue::List(l) => {
-                let result = l
-                    .data()
-                    .iter()
-                    .map(|v| v.deep_copy())
-                    .collect::<Result<_>>()?;
-                KList::with_data(result).into()
+                if let Some(copy) = copies.get(&l.address()) {
+                    return Ok(copy.clone());
+                }
+
+                // The copy is registered before copying the list's contents,
+                // so that any references back to the list will refer to the copy.
+                let result = KList::with_capacity(l.len());
+                copies.insert(l.address(), result.clone().into());
+
+                for value in l.data().iter() {
+                    let value = value.deep_copy_with_copies(copies)?;
+                    result.data_mut().push(value);
+                }
+
+                result.into()
             }
             KValue::Tuple(t) => {
                 let result = t
                     .iter()
-                    .map(|v| v.deep_copy())
+                    .map(|v| v.deep_copy_with_copies(copies))
                     .collect::<Result<Vec<_>>>()?;
                 KValue::Tuple(result.into())
             }
             KValue::Map(m) => {
-                let data = m
-                    .data()
-                    .iter()
-                    .map(|(k, v)| v.deep_copy().map(|v| (k.clone(), v)))
-                    .collect::<Result<_>>()?;
+                if let Some(copy) = copies.get(&m.address()) {
+                    return Ok(copy.clone());
+                }
+
                 let meta = m.meta_map().map(|meta| meta.borrow().clone());
-                KMap::with_contents(data, meta).into()
+                let result = KMap::with_contents(ValueMap::with_capacity(m.len()), meta);
+                copies.insert(m.address(), result.clone().into());
+
+                for (key, value) in m.data().iter() {
+                    let value = value.deep_copy_with_copies(copies)?;
+                    result.data_mut().insert(key.clone(), value);
+                }
+
+                result.into()
             }
             KValue::Iterator(i) => i.make_copy()?.into(),
-            KValue::Object(o) => o.try_borrow()?.copy().into(),
+            KValue::Object(o) => o.try_borrow()?.deep_copy().into(),
             _ => self.clone(),
         };
 
@@ -105,6 +133,17 @@ impl KValue {
         }
     }
 
+    /// Returns information about the value's arguments if it's a Koto function
+    ///
+    /// `None` is returned for other values, including native functions.
+    pub fn function_info(&self) -> Option<FunctionInfo> {
+        match self {
+            KValue::Function(f) => Some(f.info()),
+            KValue::CaptureFunction(f) => Some(f.info()),
+            _ => None,
+        }
+    }
+
     /// Returns true if the value is a generator function
     pub fn is_generator(&self) -> bool {
         use KValue::*;
@@ -123,6 +162,7 @@ impl KValue {
         match self {
             Null | Bool(_) | Number(_) | Range(_) | Str(_) => true,
             Tuple(t) => t.is_hashable(),
+            Object(o) => o.try_borrow().is_ok_and(|o| o.is_hashable()),
             _ => false,
         }
     }
@@ -181,9 +221,17 @@ impl KValue {
             Bool(b) => write!(ctx, "{b}"),
             Number(n) => write!(ctx, "{n}"),
             Range(r) => write!(ctx, "{r}"),
-            Function(_) | CaptureFunction(_) => write!(ctx, "||"),
+            Function(f) => write!(ctx, "{}({})", self.type_as_string(), f.args_description()),
+            CaptureFunction(f) => {
+                write!(
+                    ctx,
+                    "{}({})",
+                    self.type_as_string(),
+                    f.info.args_description()
+                )
+            }
             Iterator(_) => write!(ctx, "Iterator"),
-            NativeFunction(_) => write!(ctx, "||"),
+            NativeFunction(_) => write!(ctx, "Function(native)"),
             TemporaryTuple(RegisterSlice { start, count }) => {
                 write!(ctx, "TemporaryTuple [{start}..{}]", start + count)
             }
@@ -310,6 +358,95 @@ where
     }
 }
 
+impl From<Vec<KValue>> for KValue {
+    fn from(value: Vec<KValue>) -> Self {
+        Self::List(KList::with_data(ValueVec::from_vec(value)))
+    }
+}
+
+impl TryFrom<&KValue> for bool {
+    type Error = Error;
+
+    fn try_from(value: &KValue) -> Result<Self> {
+        match value {
+            KValue::Bool(b) => Ok(*b),
+            unexpected => type_error("Bool", unexpected),
+        }
+    }
+}
+
+impl TryFrom<&KValue> for f64 {
+    type Error = Error;
+
+    fn try_from(value: &KValue) -> Result<Self> {
+        match value {
+            KValue::Number(n) => Ok(n.into()),
+            unexpected => type_error("Number", unexpected),
+        }
+    }
+}
+
+/// Converts a Number into an `i64`
+///
+/// Floats are only converted if they have no fractional part and are within the `i64` range,
+/// otherwise an error is returned rather than silently truncating the number.
+impl TryFrom<&KValue> for i64 {
+    type Error = Error;
+
+    fn try_from(value: &KValue) -> Result<Self> {
+        match value {
+            KValue::Number(KNumber::I64(n)) => Ok(*n),
+            KValue::Number(KNumber::F64(n)) => {
+                // i64::MAX isn't representable as an f64, so 2^63 is used as the exclusive limit
+                const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+                if n.fract() != 0.0 || !n.is_finite() {
+                    runtime_error!("Expected an integer, but found '{n}'")
+                } else if *n < -LIMIT || *n >= LIMIT {
+                    runtime_error!("The number '{n}' is out of the range of a 64 bit integer")
+                } else {
+                    Ok(*n as i64)
+                }
+            }
+            unexpected => type_error("Number", unexpected),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a KValue> for &'a str {
+    type Error = Error;
+
+    fn try_from(value: &'a KValue) -> Result<Self> {
+        match value {
+            KValue::Str(s) => Ok(s.as_str()),
+            unexpected => type_error("String", unexpected),
+        }
+    }
+}
+
+impl TryFrom<&KValue> for String {
+    type Error = Error;
+
+    fn try_from(value: &KValue) -> Result<Self> {
+        <&str>::try_from(value).map(String::from)
+    }
+}
+
+/// Converts a List or Tuple into a `Vec`, with each element being converted into `T`
+impl<T> TryFrom<&KValue> for Vec<T>
+where
+    T: for<'a> TryFrom<&'a KValue, Error = Error>,
+{
+    type Error = Error;
+
+    fn try_from(value: &KValue) -> Result<Self> {
+        match value {
+            KValue::List(l) => l.data().iter().map(T::try_from).collect(),
+            KValue::Tuple(t) => t.iter().map(T::try_from).collect(),
+            unexpected => type_error("List or Tuple", unexpected),
+        }
+    }
+}
+
 /// A slice of a VM's registers
 ///
 /// See [Value::TemporaryTuple]
@@ -330,4 +467,58 @@ mod tests {
         // total size of Value will be <= 24 bytes.
         assert!(std::mem::size_of::<KValue>() <= 24);
     }
+
+    #[test]
+    fn primitive_conversions() {
+        assert!(bool::try_from(&KValue::from(true)).unwrap());
+        assert_eq!(f64::try_from(&KValue::from(1.5)).unwrap(), 1.5);
+        assert_eq!(f64::try_from(&KValue::from(2)).unwrap(), 2.0);
+        assert_eq!(i64::try_from(&KValue::from(-42)).unwrap(), -42);
+        assert_eq!(<&str>::try_from(&KValue::from("abc")).unwrap(), "abc");
+        assert_eq!(String::try_from(&KValue::from("abc")).unwrap(), "abc");
+
+        let list = KValue::from(vec![KValue::from(1), KValue::from(2.5)]);
+        assert!(matches!(list, KValue::List(_)));
+        assert_eq!(Vec::<f64>::try_from(&list).unwrap(), vec![1.0, 2.5]);
+
+        let tuple = KValue::Tuple(vec![KValue::from("x"), KValue::from("y")].into());
+        assert_eq!(Vec::<String>::try_from(&tuple).unwrap(), vec!["x", "y"]);
+    }
+
+    #[test]
+    fn conversion_errors_describe_the_unexpected_value() {
+        let error = f64::try_from(&KValue::from("abc")).unwrap_err();
+        assert_eq!(error.to_string(), "Expected 'Number', but found 'String'");
+
+        let error = bool::try_from(&KValue::Null).unwrap_err();
+        assert_eq!(error.to_string(), "Expected 'Bool', but found 'Null'");
+
+        // An element that fails to convert causes the whole conversion to fail
+        let list = KValue::from(vec![KValue::from(1), KValue::from("2")]);
+        assert!(Vec::<i64>::try_from(&list).is_err());
+    }
+
+    #[test]
+    fn lossy_integer_conversions() {
+        // Floats without a fractional part can be converted
+        assert_eq!(i64::try_from(&KValue::from(3.0)).unwrap(), 3);
+        assert_eq!(
+            i64::try_from(&KValue::from(-1e18)).unwrap(),
+            -1_000_000_000_000_000_000
+        );
+
+        // Fractional parts aren't truncated
+        assert!(i64::try_from(&KValue::from(2.5)).is_err());
+        assert!(i64::try_from(&KValue::from(-0.1)).is_err());
+        assert!(i64::try_from(&KValue::from(f64::NAN)).is_err());
+        assert!(i64::try_from(&KValue::from(f64::INFINITY)).is_err());
+
+        // Floats outside of the i64 range aren't saturated
+        assert!(i64::try_from(&KValue::from(9.3e18)).is_err());
+        assert!(i64::try_from(&KValue::from(-9.3e18)).is_err());
+        assert_eq!(
+            i64::try_from(&KValue::from(-9_223_372_036_854_775_808.0)).unwrap(),
+            i64::MIN
+        );
+    }
 }
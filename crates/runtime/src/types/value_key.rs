@@ -1,4 +1,4 @@
-use crate::{prelude::*, Error};
+use crate::{prelude::*, Error, Ptr};
 use indexmap::Equivalent;
 use std::{
     cmp::Ordering,
@@ -8,14 +8,50 @@ use std::{
 
 /// The key type used by [ValueMap](crate::ValueMap)
 ///
-/// Only hashable values can be used as keys, see [KValue::is_hashable]
+/// Only hashable values can be used as keys, see [KValue::is_hashable].
+///
+/// Maps that implement `@hash` can also be used as keys, see [KotoVm::make_value_key].
+#[derive(Clone)]
+pub struct ValueKey(KeyValue);
+
 #[derive(Clone)]
-pub struct ValueKey(KValue);
+enum KeyValue {
+    // A hashable value
+    Value(KValue),
+    // A map that implements @hash, along with the key made from the result of calling @hash
+    HashedMap(Ptr<(KValue, ValueKey)>),
+}
 
 impl ValueKey {
+    /// Makes a key for a value that provides its own hash value, e.g. a map that implements `@hash`
+    ///
+    /// The hash value is used in place of the value when hashing and comparing keys, so keys
+    /// with equal hash values are considered to be equal.
+    ///
+    /// An error is returned if the hash value isn't hashable.
+    pub fn with_hash(value: KValue, hash: KValue) -> Result<Self, Error> {
+        if !hash.is_hashable() {
+            return runtime_error!(
+                "Expected a hashable value from @hash, found '{}'",
+                hash.type_as_string()
+            );
+        }
+
+        let hash = Self::try_from(hash)?;
+        Ok(Self(KeyValue::HashedMap(Ptr::from((value, hash)))))
+    }
+
     /// Returns a reference to the key's value
     pub fn value(&self) -> &KValue {
-        &self.0
+        match &self.0 {
+            KeyValue::Value(value) => value,
+            KeyValue::HashedMap(hashed) => &hashed.0,
+        }
+    }
+
+    // Makes a key from a value that's known to be hashable
+    fn from_hashable(value: KValue) -> Self {
+        Self(KeyValue::Value(value))
     }
 }
 
@@ -24,9 +60,23 @@ impl TryFrom<KValue> for ValueKey {
 
     fn try_from(value: KValue) -> Result<Self, Self::Error> {
         if value.is_hashable() {
-            Ok(Self(value))
+            match value {
+                // Objects are copied so that the key isn't affected by later modifications
+                KValue::Object(o) => Ok(Self::from_hashable(o.try_borrow()?.copy().into())),
+                _ => Ok(Self::from_hashable(value)),
+            }
         } else {
-            runtime_error!("Only hashable values can be used as value keys")
+            match value {
+                KValue::Map(m) if m.contains_meta_key(&UnaryOp::Hash.into()) => runtime_error!(
+                    "A Map that implements @hash needs to be converted into a key \
+                     with KotoVm::make_value_key"
+                ),
+                KValue::Map(m) if m.contains_meta_key(&BinaryOp::Equal.into()) => runtime_error!(
+                    "A Map that implements @== also needs to implement @hash \
+                     to be used as a key"
+                ),
+                _ => runtime_error!("Only hashable values can be used as value keys"),
+            }
         }
     }
 }
@@ -35,17 +85,27 @@ impl PartialEq for ValueKey {
     fn eq(&self, other: &Self) -> bool {
         use KValue::*;
 
-        match (&self.0, &other.0) {
+        let (a, b) = match (&self.0, &other.0) {
+            (KeyValue::Value(a), KeyValue::Value(b)) => (a, b),
+            (KeyValue::HashedMap(a), KeyValue::HashedMap(b)) => return a.1 == b.1,
+            _ => return false,
+        };
+
+        match (a, b) {
             (Number(a), Number(b)) => a == b,
             (Bool(a), Bool(b)) => a == b,
             (Str(a), Str(b)) => a == b,
             (Range(a), Range(b)) => a == b,
             (Null, Null) => true,
+            (Object(a), Object(b)) => a
+                .try_borrow()
+                .and_then(|a| a.equal(&Object(b.clone())))
+                .unwrap_or(false),
             (Tuple(a), Tuple(b)) => {
                 a.len() == b.len()
-                    && a.iter()
-                        .zip(b.iter())
-                        .all(|(value_a, value_b)| Self(value_a.clone()) == Self(value_b.clone()))
+                    && a.iter().zip(b.iter()).all(|(value_a, value_b)| {
+                        Self::from_hashable(value_a.clone()) == Self::from_hashable(value_b.clone())
+                    })
             }
             _ => false,
         }
@@ -57,15 +117,25 @@ impl Hash for ValueKey {
     fn hash<H: Hasher>(&self, state: &mut H) {
         use KValue::*;
 
-        match &self.0 {
+        let value = match &self.0 {
+            KeyValue::Value(value) => value,
+            KeyValue::HashedMap(hashed) => return hashed.1.hash(state),
+        };
+
+        match value {
             Null => {}
             Bool(b) => b.hash(state),
             Number(n) => n.hash(state),
             Str(s) => s.hash(state),
             Range(r) => r.hash(state),
+            Object(o) => {
+                if let Ok(o) = o.try_borrow() {
+                    o.hash(state)
+                }
+            }
             Tuple(t) => {
                 for value in t.iter() {
-                    Self(value.clone()).hash(state)
+                    Self::from_hashable(value.clone()).hash(state)
                 }
             }
             _ => {}
@@ -77,7 +147,13 @@ impl PartialOrd for ValueKey {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         use KValue::*;
 
-        match (&self.0, &other.0) {
+        let (a, b) = match (&self.0, &other.0) {
+            (KeyValue::Value(a), KeyValue::Value(b)) => (a, b),
+            (KeyValue::HashedMap(a), KeyValue::HashedMap(b)) => return a.1.partial_cmp(&b.1),
+            _ => return Some(Ordering::Equal),
+        };
+
+        match (a, b) {
             (Null, Null) => Some(Ordering::Equal),
             (Null, _) => Some(Ordering::Less),
             (_, Null) => Some(Ordering::Greater),
@@ -88,7 +164,9 @@ impl PartialOrd for ValueKey {
                     for (value_a, value_b) in a.iter().zip(b.iter()) {
                         // Only ValueRef-able values will be contained in a tuple that's made it
                         // into a ValueKey
-                        match Self(value_a.clone()).partial_cmp(&Self(value_b.clone())) {
+                        match Self::from_hashable(value_a.clone())
+                            .partial_cmp(&Self::from_hashable(value_b.clone()))
+                        {
                             Some(Ordering::Equal) => {}
                             other => return other,
                         }
@@ -106,19 +184,24 @@ impl fmt::Display for ValueKey {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         use KValue::*;
 
-        match &self.0 {
+        match self.value() {
             Null => f.write_str("null"),
             Bool(b) => write!(f, "{b}"),
             Number(n) => write!(f, "{n}"),
             Range(r) => write!(f, "{r}"),
             Str(s) => f.write_str(s),
+            value @ (Object(_) | Map(_)) => {
+                let mut ctx = DisplayContext::default();
+                value.display(&mut ctx).map_err(|_| fmt::Error)?;
+                f.write_str(&ctx.result())
+            }
             Tuple(t) => {
                 f.write_str("(")?;
                 for (i, value) in t.iter().enumerate() {
                     if i > 0 {
                         f.write_str(", ")?;
                     }
-                    write!(f, "{}", Self(value.clone()))?;
+                    write!(f, "{}", Self::from_hashable(value.clone()))?;
                 }
                 f.write_str(")")
             }
@@ -129,7 +212,7 @@ impl fmt::Display for ValueKey {
 
 impl From<KString> for ValueKey {
     fn from(value: KString) -> Self {
-        Self(KValue::Str(value))
+        Self::from_hashable(KValue::Str(value))
     }
 }
 
@@ -138,13 +221,13 @@ where
     KNumber: From<T>,
 {
     fn from(value: T) -> Self {
-        Self(KValue::Number(value.into()))
+        Self::from_hashable(KValue::Number(value.into()))
     }
 }
 
 impl From<&str> for ValueKey {
     fn from(value: &str) -> Self {
-        Self(KValue::Str(value.into()))
+        Self::from_hashable(KValue::Str(value.into()))
     }
 }
 
@@ -152,7 +235,7 @@ impl From<&str> for ValueKey {
 impl Equivalent<ValueKey> for str {
     fn equivalent(&self, other: &ValueKey) -> bool {
         match &other.0 {
-            KValue::Str(s) => self == s.as_str(),
+            KeyValue::Value(KValue::Str(s)) => self == s.as_str(),
             _ => false,
         }
     }
@@ -161,7 +244,7 @@ impl Equivalent<ValueKey> for str {
 impl Equivalent<ValueKey> for KString {
     fn equivalent(&self, other: &ValueKey) -> bool {
         match &other.0 {
-            KValue::Str(s) => self == s,
+            KeyValue::Value(KValue::Str(s)) => self == s,
             _ => false,
         }
     }
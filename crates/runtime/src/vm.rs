@@ -1,19 +1,27 @@
 use crate::{
     core_lib::CoreLib,
     error::{Error, ErrorKind},
+    memory_usage::{map_entries_size, values_size, MemoryUsage, Owner},
     prelude::*,
+    profile::{FrameProfile, ProfileEntryKind, Profiler},
     types::{meta_id_to_key, value::RegisterSlice},
-    DefaultStderr, DefaultStdin, DefaultStdout, KCaptureFunction, KFunction, Ptr, Result,
+    DefaultFilesystem, DefaultStderr, DefaultStdin, DefaultStdout, KCaptureFunction, KFunction,
+    Ptr, Result,
 };
 use instant::Instant;
 use koto_bytecode::{Chunk, Instruction, InstructionReader, Loader};
-use koto_parser::{ConstantIndex, MetaKeyId, StringAlignment, StringFormatOptions};
+use koto_memory::Address;
+use koto_parser::{ConstantIndex, MetaKeyId, Span, StringAlignment, StringFormatOptions};
 use rustc_hash::FxHasher;
 use std::{
     collections::HashMap,
     fmt,
     hash::BuildHasherDefault,
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     time::Duration,
 };
 use unicode_segmentation::UnicodeSegmentation;
@@ -55,6 +63,16 @@ struct VmContext {
     loader: KCell<Loader>,
     // The cached export maps of imported modules
     imported_modules: KCell<ModuleCache>,
+    // The approximate number of bytes that are in use, see KotoVmSettings::memory_limit
+    memory_usage: KCell<MemoryUsage>,
+    // The flag that's checked during execution to see if an interruption has been requested
+    interrupt: KotoInterrupt,
+    // The breakpoints that have been set via KotoVm::set_breakpoint
+    breakpoints: KCell<Vec<Breakpoint>>,
+    // The addresses of maps whose @display functions are currently being evaluated
+    active_display_ops: KCell<Vec<Address>>,
+    // The profiler, available when KotoVmSettings::profiling is enabled
+    profiler: Option<KCell<Profiler>>,
 }
 
 impl Default for VmContext {
@@ -66,6 +84,7 @@ impl Default for VmContext {
 impl VmContext {
     fn with_settings(settings: KotoVmSettings) -> Self {
         let core_lib = CoreLib::default();
+        let profiler = settings.profiling.then(|| Profiler::default().into());
 
         Self {
             settings,
@@ -73,6 +92,11 @@ impl VmContext {
             core_lib,
             loader: Loader::default().into(),
             imported_modules: ModuleCache::default().into(),
+            memory_usage: KCell::default(),
+            interrupt: KotoInterrupt::default(),
+            breakpoints: Vec::new().into(),
+            active_display_ops: Vec::new().into(),
+            profiler,
         }
     }
 }
@@ -83,6 +107,87 @@ pub trait ModuleImportedCallback: Fn(&Path) + KotoSend + KotoSync {}
 // Implement the trait for any matching function
 impl<T> ModuleImportedCallback for T where T: Fn(&Path) + KotoSend + KotoSync {}
 
+/// A module provided by a [ModuleLoader]
+pub enum LoadedModule {
+    /// A Koto script that will be compiled and run by the runtime to produce the module's exports
+    Script {
+        /// The path of the module
+        ///
+        /// The path doesn't need to exist on the file system. It's used to identify the module in
+        /// the runtime's module cache and in error messages, and it's passed to the loader when the
+        /// module imports other modules.
+        path: PathBuf,
+        /// The module's script
+        script: String,
+    },
+    /// A map that will be used directly as the module
+    Map(KMap),
+}
+
+/// The trait used by the custom module loader mechanism, see [KotoVmSettings::module_loader]
+///
+/// The loader is called with the name of the imported module, and the path of the importing
+/// script. If `None` is returned then the runtime will attempt to load the module from the file
+/// system.
+pub trait ModuleLoader:
+    Fn(&str, Option<&Path>) -> Result<Option<LoadedModule>> + KotoSend + KotoSync
+{
+}
+
+// Implement the trait for any matching function
+impl<T> ModuleLoader for T where
+    T: Fn(&str, Option<&Path>) -> Result<Option<LoadedModule>> + KotoSend + KotoSync
+{
+}
+
+/// The trait used by the trace hook mechanism, see [KotoVmSettings::trace_hook]
+pub trait TraceHook: Fn(&TraceContext) -> TraceControl + KotoSend + KotoSync {}
+
+// Implement the trait for any matching function
+impl<T> TraceHook for T where T: Fn(&TraceContext) -> TraceControl + KotoSend + KotoSync {}
+
+/// Information about the instruction that's about to be executed, see [TraceHook]
+pub struct TraceContext<'a> {
+    /// The VM that's executing the instruction
+    pub vm: &'a KotoVm,
+    /// The position of the instruction in the active chunk's bytecode
+    pub ip: u32,
+}
+
+impl TraceContext<'_> {
+    /// The bytecode chunk that contains the instruction
+    pub fn chunk(&self) -> Ptr<Chunk> {
+        self.vm.chunk()
+    }
+
+    /// The span in the chunk's source that produced the instruction
+    pub fn span(&self) -> Option<Span> {
+        self.vm.reader.chunk.debug_info.get_source_span(self.ip)
+    }
+
+    /// The values in the active call frame's registers
+    ///
+    /// The first register contains the frame's instance (or `null`), followed by the function's
+    /// arguments and local values, and then any temporary values.
+    pub fn frame_registers(&self) -> &[KValue] {
+        &self.vm.registers[self.vm.register_base()..]
+    }
+
+    /// The exports map of the active module
+    pub fn exports(&self) -> &KMap {
+        self.vm.exports()
+    }
+}
+
+/// The value returned by a [TraceHook], determining whether or not execution should continue
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceControl {
+    /// Execution should continue with the instruction
+    Continue,
+    /// Execution should stop with an [Interrupted](ErrorKind::Interrupted) error
+    Abort,
+}
+
 /// The configurable settings that should be used by the Koto runtime
 pub struct KotoVmSettings {
     /// Whether or not tests should be run when importing modules
@@ -98,14 +203,77 @@ pub struct KotoVmSettings {
     ///
     /// The check is performed between VM instructions, so external functions will still be able to
     /// block execution.
+    ///
+    /// The limit applies to each call into the runtime, including calls made from Rust functions
+    /// via [KotoVm::call_function], with each call having its own deadline.
     pub execution_limit: Option<Duration>,
 
+    /// An optional limit on the approximate number of bytes that can be allocated by the runtime
+    ///
+    /// If the limit is exceeded then a [MemoryLimit](ErrorKind::MemoryLimit) error will be
+    /// returned. The error can't be caught by scripts.
+    ///
+    /// The VM keeps a running total of the memory allocated when lists, tuples, maps, and strings
+    /// are created or grown, with each element counting as the size of a [KValue].
+    /// Memory is returned to the total when containers are dropped or shrink, so the limit applies
+    /// to the memory that's in use rather than to the memory that has been allocated over time.
+    /// The total is reset by [KotoVm::reset_memory_usage], and the `Koto` API resets the total
+    /// for each call into the runtime, in the same way that each call has its own execution
+    /// deadline.
+    ///
+    /// Allocations made by external functions and objects aren't counted unless they call
+    /// [KotoVm::track_allocation], and memory tracked in this way isn't returned to the total
+    /// until it's reset.
+    pub memory_limit: Option<usize>,
+
     /// An optional callback that is called whenever a module is imported by the runtime
     ///
     /// This allows you to track the runtime's dependencies, which might be useful if you want to
     /// reload the script when one of its dependencies has changed.
     pub module_imported_callback: Option<Box<dyn ModuleImportedCallback>>,
 
+    /// An optional loader that's used to provide imported modules
+    ///
+    /// The loader is used when an imported module isn't found in the current exports or in the
+    /// prelude, allowing modules to be provided from sources other than the file system.
+    pub module_loader: Option<Box<dyn ModuleLoader>>,
+
+    /// An optional hook that's called before each instruction is executed
+    ///
+    /// This allows tools like profilers and debuggers to follow the progress of execution,
+    /// with the instruction's source span available via the provided [TraceContext].
+    ///
+    /// Execution continues when the hook returns [TraceControl::Continue], so the hook can pause
+    /// execution by blocking until it's ready to continue, e.g. while waiting for user input in a
+    /// step debugger. Returning [TraceControl::Abort] stops execution.
+    ///
+    /// When no hook is installed, the only cost to the runtime is a check per instruction.
+    pub trace_hook: Option<Box<dyn TraceHook>>,
+
+    /// An optional callback that's called when execution reaches a breakpoint
+    ///
+    /// Breakpoints are set with [KotoVm::set_breakpoint], and the callback is called before the
+    /// breakpoint's instruction is executed. The provided [TraceContext] allows the values in the
+    /// active call frame, along with the active module's exports, to be inspected.
+    ///
+    /// Execution is paused while the callback is running, and resumes when the callback returns
+    /// [TraceControl::Continue]. Returning [TraceControl::Abort] stops execution.
+    pub breakpoint_callback: Option<Box<dyn TraceHook>>,
+
+    /// Enables the runtime's profiler
+    ///
+    /// When enabled, the time spent in each Koto function, along with the time spent in
+    /// external functions, is recorded and made available via [KotoVm::profile_report].
+    ///
+    /// Profiling adds some overhead to function calls, so it's disabled by default.
+    pub profiling: bool,
+
+    /// The filesystem used by the `io` core library module
+    ///
+    /// By default the system's filesystem is used, a custom [KotoFilesystem] can be provided to
+    /// restrict or virtualize the filesystem access that's available to scripts.
+    pub filesystem: Ptr<dyn KotoFilesystem>,
+
     /// The runtime's stdin
     pub stdin: Ptr<dyn KotoFile>,
 
@@ -121,7 +289,13 @@ impl Default for KotoVmSettings {
         Self {
             run_import_tests: true,
             execution_limit: None,
+            memory_limit: None,
             module_imported_callback: None,
+            module_loader: None,
+            trace_hook: None,
+            breakpoint_callback: None,
+            profiling: false,
+            filesystem: make_ptr!(DefaultFilesystem::default()),
             stdin: make_ptr!(DefaultStdin::default()),
             stdout: make_ptr!(DefaultStdout::default()),
             stderr: make_ptr!(DefaultStderr::default()),
@@ -229,6 +403,11 @@ impl KotoVm {
         &mut self.exports
     }
 
+    /// The filesystem used by the VM
+    pub fn filesystem(&self) -> &Ptr<dyn KotoFilesystem> {
+        &self.context.settings.filesystem
+    }
+
     /// The stdin wrapper used by the VM
     pub fn stdin(&self) -> &Ptr<dyn KotoFile> {
         &self.context.settings.stdin
@@ -244,6 +423,154 @@ impl KotoVm {
         &self.context.settings.stderr
     }
 
+    /// Records an allocation of the given number of bytes, see [KotoVmSettings::memory_limit]
+    ///
+    /// An error will be returned if the allocation causes the memory limit to be exceeded.
+    pub fn track_allocation(&self, bytes: usize) -> Result<()> {
+        self.allocate(None::<&KList>, bytes)
+    }
+
+    /// Records an allocation of the given number of values for a container that will be created
+    ///
+    /// The allocation should be assigned to the container once it's been created,
+    /// see [KotoVm::assign_allocation].
+    pub(crate) fn track_value_allocation(&self, count: usize) -> Result<()> {
+        self.track_allocation(values_size(count))
+    }
+
+    /// Records an allocation of the given number of map entries for a map that will be created
+    ///
+    /// The allocation should be assigned to the map once it's been created,
+    /// see [KotoVm::assign_allocation].
+    pub(crate) fn track_map_entry_allocation(&self, count: usize) -> Result<()> {
+        self.track_allocation(map_entries_size(count))
+    }
+
+    /// Records an allocation of the given number of values that will be added to a list
+    pub(crate) fn track_list_growth(&self, list: &KList, count: usize) -> Result<()> {
+        self.allocate(Some(list), values_size(count))
+    }
+
+    /// Records an allocation of the given number of entries that will be added to a map
+    pub(crate) fn track_map_growth(&self, map: &KMap, count: usize) -> Result<()> {
+        self.allocate(Some(map), map_entries_size(count))
+    }
+
+    /// Assigns previously tracked allocations to the container that now owns them
+    ///
+    /// The allocation is then released when the container is dropped.
+    pub(crate) fn assign_allocation(&self, owner: impl Into<Owner>, bytes: usize) {
+        if self.context.settings.memory_limit.is_some() {
+            self.context
+                .memory_usage
+                .borrow_mut()
+                .assign(owner.into(), bytes);
+        }
+    }
+
+    fn allocate(&self, owner: Option<impl Into<Owner>>, bytes: usize) -> Result<()> {
+        if let Some(limit) = self.context.settings.memory_limit {
+            let within_limit = self.context.memory_usage.borrow_mut().allocate(
+                owner.map(Into::into),
+                bytes,
+                limit,
+            );
+            if !within_limit {
+                return runtime_error!(ErrorKind::MemoryLimit(limit));
+            }
+        }
+        Ok(())
+    }
+
+    /// The approximate number of bytes that are in use by the runtime
+    ///
+    /// Allocations are only tracked when a [memory limit](KotoVmSettings::memory_limit) is set.
+    /// Memory used by containers that have been dropped is released from the total when the limit
+    /// would otherwise be exceeded.
+    pub fn memory_usage(&self) -> usize {
+        self.context.memory_usage.borrow().total()
+    }
+
+    /// Resets the runtime's tracked memory usage, see [KotoVmSettings::memory_limit]
+    pub fn reset_memory_usage(&self) {
+        self.context.memory_usage.borrow_mut().reset();
+    }
+
+    /// Returns a handle that can be used to interrupt execution, see [KotoInterrupt]
+    pub fn interrupt_handle(&self) -> KotoInterrupt {
+        self.context.interrupt.clone()
+    }
+
+    /// Clears any pending interruption that was requested via a [KotoInterrupt] handle
+    pub fn clear_interrupt(&self) {
+        self.context.interrupt.clear();
+    }
+
+    /// Sets a breakpoint at the first instruction found on the given line of the chunk's source
+    ///
+    /// The line is one-based, matching the line numbers shown in error messages.
+    /// When execution reaches the breakpoint, [KotoVmSettings::breakpoint_callback] is called.
+    ///
+    /// An error is returned if no instructions were found for the line.
+    pub fn set_breakpoint(&self, chunk: Ptr<Chunk>, line: u32) -> Result<()> {
+        let Some(ip) = line
+            .checked_sub(1)
+            .and_then(|line| chunk.debug_info.first_ip_on_line(line))
+        else {
+            return runtime_error!("No instructions found on line {line}");
+        };
+
+        self.context
+            .breakpoints
+            .borrow_mut()
+            .push(Breakpoint { chunk, ip });
+        Ok(())
+    }
+
+    /// Removes all breakpoints that were set with [KotoVm::set_breakpoint]
+    pub fn clear_breakpoints(&self) {
+        self.context.breakpoints.borrow_mut().clear();
+    }
+
+    // Registers that a @display function is being evaluated for the value at the given address
+    //
+    // Returns false if the value's @display function is already being evaluated.
+    pub(crate) fn start_display_op(&self, address: Address) -> bool {
+        let mut active = self.context.active_display_ops.borrow_mut();
+        if active.contains(&address) {
+            false
+        } else {
+            active.push(address);
+            true
+        }
+    }
+
+    // Called when a @display function registered with start_display_op has finished
+    pub(crate) fn end_display_op(&self, address: Address) {
+        let mut active = self.context.active_display_ops.borrow_mut();
+        if let Some(position) = active.iter().rposition(|a| *a == address) {
+            active.remove(position);
+        }
+    }
+
+    /// Returns a report containing the profiling data that has been collected by the runtime
+    ///
+    /// Profiling data is collected when [KotoVmSettings::profiling] is enabled, otherwise `None`
+    /// is returned. The data accumulates until [KotoVm::reset_profile] is called.
+    pub fn profile_report(&self) -> Option<ProfileReport> {
+        self.context
+            .profiler
+            .as_ref()
+            .map(|profiler| profiler.borrow().report())
+    }
+
+    /// Clears the profiling data that has been collected by the runtime
+    pub fn reset_profile(&self) {
+        if let Some(profiler) = &self.context.profiler {
+            profiler.borrow_mut().reset();
+        }
+    }
+
     /// Runs the provided [Chunk], returning the resulting [KValue]
     pub fn run(&mut self, chunk: Ptr<Chunk>) -> Result<KValue> {
         // Set up an execution frame to run the chunk in
@@ -252,6 +579,7 @@ impl KotoVm {
         self.registers.push(KValue::Null); // result register
         self.registers.push(KValue::Null); // instance register
         self.push_frame(chunk, 0, frame_base, result_register);
+        self.start_frame_profile(0);
 
         // Ensure that execution stops here if an error is thrown
         self.frame_mut().execution_barrier = true;
@@ -441,6 +769,18 @@ impl KotoVm {
                 }
             },
             Size => self.run_size(result_register, value_register, true)?,
+            Hash => match self.clone_register(value_register) {
+                KValue::Map(m) if m.contains_meta_key(&Hash.into()) => {
+                    let op = m.get_meta_value(&Hash.into()).unwrap();
+                    if !op.is_callable() {
+                        return type_error("Callable function from @hash", &op);
+                    }
+                    self.call_overridden_unary_op(result_register, value_register, op)?
+                }
+                unexpected => {
+                    return type_error("Value with an implementation of @hash", &unexpected)
+                }
+            },
         }
 
         let result = if self.call_stack.len() == old_frame_count {
@@ -460,6 +800,21 @@ impl KotoVm {
         result
     }
 
+    /// Makes a [ValueKey] from the provided value
+    ///
+    /// Maps that implement `@hash` can be used as keys, with the result of calling `@hash` being
+    /// stored in the key, see [ValueKey::with_hash]. Other values are converted with
+    /// [ValueKey::try_from].
+    pub fn make_value_key(&mut self, value: KValue) -> Result<ValueKey> {
+        match &value {
+            KValue::Map(m) if m.contains_meta_key(&UnaryOp::Hash.into()) => {
+                let hash = self.run_unary_op(UnaryOp::Hash, value.clone())?;
+                ValueKey::with_hash(value, hash)
+            }
+            _ => ValueKey::try_from(value),
+        }
+    }
+
     /// Provides the result of running a binary operation on a pair of Values
     pub fn run_binary_op(&mut self, op: BinaryOp, lhs: KValue, rhs: KValue) -> Result<KValue> {
         let old_frame_count = self.call_stack.len();
@@ -651,12 +1006,26 @@ impl KotoVm {
     }
 
     fn execute_instructions(&mut self) -> Result<KValue> {
+        // A separate version of the execution loop is used when a trace hook is installed or when
+        // breakpoints have been set, avoiding any overhead when they aren't needed.
+        if self.context.settings.trace_hook.is_some()
+            || !self.context.breakpoints.borrow().is_empty()
+        {
+            self.execute_instructions_with_trace::<true>()
+        } else {
+            self.execute_instructions_with_trace::<false>()
+        }
+    }
+
+    fn execute_instructions_with_trace<const TRACE: bool>(&mut self) -> Result<KValue> {
         let mut timeout = self
             .context
             .settings
             .execution_limit
             .map(ExecutionTimeout::new);
 
+        let mut instructions_until_interrupt_check = 0;
+
         self.instruction_ip = self.ip();
 
         // Every code path in this function must set the execution state to something other
@@ -664,6 +1033,26 @@ impl KotoVm {
         self.execution_state = ExecutionState::Active;
 
         while let Some(instruction) = self.reader.next() {
+            // The check is also made for the first instruction, so that short function calls made
+            // by external functions are able to be interrupted.
+            if instructions_until_interrupt_check == 0 {
+                if self.context.interrupt.is_interrupted() {
+                    self.execution_state = ExecutionState::Inactive;
+                    return self
+                        .pop_call_stack_on_error(ErrorKind::Interrupted.into(), false)
+                        .map(|_| KValue::Null);
+                }
+                instructions_until_interrupt_check = INTERRUPT_CHECK_INTERVAL;
+            }
+            instructions_until_interrupt_check -= 1;
+
+            if TRACE && self.call_trace_hook() == TraceControl::Abort {
+                self.execution_state = ExecutionState::Inactive;
+                return self
+                    .pop_call_stack_on_error(ErrorKind::Interrupted.into(), false)
+                    .map(|_| KValue::Null);
+            }
+
             if let Some(timeout) = timeout.as_mut() {
                 if timeout.check_for_timeout() {
                     self.execution_state = ExecutionState::Inactive;
@@ -686,21 +1075,29 @@ impl KotoVm {
                     self.execution_state = ExecutionState::Suspended;
                     return Ok(value);
                 }
-                Err(error) => match self.pop_call_stack_on_error(error.clone(), true) {
-                    Ok((recover_register, ip)) => {
-                        let catch_value = match error.error {
-                            ErrorKind::KotoError { thrown_value, .. } => thrown_value,
-                            _ => KValue::Str(error.to_string().into()),
-                        };
-
-                        self.set_register(recover_register, catch_value);
-                        self.set_ip(ip);
-                    }
-                    Err(error) => {
-                        self.execution_state = ExecutionState::Inactive;
-                        return Err(error);
+                Err(error) => {
+                    // Exceeding the memory limit or being interrupted ends execution, rather than
+                    // being catchable by the script.
+                    let allow_catch = !matches!(
+                        error.error,
+                        ErrorKind::MemoryLimit(_) | ErrorKind::Interrupted
+                    );
+                    match self.pop_call_stack_on_error(error.clone(), allow_catch) {
+                        Ok((recover_register, ip)) => {
+                            let catch_value = match &error.error {
+                                ErrorKind::KotoError { thrown_value, .. } => thrown_value.clone(),
+                                _ => KValue::Str(error.to_string().into()),
+                            };
+
+                            self.set_register(recover_register, catch_value);
+                            self.set_ip(ip);
+                        }
+                        Err(error) => {
+                            self.execution_state = ExecutionState::Inactive;
+                            return Err(error);
+                        }
                     }
-                },
+                }
             }
 
             self.instruction_ip = self.ip();
@@ -710,6 +1107,32 @@ impl KotoVm {
         Ok(KValue::Null)
     }
 
+    #[cold]
+    fn call_trace_hook(&self) -> TraceControl {
+        let trace_context = TraceContext {
+            vm: self,
+            ip: self.instruction_ip,
+        };
+
+        if let Some(trace_hook) = &self.context.settings.trace_hook {
+            if trace_hook(&trace_context) == TraceControl::Abort {
+                return TraceControl::Abort;
+            }
+        }
+
+        if let Some(breakpoint_callback) = &self.context.settings.breakpoint_callback {
+            let at_breakpoint = self.context.breakpoints.borrow().iter().any(|breakpoint| {
+                breakpoint.ip == self.instruction_ip
+                    && Ptr::ptr_eq(&breakpoint.chunk, &self.reader.chunk)
+            });
+            if at_breakpoint {
+                return breakpoint_callback(&trace_context);
+            }
+        }
+
+        TraceControl::Continue
+    }
+
     fn execute_instruction(&mut self, instruction: Instruction) -> Result<ControlFlow> {
         use Instruction::*;
 
@@ -1221,8 +1644,13 @@ impl KotoVm {
                 }
             }
             Str(s) => {
-                let index = signed_index_to_unsigned(index, s.len());
-                s.with_bounds(index..index + 1).map_or(Null, KValue::from)
+                let index = signed_index_to_unsigned(index, s.grapheme_count());
+                let result = s.with_grapheme_indices(index..index + 1);
+                if result.is_empty() {
+                    Null
+                } else {
+                    result.into()
+                }
             }
             Map(map) if map.contains_meta_key(&index_op) => {
                 let op = map.get_meta_value(&index_op).unwrap();
@@ -1280,11 +1708,12 @@ impl KotoVm {
                 }
             }
             Str(s) => {
-                let index = signed_index_to_unsigned(index, s.len());
+                let size = s.grapheme_count();
+                let index = signed_index_to_unsigned(index, size);
                 if is_slice_to {
-                    s.with_bounds(0..index).map_or(Null, KValue::from)
+                    s.with_grapheme_indices(0..index).into()
                 } else {
-                    s.with_bounds(index..s.len()).map_or(Null, KValue::from)
+                    s.with_grapheme_indices(index..size).into()
                 }
             }
             Map(m) if m.contains_meta_key(&index_op) => {
@@ -1341,6 +1770,7 @@ impl KotoVm {
                 variadic,
                 generator,
                 arg_is_unpacked_tuple,
+                accesses_self,
                 size,
             } => {
                 let info = KFunction {
@@ -1350,6 +1780,7 @@ impl KotoVm {
                     variadic,
                     arg_is_unpacked_tuple,
                     generator,
+                    accesses_self,
                 };
 
                 let value = if capture_count > 0 {
@@ -1423,25 +1854,15 @@ impl KotoVm {
         Ok(())
     }
 
+    // Maps with @display functions are also rendered via KMap::display, which guards against
+    // infinite recursion when a @display function displays its own map.
     fn run_display(&mut self, result: u8, value: u8) -> Result<()> {
-        use UnaryOp::Display;
-
-        match self.clone_register(value) {
-            KValue::Map(m) if m.contains_meta_key(&Display.into()) => {
-                let op = m.get_meta_value(&Display.into()).unwrap();
-                self.call_overridden_unary_op(result, value, op)
-            }
-            other => {
-                let mut display_context = DisplayContext::with_vm(self);
-                match other.display(&mut display_context) {
-                    Ok(_) => {
-                        self.set_register(result, display_context.result().into());
-                        Ok(())
-                    }
-                    Err(_) => runtime_error!("Failed to get display value"),
-                }
-            }
-        }
+        let value = self.clone_register(value);
+        let mut display_context = DisplayContext::with_vm(self);
+        value.display(&mut display_context)?;
+        let display_result = display_context.result();
+        self.set_register(result, display_result.into());
+        Ok(())
     }
 
     fn run_add(&mut self, result: u8, lhs: u8, rhs: u8) -> Result<()> {
@@ -1453,16 +1874,27 @@ impl KotoVm {
         let result_value = match (lhs_value, rhs_value) {
             (Number(a), Number(b)) => Number(a + b),
             (Str(a), Str(b)) => {
-                let result = a.to_string() + b.as_ref();
-                Str(result.into())
+                let bytes = a.len() + b.len();
+                self.track_allocation(bytes)?;
+                let result = KString::from(a.to_string() + b.as_ref());
+                self.assign_allocation(&result, bytes);
+                Str(result)
             }
             (List(a), List(b)) => {
+                let count = a.len() + b.len();
+                self.track_value_allocation(count)?;
                 let result: ValueVec = a.data().iter().chain(b.data().iter()).cloned().collect();
-                List(KList::with_data(result))
+                let result = KList::with_data(result);
+                self.assign_allocation(&result, values_size(count));
+                List(result)
             }
             (Tuple(a), Tuple(b)) => {
+                let count = a.len() + b.len();
+                self.track_value_allocation(count)?;
                 let result: Vec<_> = a.iter().chain(b.iter()).cloned().collect();
-                Tuple(result.into())
+                let result = KTuple::from(result);
+                self.assign_allocation(&result, values_size(count));
+                Tuple(result)
             }
             (Map(m), _) if m.contains_meta_key(&Add.into()) => {
                 let op = m.get_meta_value(&Add.into()).unwrap();
@@ -1470,6 +1902,8 @@ impl KotoVm {
                 return self.call_overridden_binary_op(result, lhs, rhs_value, op);
             }
             (Map(a), Map(b)) => {
+                let count = a.len() + b.len();
+                self.track_map_entry_allocation(count)?;
                 let mut data = a.data().clone();
                 data.extend(b.data().iter().map(|(k, v)| (k.clone(), v.clone())));
                 let meta = match (a.meta_map(), b.meta_map()) {
@@ -1482,9 +1916,12 @@ impl KotoVm {
                         Some(result)
                     }
                 };
-                Map(KMap::with_contents(data, meta))
+                let result = KMap::with_contents(data, meta);
+                self.assign_allocation(&result, map_entries_size(count));
+                Map(result)
             }
             (Object(o), _) => o.try_borrow()?.add(rhs_value)?,
+            (_, Object(o)) => o.try_borrow()?.add_rhs(lhs_value)?,
             _ => return binary_op_error(lhs_value, rhs_value, Add),
         };
 
@@ -1506,6 +1943,7 @@ impl KotoVm {
                 return self.call_overridden_binary_op(result, lhs, rhs_value, op);
             }
             (Object(o), _) => o.try_borrow()?.subtract(rhs_value)?,
+            (_, Object(o)) => o.try_borrow()?.subtract_rhs(lhs_value)?,
             _ => return binary_op_error(lhs_value, rhs_value, Subtract),
         };
 
@@ -1528,6 +1966,7 @@ impl KotoVm {
                 return self.call_overridden_binary_op(result, lhs, rhs_value, op);
             }
             (Object(o), _) => o.try_borrow()?.multiply(rhs_value)?,
+            (_, Object(o)) => o.try_borrow()?.multiply_rhs(lhs_value)?,
             _ => return binary_op_error(lhs_value, rhs_value, Multiply),
         };
 
@@ -1549,6 +1988,7 @@ impl KotoVm {
                 return self.call_overridden_binary_op(result, lhs, rhs_value, op);
             }
             (Object(o), _) => o.try_borrow()?.divide(rhs_value)?,
+            (_, Object(o)) => o.try_borrow()?.divide_rhs(lhs_value)?,
             _ => return binary_op_error(lhs_value, rhs_value, Divide),
         };
 
@@ -1575,6 +2015,7 @@ impl KotoVm {
                 return self.call_overridden_binary_op(result, lhs, rhs_value, op);
             }
             (Object(o), _) => o.try_borrow()?.remainder(rhs_value)?,
+            (_, Object(o)) => o.try_borrow()?.remainder_rhs(lhs_value)?,
             _ => return binary_op_error(lhs_value, rhs_value, Remainder),
         };
         self.set_register(result, result_value);
@@ -1732,6 +2173,8 @@ impl KotoVm {
                 return self.call_overridden_binary_op(result, lhs, rhs_value, op);
             }
             (Object(o), _) => o.try_borrow()?.less(rhs_value)?.into(),
+            // The operands are swapped when the object is on the right-hand side
+            (_, Object(o)) => o.try_borrow()?.greater(lhs_value)?.into(),
             _ => return binary_op_error(lhs_value, rhs_value, Less),
         };
         self.set_register(result, result_value);
@@ -1754,6 +2197,8 @@ impl KotoVm {
                 return self.call_overridden_binary_op(result, lhs, rhs_value, op);
             }
             (Object(o), _) => o.try_borrow()?.less_or_equal(rhs_value)?.into(),
+            // The operands are swapped when the object is on the right-hand side
+            (_, Object(o)) => o.try_borrow()?.greater_or_equal(lhs_value)?.into(),
             _ => return binary_op_error(lhs_value, rhs_value, LessOrEqual),
         };
         self.set_register(result, result_value);
@@ -1776,6 +2221,8 @@ impl KotoVm {
                 return self.call_overridden_binary_op(result, lhs, rhs_value, op);
             }
             (Object(o), _) => o.try_borrow()?.greater(rhs_value)?.into(),
+            // The operands are swapped when the object is on the right-hand side
+            (_, Object(o)) => o.try_borrow()?.less(lhs_value)?.into(),
             _ => return binary_op_error(lhs_value, rhs_value, Greater),
         };
         self.set_register(result, result_value);
@@ -1798,6 +2245,8 @@ impl KotoVm {
                 return self.call_overridden_binary_op(result, lhs, rhs_value, op);
             }
             (Object(o), _) => o.try_borrow()?.greater_or_equal(rhs_value)?.into(),
+            // The operands are swapped when the object is on the right-hand side
+            (_, Object(o)) => o.try_borrow()?.less_or_equal(lhs_value)?.into(),
             _ => return binary_op_error(lhs_value, rhs_value, GreaterOrEqual),
         };
         self.set_register(result, result_value);
@@ -1835,16 +2284,20 @@ impl KotoVm {
                 let rhs_value = rhs_value.clone();
                 return self.call_overridden_binary_op(result, lhs, rhs_value, op);
             }
-            (Map(map), _) => {
-                if let Map(rhs_map) = rhs_value {
-                    let a = map.clone();
-                    let b = rhs_map.clone();
-                    self.compare_value_maps(a, b)?
-                } else {
-                    false
-                }
+            (Object(o), _) => unsupported_equality(o.try_borrow()?.equal(rhs_value), false)?,
+            // The rhs map's implementation is used when the lhs doesn't implement the operator,
+            // with the lhs value passed in as the argument.
+            (_, Map(m)) if m.contains_meta_key(&Equal.into()) => {
+                let op = m.get_meta_value(&Equal.into()).unwrap();
+                let lhs_value = lhs_value.clone();
+                return self.call_overridden_binary_op(result, rhs, lhs_value, op);
             }
-            (Object(o), _) => o.try_borrow()?.equal(rhs_value)?,
+            (Map(a), Map(b)) => {
+                let a = a.clone();
+                let b = b.clone();
+                self.compare_value_maps(a, b)?
+            }
+            (_, Object(o)) => unsupported_equality(o.try_borrow()?.equal(lhs_value), false)?,
             (CaptureFunction(a), CaptureFunction(b)) => {
                 if a.info == b.info {
                     let captures_a = a.captures.clone();
@@ -1895,16 +2348,20 @@ impl KotoVm {
                 let rhs_value = rhs_value.clone();
                 return self.call_overridden_binary_op(result, lhs, rhs_value, op);
             }
-            (Map(map), _) => {
-                if let Map(rhs_map) = rhs_value {
-                    let a = map.clone();
-                    let b = rhs_map.clone();
-                    !self.compare_value_maps(a, b)?
-                } else {
-                    true
-                }
+            (Object(o), _) => unsupported_equality(o.try_borrow()?.not_equal(rhs_value), true)?,
+            // The rhs map's implementation is used when the lhs doesn't implement the operator,
+            // with the lhs value passed in as the argument.
+            (_, Map(m)) if m.contains_meta_key(&NotEqual.into()) => {
+                let op = m.get_meta_value(&NotEqual.into()).unwrap();
+                let lhs_value = lhs_value.clone();
+                return self.call_overridden_binary_op(result, rhs, lhs_value, op);
             }
-            (Object(o), _) => o.try_borrow()?.not_equal(rhs_value)?,
+            (Map(a), Map(b)) => {
+                let a = a.clone();
+                let b = b.clone();
+                !self.compare_value_maps(a, b)?
+            }
+            (_, Object(o)) => unsupported_equality(o.try_borrow()?.not_equal(lhs_value), true)?,
             (CaptureFunction(a), CaptureFunction(b)) => {
                 if a.info == b.info {
                     let captures_a = a.captures.clone();
@@ -2056,7 +2513,7 @@ impl KotoVm {
         let size = match value {
             List(l) => Some(l.len()),
             Tuple(t) => Some(t.len()),
-            Str(l) => Some(l.len()),
+            Str(s) => Some(s.grapheme_count()),
             Range(r) => r.size(),
             Map(m) if m.contains_meta_key(&size_key) => {
                 let op = m.get_meta_value(&size_key).unwrap();
@@ -2103,15 +2560,34 @@ impl KotoVm {
             return Ok(());
         }
 
-        // Attempt to compile the imported module from disk,
-        // using the current source path as the relative starting location
         let source_path = self.reader.chunk.source_path.clone();
-        let compile_result = match self
-            .context
-            .loader
-            .borrow_mut()
-            .compile_module(&import_name, source_path.as_deref())
-        {
+
+        // Is the module provided by a custom loader?
+        let loaded_module = match &self.context.settings.module_loader {
+            Some(module_loader) => module_loader(&import_name, source_path.as_deref())
+                .map_err(|error| error.with_prefix(&format!("Failed to import '{import_name}'")))?,
+            None => None,
+        };
+
+        // Compile the imported module, loading it from disk if it wasn't provided by the loader,
+        // using the current source path as the relative starting location.
+        let compile_result = match loaded_module {
+            Some(LoadedModule::Map(module)) => {
+                self.set_register(import_register, KValue::Map(module));
+                return Ok(());
+            }
+            Some(LoadedModule::Script { path, script }) => self
+                .context
+                .loader
+                .borrow_mut()
+                .compile_module_with_script(&path, &script),
+            None => self
+                .context
+                .loader
+                .borrow_mut()
+                .compile_module(&import_name, source_path.as_deref()),
+        };
+        let compile_result = match compile_result {
             Ok(result) => result,
             Err(error) => return runtime_error!("Failed to import '{import_name}': {error}"),
         };
@@ -2230,14 +2706,10 @@ impl KotoVm {
                 let mut list_data = list.data_mut();
                 let list_len = list_data.len();
                 match index_value {
-                    Number(index) => {
-                        let u_index = usize::from(index);
-                        if index >= 0.0 && u_index < list_len {
-                            list_data[u_index] = value;
-                        } else {
-                            return runtime_error!("Index '{index}' not in List");
-                        }
-                    }
+                    Number(index) => match index.as_index(list_len) {
+                        Some(index) => list_data[index] = value,
+                        None => return runtime_error!("Index '{index}' not in List"),
+                    },
                     Range(range) => {
                         for i in range.indices(list_len) {
                             list_data[i] = value.clone();
@@ -2246,24 +2718,25 @@ impl KotoVm {
                     unexpected => return type_error("index", &unexpected),
                 }
             }
+            Map(map) => {
+                let key = self.make_value_key(index_value)?;
+                if !map.data().contains_key(&key) {
+                    self.track_map_growth(&map, 1)?;
+                }
+                map.data_mut().insert(key, value);
+            }
             unexpected => return type_error("a mutable indexable value", &unexpected),
         };
 
         Ok(())
     }
 
-    fn validate_index(&self, n: KNumber, size: Option<usize>) -> Result<usize> {
-        let index = usize::from(n);
-
-        if n < 0.0 {
-            return runtime_error!("Negative indices aren't allowed ('{n}')");
-        } else if let Some(size) = size {
-            if index >= size {
-                return runtime_error!("Index out of bounds - index: {n}, size: {size}");
-            }
+    // Negative indices count backwards from the end of the indexed value
+    fn validate_index(&self, n: KNumber, size: usize) -> Result<usize> {
+        match n.as_index(size) {
+            Some(index) => Ok(index),
+            None => runtime_error!("Index out of bounds - index: {n}, size: {size}"),
         }
-
-        Ok(index)
     }
 
     fn run_index(
@@ -2280,15 +2753,16 @@ impl KotoVm {
 
         match (&value, index) {
             (List(l), Number(n)) => {
-                let index = self.validate_index(n, Some(l.len()))?;
+                let index = self.validate_index(n, l.len())?;
                 self.set_register(result_register, l.data()[index].clone());
             }
-            (List(l), Range(range)) => self.set_register(
-                result_register,
-                List(KList::from_slice(&l.data()[range.indices(l.len())])),
-            ),
+            (List(l), Range(range)) => {
+                // range.indices clamps the range to the list's length, so slicing can't fail
+                let result = l.slice(range.indices(l.len())).unwrap();
+                self.set_register(result_register, List(result))
+            }
             (Tuple(t), Number(n)) => {
-                let index = self.validate_index(n, Some(t.len()))?;
+                let index = self.validate_index(n, t.len())?;
                 self.set_register(result_register, t[index].clone());
             }
             (Tuple(t), Range(range)) => {
@@ -2297,25 +2771,21 @@ impl KotoVm {
                 self.set_register(result_register, Tuple(result))
             }
             (Str(s), Number(n)) => {
-                let index = self.validate_index(n, Some(s.len()))?;
-                let Some(result) = s.with_bounds(index..index + 1) else {
-                    return runtime_error!(
-                        "indexing with ({index}) would result in invalid UTF-8 data"
-                    );
-                };
+                let index = self.validate_index(n, s.grapheme_count())?;
+                let result = s.with_grapheme_indices(index..index + 1);
                 self.set_register(result_register, Str(result));
             }
             (Str(s), Range(range)) => {
-                let Some(result) = s.with_bounds(range.indices(s.len())) else {
-                    return runtime_error!(
-                        "indexing with ({range}) would result in invalid UTF-8 data"
-                    );
-                };
+                let result = s.with_grapheme_indices(range.indices(s.grapheme_count()));
                 self.set_register(result_register, Str(result));
             }
             (Map(m), index) => {
                 call_binary_op_or_else!(self, result_register, value_register, index, m, Index, {
-                    return runtime_error!("Unable to index {}", value.type_as_string());
+                    let key = self.make_value_key(index)?;
+                    let Some(result) = m.data().get(&key).cloned() else {
+                        return runtime_error!("Key '{key}' not found in Map");
+                    };
+                    self.set_register(result_register, result);
                 });
             }
             (Object(o), index) => {
@@ -2340,23 +2810,21 @@ impl KotoVm {
         key_register: u8,
         value_register: u8,
     ) -> Result<()> {
-        let key = ValueKey::try_from(self.clone_register(key_register))?;
+        let key = self.make_value_key(self.clone_register(key_register))?;
         let value = self.clone_register(value_register);
 
         match self.get_register(map_register) {
             KValue::Map(map) => {
+                if !map.data().contains_key(&key) {
+                    self.track_map_growth(map, 1)?;
+                }
                 map.data_mut().insert(key, value);
                 Ok(())
             }
-            KValue::Object(o) => {
-                let o = o.try_borrow()?;
-                if let Some(entries) = o.entries() {
-                    entries.insert(key, value);
-                    Ok(())
-                } else {
-                    runtime_error!("Insertion not supported for '{}'", o.type_string())
-                }
-            }
+            KValue::Object(o) => match key.value() {
+                KValue::Str(key) => o.try_borrow_mut()?.access_assign(key, &value),
+                unexpected => type_error("a String", unexpected),
+            },
             unexpected => type_error("a value that supports insertion", unexpected),
         }
     }
@@ -2522,6 +2990,10 @@ impl KotoVm {
                     result = entries.get(&key);
                 }
 
+                if result.is_none() {
+                    result = o.access_fallback(&key_string);
+                }
+
                 // Iterator fallback?
                 if result.is_none() && !matches!(o.is_iterable(), IsIterable::NotIterable) {
                     result = Some(self.get_core_op(
@@ -2564,6 +3036,56 @@ impl KotoVm {
     }
 
     fn call_external(&mut self, call_info: &CallInfo, callable: ExternalCallable) -> Result<()> {
+        if self.context.profiler.is_some() {
+            self.call_external_with_profiling(call_info, callable)
+        } else {
+            self.run_external_call(call_info, callable)
+        }
+    }
+
+    #[cold]
+    fn call_external_with_profiling(
+        &mut self,
+        call_info: &CallInfo,
+        callable: ExternalCallable,
+    ) -> Result<()> {
+        let caller_child_time = self
+            .call_stack
+            .last()
+            .and_then(|frame| frame.profile.as_ref())
+            .map(|profile| profile.child_time);
+        let start = Instant::now();
+
+        let result = self.run_external_call(call_info, callable);
+
+        let elapsed = start.elapsed();
+        if let Some(profiler) = &self.context.profiler {
+            profiler.borrow_mut().record(
+                &self.chunk(),
+                self.instruction_ip,
+                ProfileEntryKind::External,
+                elapsed,
+            );
+        }
+        // Any Koto functions called by the external function will have added their time to the
+        // caller's child time, which is already included in the external function's time.
+        if let (Some(caller_child_time), Some(caller_profile)) = (
+            caller_child_time,
+            self.call_stack
+                .last_mut()
+                .and_then(|frame| frame.profile.as_mut()),
+        ) {
+            caller_profile.child_time = caller_child_time + elapsed;
+        }
+
+        result
+    }
+
+    fn run_external_call(
+        &mut self,
+        call_info: &CallInfo,
+        callable: ExternalCallable,
+    ) -> Result<()> {
         let mut call_context = CallContext::new(self, call_info.frame_base, call_info.arg_count);
 
         let result = match callable {
@@ -2731,6 +3253,7 @@ impl KotoVm {
             call_info.frame_base,
             call_info.result_register,
         );
+        self.start_frame_profile(f.ip);
 
         Ok(())
     }
@@ -2872,6 +3395,7 @@ impl KotoVm {
     }
 
     fn run_sequence_push(&mut self, value_register: u8) -> Result<()> {
+        self.track_value_allocation(1)?;
         let value = self.clone_register(value_register);
         if let Some(builder) = self.sequence_builders.last_mut() {
             builder.push(value);
@@ -2883,7 +3407,9 @@ impl KotoVm {
 
     fn run_sequence_to_list(&mut self, register: u8) -> Result<()> {
         if let Some(result) = self.sequence_builders.pop() {
+            let count = result.len();
             let list = KList::with_data(ValueVec::from_vec(result));
+            self.assign_allocation(&list, values_size(count));
             self.set_register(register, list.into());
             Ok(())
         } else {
@@ -2893,7 +3419,10 @@ impl KotoVm {
 
     fn run_sequence_to_tuple(&mut self, register: u8) -> Result<()> {
         if let Some(result) = self.sequence_builders.pop() {
-            self.set_register(register, KTuple::from(result).into());
+            let count = result.len();
+            let tuple = KTuple::from(result);
+            self.assign_allocation(&tuple, values_size(count));
+            self.set_register(register, tuple.into());
             Ok(())
         } else {
             runtime_error!(ErrorKind::MissingSequenceBuilder)
@@ -2976,6 +3505,7 @@ impl KotoVm {
         };
 
         // Add the result to the string builder
+        self.track_allocation(result.len())?;
         if let Some(builder) = self.string_builders.last_mut() {
             builder.push_str(&result);
             Ok(())
@@ -2987,7 +3517,10 @@ impl KotoVm {
     fn run_string_finish(&mut self, register: u8) -> Result<()> {
         // Move the string builder out of its register to avoid cloning the string data
         if let Some(result) = self.string_builders.pop() {
-            self.set_register(register, result.into());
+            let bytes = result.len();
+            let string = KString::from(result);
+            self.assign_allocation(&string, bytes);
+            self.set_register(register, string.into());
             Ok(())
         } else {
             runtime_error!(ErrorKind::MissingStringBuilder)
@@ -3046,11 +3579,41 @@ impl KotoVm {
         self.set_chunk_and_ip(chunk, ip);
     }
 
+    // Starts profiling the active frame, if profiling is enabled
+    //
+    // Generator frames aren't profiled, their execution time is included in the time of the
+    // function that's iterating over the generator.
+    fn start_frame_profile(&mut self, ip: u32) {
+        if let Some(profiler) = &self.context.profiler {
+            let profile = profiler.borrow_mut().start_frame(ip);
+            self.frame_mut().profile = Some(profile);
+        }
+    }
+
+    #[cold]
+    fn end_frame_profile(&mut self, chunk: &Ptr<Chunk>, profile: &FrameProfile) {
+        if let Some(profiler) = &self.context.profiler {
+            let elapsed = profiler.borrow_mut().end_frame(chunk, profile);
+            // Exclude the popped frame's time from the calling frame's time
+            if let Some(caller_profile) = self
+                .call_stack
+                .last_mut()
+                .and_then(|frame| frame.profile.as_mut())
+            {
+                caller_profile.child_time += elapsed;
+            }
+        }
+    }
+
     fn pop_frame(&mut self, return_value: KValue) -> Result<Option<KValue>> {
         self.truncate_registers(0);
 
         match self.call_stack.pop() {
             Some(popped_frame) => {
+                if let Some(profile) = &popped_frame.profile {
+                    self.end_frame_profile(&popped_frame.chunk, profile);
+                }
+
                 if self.call_stack.is_empty() {
                     Ok(Some(return_value))
                 } else {
@@ -3201,6 +3764,23 @@ impl fmt::Debug for KotoVm {
     }
 }
 
+// An object that doesn't support equality checks with the other value's type isn't equal to it,
+// so unimplemented operator and type errors are replaced with the provided result.
+// Any other errors are propagated.
+fn unsupported_equality(result: Result<bool>, unsupported_result: bool) -> Result<bool> {
+    match result {
+        Err(error)
+            if matches!(
+                error.error,
+                ErrorKind::Unimplemented { .. } | ErrorKind::UnexpectedType { .. }
+            ) =>
+        {
+            Ok(unsupported_result)
+        }
+        result => result,
+    }
+}
+
 fn binary_op_error(lhs: &KValue, rhs: &KValue, op: BinaryOp) -> Result<()> {
     runtime_error!(ErrorKind::InvalidBinaryOp {
         lhs: lhs.clone(),
@@ -3253,6 +3833,18 @@ pub enum CallArgs<'a> {
     AsTuple(&'a [KValue]),
 }
 
+impl CallArgs<'_> {
+    /// Returns the number of arguments that will be passed to the called function
+    ///
+    /// Arguments that are bundled together with [CallArgs::AsTuple] count as a single argument.
+    pub fn arg_count(&self) -> usize {
+        match self {
+            Self::Single(_) | Self::AsTuple(_) => 1,
+            Self::Separate(args) => args.len(),
+        }
+    }
+}
+
 impl<T> From<T> for CallArgs<'static>
 where
     T: Into<KValue>,
@@ -3294,6 +3886,8 @@ struct Frame {
     pub return_register_and_ip: Option<(u8, u32)>,
     // A stack of catch points for handling errors
     pub catch_stack: Vec<(u8, u32)>, // catch error register, catch ip
+    // Timing information for the frame, available when profiling is enabled
+    pub profile: Option<FrameProfile>,
     // True if the frame should prevent execution from continuing after the frame is exited.
     // e.g.
     //   - a function is being called externally from the VM
@@ -3311,6 +3905,7 @@ impl Frame {
             return_register_and_ip: None,
             return_instruction_ip: 0,
             catch_stack: vec![],
+            profile: None,
             execution_barrier: false,
         }
     }
@@ -3330,6 +3925,45 @@ struct CallInfo {
     arg_count: u8,
 }
 
+// A breakpoint set via KotoVm::set_breakpoint
+struct Breakpoint {
+    chunk: Ptr<Chunk>,
+    ip: u32,
+}
+
+// The number of instructions to execute between checks for interruption
+const INTERRUPT_CHECK_INTERVAL: u32 = 1000;
+
+/// A handle that allows a running Koto VM to be interrupted
+///
+/// The handle is cheap to clone, and can be sent to other threads, e.g. allowing a UI thread to
+/// stop a script that's running on a worker thread.
+///
+/// When an interruption is requested, the VM will stop executing with an
+/// [Interrupted](ErrorKind::Interrupted) error, which can't be caught by scripts.
+/// The VM checks for interruption periodically between instructions, so external functions will
+/// still be able to block execution.
+///
+/// See [KotoVm::interrupt_handle].
+#[derive(Clone, Debug, Default)]
+pub struct KotoInterrupt(Arc<AtomicBool>);
+
+impl KotoInterrupt {
+    /// Requests that execution should be interrupted
+    pub fn interrupt(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if an interruption has been requested
+    pub fn is_interrupted(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn clear(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
 struct ExecutionTimeout {
     // The instant at which the deadline was last checked
     last_check: Instant,
@@ -498,7 +498,7 @@ y.next().get()
 ";
             check_script_output(
                 script,
-                tuple(&[range(0..1), range(1..3), range(3..5), range(5..14)]),
+                tuple(&[range(0..1), range(1..2), range(2..3), range(3..4)]),
             );
         }
 
@@ -507,7 +507,29 @@ y.next().get()
             let script = "
 '👍🫶🏽🫱🏼‍🫲🏾'.char_indices().to_tuple()
 ";
-            check_script_output(script, tuple(&[range(0..4), range(4..12), range(12..31)]));
+            check_script_output(script, tuple(&[range(0..1), range(1..2), range(2..3)]));
+        }
+
+        #[test]
+        fn indexing_with_output() {
+            let script = "
+s = 'héllo 👋🏽'
+s.char_indices()
+  .each |r| s[r]
+  .to_tuple()
+";
+            check_script_output(
+                script,
+                tuple(&[
+                    "h".into(),
+                    "é".into(),
+                    "l".into(),
+                    "l".into(),
+                    "o".into(),
+                    " ".into(),
+                    "👋🏽".into(),
+                ]),
+            );
         }
     }
 
@@ -36,6 +36,11 @@ mod objects {
             Ok(KValue::Null)
         }
 
+        #[koto_method(name = "double")]
+        fn koto_double(&mut self) {
+            self.x *= 2;
+        }
+
         #[koto_method]
         fn set_all_instances(ctx: MethodContext<Self>) -> Result<KValue> {
             match ctx.args {
@@ -69,6 +74,22 @@ mod objects {
         }
     }
 
+    macro_rules! arithmetic_rhs_op {
+        ($self:ident, $lhs:expr, $op:tt) => {
+            {
+                use KValue::*;
+                match $lhs {
+                    Number(n) => {
+                        Ok(Self::make_value(i64::from(n) $op $self.x))
+                    }
+                    unexpected => {
+                        type_error("a Number", unexpected)
+                    }
+                }
+            }
+        }
+    }
+
     macro_rules! assignment_op {
         ($self:ident, $rhs:expr, $op:tt) => {
             {
@@ -172,6 +193,26 @@ mod objects {
             arithmetic_op!(self, rhs, %)
         }
 
+        fn add_rhs(&self, lhs: &KValue) -> Result<KValue> {
+            arithmetic_rhs_op!(self, lhs, +)
+        }
+
+        fn subtract_rhs(&self, lhs: &KValue) -> Result<KValue> {
+            arithmetic_rhs_op!(self, lhs, -)
+        }
+
+        fn multiply_rhs(&self, lhs: &KValue) -> Result<KValue> {
+            arithmetic_rhs_op!(self, lhs, *)
+        }
+
+        fn divide_rhs(&self, lhs: &KValue) -> Result<KValue> {
+            arithmetic_rhs_op!(self, lhs, /)
+        }
+
+        fn remainder_rhs(&self, lhs: &KValue) -> Result<KValue> {
+            arithmetic_rhs_op!(self, lhs, %)
+        }
+
         fn add_assign(&mut self, rhs: &KValue) -> Result<()> {
             assignment_op!(self, rhs, +=)
         }
@@ -209,11 +250,44 @@ mod objects {
         }
 
         fn equal(&self, rhs: &KValue) -> Result<bool> {
-            comparison_op!(self, rhs, ==)
+            match rhs {
+                // Used to check that errors other than type errors are propagated
+                KValue::Bool(_) => runtime_error!("Equality with a Bool is unsupported"),
+                _ => comparison_op!(self, rhs, ==),
+            }
         }
 
         fn not_equal(&self, rhs: &KValue) -> Result<bool> {
-            comparison_op!(self, rhs, !=)
+            match rhs {
+                KValue::Bool(_) => runtime_error!("Equality with a Bool is unsupported"),
+                _ => comparison_op!(self, rhs, !=),
+            }
+        }
+
+        fn access_assign(&mut self, key: &KString, value: &KValue) -> Result<()> {
+            match (key.as_str(), value) {
+                ("x", KValue::Number(n)) => self.x = n.into(),
+                _ => {
+                    // Fall back to inserting into the shared entries, like the default impl
+                    if let Some(entries) = self.entries() {
+                        entries.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        fn access_fallback(&self, key: &KString) -> Option<KValue> {
+            let n: i64 = key.strip_prefix("plus_")?.parse().ok()?;
+            Some((self.x + n).into())
+        }
+
+        fn is_hashable(&self) -> bool {
+            true
+        }
+
+        fn hash(&self, state: &mut dyn std::hash::Hasher) {
+            state.write_i64(self.x);
         }
 
         fn is_iterable(&self) -> IsIterable {
@@ -263,6 +337,11 @@ mod objects {
             _ => runtime_error!("make_object: Expected a Number"),
         });
 
+        prelude.add_fn("make_iterator_object", |ctx| match ctx.args() {
+            [KValue::Number(x)] => Ok(TestIterator::make_object(x.into()).into()),
+            _ => runtime_error!("make_iterator_object: Expected a Number"),
+        });
+
         if let Err(e) = check_script_output_with_vm(vm, script, expected_output.into()) {
             panic!("{e}");
         }
@@ -330,6 +409,16 @@ x.as_number()
 ";
             test_object_script(script, 60);
         }
+
+        #[test]
+        fn renamed_method() {
+            let script = "
+x = make_object 21
+x.double()
+x.as_number()
+";
+            test_object_script(script, 42);
+        }
     }
 
     mod unary_op {
@@ -376,6 +465,31 @@ make_object(10)
 ";
             test_object_script(script, number_tuple(&[12, 11, 10]));
         }
+
+        #[test]
+        fn for_loop_with_break() {
+            let script = "
+result = []
+for n in make_object 10
+  if n > 13
+    break
+  result.push n
+result.to_tuple()
+";
+            test_object_script(script, number_tuple(&[11, 12, 13]));
+        }
+
+        #[test]
+        fn consumed_by_core_library() {
+            let script = "
+x = make_object 0
+x.take(3).to_list(), x.take(2).to_tuple(), x.take(4).sum()
+";
+            test_object_script(
+                script,
+                tuple(&[number_list(&[1, 2, 3]), number_tuple(&[1, 2]), 10.into()]),
+            );
+        }
     }
 
     mod binary_op {
@@ -426,6 +540,51 @@ x.as_number()
             test_object_script(script, 5);
         }
 
+        #[test]
+        fn add_rhs() {
+            let script = "
+x = 33 + (make_object 22)
+x.as_number()
+";
+            test_object_script(script, 55);
+        }
+
+        #[test]
+        fn subtract_rhs() {
+            let script = "
+x = 99 - (make_object 90)
+x.as_number()
+";
+            test_object_script(script, 9);
+        }
+
+        #[test]
+        fn multiply_rhs() {
+            let script = "
+x = 3 * (make_object 11)
+x.as_number()
+";
+            test_object_script(script, 33);
+        }
+
+        #[test]
+        fn divide_rhs() {
+            let script = "
+x = 90 / (make_object 10)
+x.as_number()
+";
+            test_object_script(script, 9);
+        }
+
+        #[test]
+        fn remainder_rhs() {
+            let script = "
+x = 45 % (make_object 10)
+x.as_number()
+";
+            test_object_script(script, 5);
+        }
+
         #[test]
         fn add_assign() {
             let script = "
@@ -578,6 +737,142 @@ x.as_number()
             let script = "null != (make_object 2)";
             test_object_script(script, true);
         }
+
+        #[test]
+        fn comparisons_with_object_on_rhs() {
+            let script = "
+x = make_object 2
+(1 < x), (3 < x), (2 <= x), (3 > x), (1 > x), (2 >= x)
+";
+            test_object_script(
+                script,
+                tuple(&[
+                    true.into(),
+                    false.into(),
+                    true.into(),
+                    true.into(),
+                    false.into(),
+                    true.into(),
+                ]),
+            );
+        }
+
+        #[test]
+        fn equality_with_object_on_rhs() {
+            let script = "
+x = make_object 2
+(2 == x), (3 == x), (2 != x), (3 != x)
+";
+            test_object_script(
+                script,
+                tuple(&[true.into(), false.into(), false.into(), true.into()]),
+            );
+        }
+
+        #[test]
+        fn equality_with_unsupported_types() {
+            let script = "
+x = make_object 2
+('2' == x), ('2' != x), ({} == x), ({} != x), ([x, 3].contains 3)
+";
+            test_object_script(
+                script,
+                tuple(&[
+                    false.into(),
+                    true.into(),
+                    false.into(),
+                    true.into(),
+                    true.into(),
+                ]),
+            );
+        }
+
+        #[test]
+        fn equality_with_unsupported_types_is_symmetric() {
+            let script = "
+x = make_object 2
+(x == '2'), (x != '2'), (x == {}), (x != {}), ([3, x].contains '2')
+";
+            test_object_script(
+                script,
+                tuple(&[
+                    false.into(),
+                    true.into(),
+                    false.into(),
+                    true.into(),
+                    false.into(),
+                ]),
+            );
+        }
+
+        #[test]
+        fn equality_with_unimplemented_operator() {
+            let script = "
+x = make_iterator_object 1
+(x == 1), (1 == x), (x != 1), (1 != x)
+";
+            test_object_script(
+                script,
+                tuple(&[false.into(), false.into(), true.into(), true.into()]),
+            );
+        }
+
+        #[test]
+        fn equality_errors_are_propagated() {
+            let check = |script: &str| {
+                test_object_script(script, "Equality with a Bool is unsupported");
+            };
+
+            for op in ["==", "!="] {
+                check(&format!(
+                    "
+x = make_object 2
+try
+  x {op} true
+catch error
+  '{{error}}'
+"
+                ));
+                check(&format!(
+                    "
+x = make_object 2
+try
+  true {op} x
+catch error
+  '{{error}}'
+"
+                ));
+            }
+        }
+
+        #[test]
+        fn lhs_object_is_checked_first() {
+            // The lhs object's operator is called, which doesn't support Iterators
+            let script = "
+try
+  (make_object 1) < (make_object 2).skip(1)
+catch error
+  '{error}'
+";
+            test_object_script(
+                script,
+                "Expected 'a TestObject or Number', but found 'Iterator'",
+            );
+        }
+
+        #[test]
+        fn unsupported_lhs_with_object_on_rhs() {
+            let script = "
+try
+  [] < make_object 1
+catch error
+  '{error}'
+";
+            test_object_script(
+                script,
+                "Expected 'a TestObject or Number', but found 'List'",
+            );
+        }
     }
 
     mod index_and_size {
@@ -666,6 +961,73 @@ y = make_object 100
         }
     }
 
+    mod access_fallback {
+        use super::*;
+
+        #[test]
+        fn generated_entry() {
+            let script = "
+x = make_object 42
+x.plus_10
+";
+            test_object_script(script, 52);
+        }
+
+        #[test]
+        fn unsupported_key() {
+            let script = "
+x = make_object 42
+try
+  x.plus_foo
+catch _
+  'not found'
+";
+            test_object_script(script, "not found");
+        }
+    }
+
+    mod access_assign {
+        use super::*;
+
+        #[test]
+        fn assign_to_instance_data() {
+            let script = "
+x = make_object 1
+y = make_object 2
+x.x = 40
+x.as_number() + y.as_number()
+";
+            test_object_script(script, 42);
+        }
+    }
+
+    mod map_keys {
+        use super::*;
+
+        #[test]
+        fn insert_and_get() {
+            let script = "
+m = {}
+m.insert (make_object 1), 'one'
+m.insert (make_object 2), 'two'
+m.get make_object 2
+";
+            test_object_script(script, "two");
+        }
+
+        #[test]
+        fn modifying_object_after_insertion_doesnt_change_key() {
+            let script = "
+m = {}
+x = make_object 1
+m.insert x, 'one'
+x += 1
+m.get make_object 1
+";
+            test_object_script(script, "one");
+        }
+    }
+
     mod copy {
         use super::*;
 
@@ -1,8 +1,9 @@
 mod runtime {
     use koto_bytecode::{CompilerSettings, Loader};
-    use koto_lexer::Span;
-    use koto_runtime::{ErrorFrame, KotoVm};
+    use koto_lexer::{Position, Span};
+    use koto_runtime::{Error, ErrorLocation, KotoVm};
     use koto_test_utils::script_instructions;
+    use std::path::Path;
 
     fn check_script_fails(script: &str) {
         check_that_script_fails(script, None);
@@ -34,8 +35,7 @@ mod runtime {
             }
             Err(e) => {
                 if let Some(expected_span) = span {
-                    let ErrorFrame { chunk, instruction } = e.trace.first().unwrap();
-                    let error_span = chunk.debug_info.get_source_span(*instruction).unwrap();
+                    let error_span = e.source_location().unwrap().span;
                     if error_span != expected_span {
                         println!("{}", script_instructions(script, vm.chunk()));
                         assert_eq!(expected_span, error_span);
@@ -527,4 +527,58 @@ x = r###########################################################################
             }
         }
     }
+
+    mod error_locations {
+        use super::*;
+
+        #[test]
+        fn runtime_error() {
+            let script = "
+x = 1
+y = x + 'hello'
+";
+            let path = Path::new("test.koto");
+            let mut loader = Loader::default();
+            let chunk = loader
+                .compile_script(script, Some(path), CompilerSettings::default())
+                .unwrap();
+
+            let error = KotoVm::default().run(chunk).unwrap_err();
+            assert!(!error.is_compile_error());
+            assert_eq!(
+                error.source_location(),
+                Some(ErrorLocation {
+                    span: Span {
+                        start: Position { line: 2, column: 4 },
+                        end: Position {
+                            line: 2,
+                            column: 15
+                        },
+                    },
+                    source: script,
+                    path: Some(path),
+                })
+            );
+        }
+
+        #[test]
+        fn compile_error() {
+            let script = "
+x = 1
+y = (x +
+";
+            let path = Path::new("test.koto");
+            let mut loader = Loader::default();
+            let error = Error::from(
+                loader
+                    .compile_script(script, Some(path), CompilerSettings::default())
+                    .unwrap_err(),
+            );
+
+            assert!(error.is_compile_error());
+            let location = error.source_location().unwrap();
+            assert_eq!(location.span.start.line, 2);
+            assert_eq!(location.path, Some(path));
+        }
+    }
 }
@@ -1,6 +1,6 @@
 mod timeout {
     use koto_bytecode::{CompilerSettings, Loader};
-    use koto_runtime::{prelude::*, Error, ErrorKind};
+    use koto_runtime::{prelude::*, ErrorKind};
     use std::time::Duration;
 
     fn test_script_with_timeout(script: &str, should_timeout: bool) {
@@ -21,10 +21,7 @@ mod timeout {
 
         if should_timeout {
             match result {
-                Err(Error {
-                    error: ErrorKind::Timeout(_),
-                    ..
-                }) => {}
+                Err(error) if matches!(error.error, ErrorKind::Timeout(_)) => {}
                 _ => {
                     panic!("Script didn't time out as expected");
                 }
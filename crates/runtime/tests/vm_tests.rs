@@ -2622,6 +2622,62 @@ x.next()
 ";
             check_script_output(script, KValue::Null);
         }
+
+        #[test]
+        fn return_from_generator_mid_iteration() {
+            let script = "
+gen = ||
+  for i in 1..10
+    if i == 4
+      return
+    yield i
+  yield -1 # Not reached
+gen().to_tuple()
+";
+            check_script_output(script, number_tuple(&[1, 2, 3]));
+        }
+
+        #[test]
+        fn generator_capturing_locals() {
+            let script = "
+offset = 100
+scale = 2
+gen = |n|
+  for i in 0..n
+    yield offset + i * scale
+offset = 0 # Captures are copied when the function is created
+gen(3).to_tuple()
+";
+            check_script_output(script, number_tuple(&[100, 102, 104]));
+        }
+
+        #[test]
+        fn nested_generators() {
+            let script = "
+inner = |n|
+  for i in 0..n
+    yield i
+outer = ||
+  for n in 1..=3
+    for i in inner n
+      yield n * 10 + i
+outer().to_tuple()
+";
+            check_script_output(script, number_tuple(&[10, 20, 21, 30, 31, 32]));
+        }
+
+        #[test]
+        fn infinite_generator_with_take() {
+            let script = "
+counter = ||
+  n = 0
+  loop
+    yield n
+    n += 1
+counter().take(4).to_list()
+";
+            check_script_output(script, number_list(&[0, 1, 2, 3]));
+        }
     }
 
     mod strings {
@@ -2665,7 +2721,8 @@ x.next()
         fn index_start_and_end() {
             check_script_output("'hello'[1..2]", "e");
             check_script_output("'hello'[1..3]", "el");
-            check_script_output("'föo'[1..3]", "ö");
+            check_script_output("'föo'[1..2]", "ö");
+            check_script_output("'föo'[1..3]", "öo");
         }
 
         #[test]
@@ -2834,6 +2891,58 @@ x.baz = x
             check_script_output(script, "{foo: 1, bar: 2, baz: {...}}");
         }
 
+        #[test]
+        fn nested_map() {
+            let script = "
+x = {foo: 1, nested: {bar: [2, 3], baz: 'x'}}
+'{x}'
+";
+            check_script_output(script, "{foo: 1, nested: {bar: [2, 3], baz: 'x'}}");
+        }
+
+        #[test]
+        fn map_and_list_referring_to_each_other() {
+            let script = "
+x = {foo: []}
+x.foo.push x
+'{x}'
+";
+            check_script_output(script, "{foo: [{...}]}");
+        }
+
+        #[test]
+        fn functions() {
+            let script = "
+f = || null
+g = |a| a
+h = |a, b...| a
+c = |a, b| a + b + g a
+'{f}, {g}, {h}, {c}'
+";
+            check_script_output(
+                script,
+                "Function(0 args), Function(1 arg), Function(2 args, variadic), Function(2 args)",
+            );
+        }
+
+        #[test]
+        fn generator() {
+            let script = "
+f = |n|
+  yield n
+'{f}'
+";
+            check_script_output(script, "Generator(1 arg)");
+        }
+
+        #[test]
+        fn native_function() {
+            let script = "
+'{koto.type}'
+";
+            check_script_output(script, "Function(native)");
+        }
+
         #[test]
         fn strings_in_tuples() {
             let script = "
@@ -3010,6 +3119,24 @@ finally
             check_script_output(script, 99);
         }
 
+        #[test]
+        fn try_catch_finally_with_error_in_catch() {
+            let script = "
+x = 0
+try
+  try
+    throw 'inner'
+  catch error
+    throw '{error}!'
+  finally
+    x += 1
+catch error
+  x += 10
+  '{error} {x}'
+";
+            check_script_output(script, "inner! 11");
+        }
+
         #[test]
         fn try_catch_nested() {
             let script = "
@@ -0,0 +1,114 @@
+use crate::Error;
+use koto_runtime::KValue;
+use serde::de::{
+    self,
+    value::{MapAccessDeserializer, MapDeserializer, SeqDeserializer},
+    DeserializeOwned, IntoDeserializer, Visitor,
+};
+
+/// Deserializes an instance of `T` from a Koto value
+///
+/// Maps are deserialized as structs or maps, lists and tuples are deserialized as sequences,
+/// and `null` is deserialized as `None` for optional values.
+///
+/// Enum variants can be deserialized from strings for unit variants, or from maps containing a
+/// single entry, with the variant name as the key.
+pub fn from_koto_value<T: DeserializeOwned>(value: &KValue) -> Result<T, Error> {
+    T::deserialize(ValueDeserializer(value.clone()))
+}
+
+struct ValueDeserializer(KValue);
+
+impl<'de> IntoDeserializer<'de, Error> for ValueDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl ValueDeserializer {
+    fn map_deserializer(
+        map: &koto_runtime::KMap,
+    ) -> MapDeserializer<'static, impl Iterator<Item = (String, Self)>, Error> {
+        let entries = map
+            .data()
+            .iter()
+            .map(|(key, value)| (key.to_string(), Self(value.clone())))
+            .collect::<Vec<_>>();
+        MapDeserializer::new(entries.into_iter())
+    }
+}
+
+fn visit_seq<'de, V: Visitor<'de>>(values: &[KValue], visitor: V) -> Result<V::Value, Error> {
+    let mut seq = SeqDeserializer::new(values.iter().cloned().map(ValueDeserializer));
+    let result = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+    Ok(result)
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            KValue::Null => visitor.visit_unit(),
+            KValue::Bool(b) => visitor.visit_bool(b),
+            KValue::Number(n) => {
+                if n.is_f64() {
+                    visitor.visit_f64(n.into())
+                } else {
+                    visitor.visit_i64(n.into())
+                }
+            }
+            KValue::Str(s) => visitor.visit_str(&s),
+            KValue::List(l) => visit_seq(&l.data(), visitor),
+            KValue::Tuple(t) => visit_seq(&t, visitor),
+            KValue::Map(m) => {
+                let mut map = Self::map_deserializer(&m);
+                let result = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(result)
+            }
+            other => Err(Error::unsupported_type(&other.type_as_string())),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            KValue::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.0 {
+            KValue::Str(s) => visitor.visit_enum(s.as_str().into_deserializer()),
+            KValue::Map(m) if m.len() == 1 => {
+                visitor.visit_enum(MapAccessDeserializer::new(Self::map_deserializer(&m)))
+            }
+            other => Err(de::Error::invalid_type(
+                de::Unexpected::Other(&other.type_as_string()),
+                &"a String or a Map with a single entry",
+            )),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
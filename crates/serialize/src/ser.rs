@@ -0,0 +1,350 @@
+use crate::Error;
+use koto_runtime::{KList, KMap, KTuple, KValue, ValueKey, ValueMap};
+use serde::ser::{self, Serialize};
+
+/// Serializes a Rust value as a Koto value
+///
+/// Structs and maps are serialized as Koto maps, sequences are serialized as lists,
+/// and tuples are serialized as Koto tuples. `None` and unit values are serialized as `null`.
+///
+/// Unit enum variants are serialized as strings, and other variants are serialized as maps
+/// containing a single entry, with the variant name as the key.
+pub fn to_koto_value<T: Serialize + ?Sized>(value: &T) -> Result<KValue, Error> {
+    value.serialize(ValueSerializer)
+}
+
+struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = KValue;
+    type Error = Error;
+
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = VariantSerializer<SeqSerializer>;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = MapSerializer;
+    type SerializeStructVariant = VariantSerializer<MapSerializer>;
+
+    fn serialize_bool(self, v: bool) -> Result<KValue, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<KValue, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<KValue, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<KValue, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<KValue, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<KValue, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<KValue, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<KValue, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<KValue, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<KValue, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<KValue, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_char(self, v: char) -> Result<KValue, Error> {
+        Ok(v.to_string().into())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<KValue, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<KValue, Error> {
+        Ok(KList::from_iter(v.iter().copied()).into())
+    }
+
+    fn serialize_none(self) -> Result<KValue, Error> {
+        Ok(KValue::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<KValue, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<KValue, Error> {
+        Ok(KValue::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<KValue, Error> {
+        Ok(KValue::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<KValue, Error> {
+        Ok(variant.into())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<KValue, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<KValue, Error> {
+        Ok(KMap::from_iter([(variant, to_koto_value(value)?)]).into())
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer::new(len, false))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer::new(Some(len), true))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer::new(Some(len), true))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<VariantSerializer<SeqSerializer>, Error> {
+        Ok(VariantSerializer {
+            variant,
+            inner: SeqSerializer::new(Some(len), true),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<MapSerializer, Error> {
+        Ok(MapSerializer::new(len))
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<MapSerializer, Error> {
+        Ok(MapSerializer::new(Some(len)))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<VariantSerializer<MapSerializer>, Error> {
+        Ok(VariantSerializer {
+            variant,
+            inner: MapSerializer::new(Some(len)),
+        })
+    }
+}
+
+// Serializes sequences as lists, and tuples as Koto tuples
+struct SeqSerializer {
+    values: Vec<KValue>,
+    is_tuple: bool,
+}
+
+impl SeqSerializer {
+    fn new(len: Option<usize>, is_tuple: bool) -> Self {
+        Self {
+            values: Vec::with_capacity(len.unwrap_or_default()),
+            is_tuple,
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.values.push(to_koto_value(value)?);
+        Ok(())
+    }
+
+    fn finish(self) -> KValue {
+        if self.is_tuple {
+            KTuple::from(self.values).into()
+        } else {
+            KList::from_iter(self.values).into()
+        }
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = KValue;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<KValue, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = KValue;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<KValue, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = KValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<KValue, Error> {
+        Ok(self.finish())
+    }
+}
+
+struct MapSerializer {
+    map: ValueMap,
+    next_key: Option<ValueKey>,
+}
+
+impl MapSerializer {
+    fn new(len: Option<usize>) -> Self {
+        Self {
+            map: ValueMap::with_capacity(len.unwrap_or_default()),
+            next_key: None,
+        }
+    }
+
+    fn finish(self) -> KValue {
+        KMap::with_data(self.map).into()
+    }
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = KValue;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        let key = to_koto_value(key)?;
+        let type_name = key.type_as_string();
+        let key = ValueKey::try_from(key).map_err(|_| {
+            <Error as ser::Error>::custom(format!("'{type_name}' can't be used as a map key"))
+        })?;
+        self.next_key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self.next_key.take().ok_or_else(|| {
+            <Error as ser::Error>::custom("serialize_value called before serialize_key")
+        })?;
+        self.map.insert(key, to_koto_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<KValue, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStruct for MapSerializer {
+    type Ok = KValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.map.insert(key.into(), to_koto_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<KValue, Error> {
+        Ok(self.finish())
+    }
+}
+
+// Wraps the serialized variant's data in a map, with the variant's name as the key
+struct VariantSerializer<T> {
+    variant: &'static str,
+    inner: T,
+}
+
+impl<T> VariantSerializer<T> {
+    fn finish(variant: &'static str, value: KValue) -> KValue {
+        KMap::from_iter([(variant, value)]).into()
+    }
+}
+
+impl ser::SerializeTupleVariant for VariantSerializer<SeqSerializer> {
+    type Ok = KValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.inner.push(value)
+    }
+
+    fn end(self) -> Result<KValue, Error> {
+        Ok(Self::finish(self.variant, self.inner.finish()))
+    }
+}
+
+impl ser::SerializeStructVariant for VariantSerializer<MapSerializer> {
+    type Ok = KValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<KValue, Error> {
+        Ok(Self::finish(self.variant, self.inner.finish()))
+    }
+}
@@ -0,0 +1,431 @@
+use koto_runtime::{KList, KMap, KNumber, KTuple, KValue, ValueKey, ValueMap};
+use serde::{
+    de::{
+        self, Deserialize, Deserializer, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor,
+    },
+    ser::{self, Serialize, SerializeMap, SerializeSeq, Serializer},
+};
+use std::fmt;
+
+/// How values that can't be represented with Serde should be serialized
+///
+/// Functions, objects, and other values that don't have a Serde equivalent are considered to be
+/// unsupported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnsupportedValues {
+    /// Unsupported values are serialized as `null`
+    #[default]
+    Null,
+    /// Unsupported values are serialized as a map with a single `unsupported` entry,
+    /// containing the name of the value's type
+    ///
+    /// e.g. a function will be serialized as `{unsupported: "Function"}`.
+    Placeholder,
+    /// Serialization will fail with an error when an unsupported value is encountered
+    Error,
+}
+
+/// A newtype that allows us to implement support for Serde serialization
+///
+/// Null, Bool, Number, String, List, Tuple, and Map values are supported, see
+/// [UnsupportedValues] for how other values are handled.
+///
+/// Human-readable formats (e.g. JSON) will receive the value's data directly, with map keys
+/// being converted into strings. Other formats (e.g. bincode) will receive values as tagged
+/// variants that preserve the distinction between integers and floats, lists and tuples, and
+/// the types of map keys, allowing values to be faithfully deserialized with
+/// [DeserializableValue].
+pub struct SerializableValue<'a> {
+    value: &'a KValue,
+    unsupported: UnsupportedValues,
+}
+
+impl<'a> SerializableValue<'a> {
+    /// Prepares the value for serialization
+    pub fn new(value: &'a KValue) -> Self {
+        Self {
+            value,
+            unsupported: UnsupportedValues::default(),
+        }
+    }
+
+    /// Sets how unsupported values should be serialized
+    pub fn unsupported_values(self, unsupported: UnsupportedValues) -> Self {
+        Self {
+            unsupported,
+            ..self
+        }
+    }
+
+    fn nested<'b>(&self, value: &'b KValue) -> SerializableValue<'b> {
+        SerializableValue {
+            value,
+            unsupported: self.unsupported,
+        }
+    }
+}
+
+// The name and variants used when serializing with non-human-readable formats
+const VALUE_NAME: &str = "KValue";
+const VALUE_VARIANTS: &[&str] = &[
+    "Null", "Bool", "Int", "Float", "Str", "List", "Tuple", "Map",
+];
+
+impl Serialize for SerializableValue<'_> {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if s.is_human_readable() {
+            self.serialize_untagged(s)
+        } else {
+            self.serialize_tagged(s)
+        }
+    }
+}
+
+impl SerializableValue<'_> {
+    fn serialize_untagged<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        match self.value {
+            KValue::Null => s.serialize_unit(),
+            KValue::Bool(b) => s.serialize_bool(*b),
+            KValue::Number(n) => {
+                if n.is_f64() {
+                    s.serialize_f64(f64::from(n))
+                } else {
+                    s.serialize_i64(i64::from(n))
+                }
+            }
+            KValue::List(l) => self.serialize_seq(&l.data(), s),
+            KValue::Tuple(t) => self.serialize_seq(t, s),
+            KValue::Map(m) => {
+                let mut seq = s.serialize_map(Some(m.len()))?;
+                for (key, value) in m.data().iter() {
+                    seq.serialize_entry(&key.to_string(), &self.nested(value))?;
+                }
+                seq.end()
+            }
+            KValue::Str(string) => s.serialize_str(string),
+            unsupported => self.serialize_unsupported(unsupported, s),
+        }
+    }
+
+    fn serialize_tagged<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        match self.value {
+            KValue::Null => s.serialize_unit_variant(VALUE_NAME, 0, "Null"),
+            KValue::Bool(b) => s.serialize_newtype_variant(VALUE_NAME, 1, "Bool", b),
+            KValue::Number(KNumber::I64(n)) => s.serialize_newtype_variant(VALUE_NAME, 2, "Int", n),
+            KValue::Number(KNumber::F64(n)) => {
+                s.serialize_newtype_variant(VALUE_NAME, 3, "Float", n)
+            }
+            KValue::Str(string) => {
+                s.serialize_newtype_variant(VALUE_NAME, 4, "Str", string.as_str())
+            }
+            KValue::List(l) => {
+                let data = l.data();
+                let seq = SerializableSeq(self, &data);
+                s.serialize_newtype_variant(VALUE_NAME, 5, "List", &seq)
+            }
+            KValue::Tuple(t) => {
+                let seq = SerializableSeq(self, t);
+                s.serialize_newtype_variant(VALUE_NAME, 6, "Tuple", &seq)
+            }
+            KValue::Map(m) => {
+                let data = m.data();
+                let map = SerializableMap(self, &data);
+                s.serialize_newtype_variant(VALUE_NAME, 7, "Map", &map)
+            }
+            unsupported => self.serialize_unsupported(unsupported, s),
+        }
+    }
+
+    fn serialize_seq<S: Serializer>(&self, values: &[KValue], s: S) -> Result<S::Ok, S::Error> {
+        let mut seq = s.serialize_seq(Some(values.len()))?;
+        for element in values {
+            seq.serialize_element(&self.nested(element))?;
+        }
+        seq.end()
+    }
+
+    fn serialize_unsupported<S: Serializer>(
+        &self,
+        value: &KValue,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let type_name = value.type_as_string();
+        match self.unsupported {
+            UnsupportedValues::Null => self.nested(&KValue::Null).serialize(s),
+            UnsupportedValues::Placeholder => {
+                let placeholder = KMap::from_iter([("unsupported", type_name)]).into();
+                self.nested(&placeholder).serialize(s)
+            }
+            UnsupportedValues::Error => Err(ser::Error::custom(format!(
+                "unsupported value type '{type_name}'"
+            ))),
+        }
+    }
+}
+
+// Serializes the contents of a List or Tuple for the tagged representation
+struct SerializableSeq<'a>(&'a SerializableValue<'a>, &'a [KValue]);
+
+impl Serialize for SerializableSeq<'_> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize_seq(self.1, s)
+    }
+}
+
+// Serializes the contents of a Map for the tagged representation, with keys serialized as values
+struct SerializableMap<'a>(&'a SerializableValue<'a>, &'a ValueMap);
+
+impl Serialize for SerializableMap<'_> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(self.1.len()))?;
+        for (key, value) in self.1.iter() {
+            map.serialize_entry(&self.0.nested(key.value()), &self.0.nested(value))?;
+        }
+        map.end()
+    }
+}
+
+/// A newtype that allows us to implement support for Serde deserialization
+///
+/// Integers are deserialized as integer Numbers, unless they're out of the range of `i64`, in
+/// which case they're deserialized as floats. Floats are always deserialized as float Numbers,
+/// even if they have no fractional part.
+///
+/// Sequences are deserialized as Lists, and maps are deserialized as Maps. Values that were
+/// serialized with a non-human-readable format via [SerializableValue] are deserialized with
+/// their original types.
+pub struct DeserializableValue(pub KValue);
+
+impl From<DeserializableValue> for KValue {
+    fn from(value: DeserializableValue) -> Self {
+        value.0
+    }
+}
+
+impl<'de> Deserialize<'de> for DeserializableValue {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if d.is_human_readable() {
+            d.deserialize_any(ValueVisitor)
+        } else {
+            d.deserialize_enum(VALUE_NAME, VALUE_VARIANTS, ValueVisitor)
+        }
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = DeserializableValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Koto value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(DeserializableValue(KValue::Null))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.visit_unit()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        DeserializableValue::deserialize(d)
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(DeserializableValue(v.into()))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(DeserializableValue(v.into()))
+    }
+
+    fn visit_i128<E: de::Error>(self, v: i128) -> Result<Self::Value, E> {
+        match i64::try_from(v) {
+            Ok(v) => self.visit_i64(v),
+            Err(_) => self.visit_f64(v as f64),
+        }
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        self.visit_i128(v.into())
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<Self::Value, E> {
+        match i64::try_from(v) {
+            Ok(v) => self.visit_i64(v),
+            Err(_) => self.visit_f64(v as f64),
+        }
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(DeserializableValue(v.into()))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(DeserializableValue(v.into()))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        let values = SeqVisitor.visit_seq(seq)?;
+        Ok(DeserializableValue(KList::from_iter(values).into()))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        let data = MapVisitor.visit_map(map)?;
+        Ok(DeserializableValue(KMap::with_data(data).into()))
+    }
+
+    // Values serialized with the tagged representation
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        let (tag, variant) = data.variant::<ValueTag>()?;
+        let result = match tag {
+            ValueTag::Null => {
+                variant.unit_variant()?;
+                KValue::Null
+            }
+            ValueTag::Bool => variant.newtype_variant::<bool>()?.into(),
+            ValueTag::Int => variant.newtype_variant::<i64>()?.into(),
+            ValueTag::Float => variant.newtype_variant::<f64>()?.into(),
+            ValueTag::Str => variant.newtype_variant::<String>()?.into(),
+            ValueTag::List => {
+                let values = variant.newtype_variant_seed(SeqVisitor)?;
+                KList::from_iter(values).into()
+            }
+            ValueTag::Tuple => {
+                let values = variant.newtype_variant_seed(SeqVisitor)?;
+                KTuple::from(values).into()
+            }
+            ValueTag::Map => {
+                let data = variant.newtype_variant_seed(MapVisitor)?;
+                KMap::with_data(data).into()
+            }
+        };
+        Ok(DeserializableValue(result))
+    }
+}
+
+// Deserializes a sequence of values
+struct SeqVisitor;
+
+impl<'de> de::DeserializeSeed<'de> for SeqVisitor {
+    type Value = Vec<KValue>;
+
+    fn deserialize<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        d.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for SeqVisitor {
+    type Value = Vec<KValue>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a sequence of Koto values")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(DeserializableValue(value)) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+// Deserializes a map's entries, with keys that can be of any hashable type
+struct MapVisitor;
+
+impl<'de> de::DeserializeSeed<'de> for MapVisitor {
+    type Value = ValueMap;
+
+    fn deserialize<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        d.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for MapVisitor {
+    type Value = ValueMap;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map of Koto values")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut data = ValueMap::with_capacity(map.size_hint().unwrap_or_default());
+        while let Some((DeserializableValue(key), DeserializableValue(value))) = map.next_entry()? {
+            let type_name = key.type_as_string();
+            let key = ValueKey::try_from(key).map_err(|_| {
+                de::Error::custom(format!("'{type_name}' can't be used as a map key"))
+            })?;
+            data.insert(key, value);
+        }
+        Ok(data)
+    }
+}
+
+// The variants of the tagged representation, identified either by index or by name
+enum ValueTag {
+    Null,
+    Bool,
+    Int,
+    Float,
+    Str,
+    List,
+    Tuple,
+    Map,
+}
+
+impl ValueTag {
+    fn from_index(index: u64) -> Option<Self> {
+        use ValueTag::*;
+        let result = match index {
+            0 => Null,
+            1 => Bool,
+            2 => Int,
+            3 => Float,
+            4 => Str,
+            5 => List,
+            6 => Tuple,
+            7 => Map,
+            _ => return None,
+        };
+        Some(result)
+    }
+}
+
+impl<'de> Deserialize<'de> for ValueTag {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_identifier(ValueTagVisitor)
+    }
+}
+
+struct ValueTagVisitor;
+
+impl Visitor<'_> for ValueTagVisitor {
+    type Value = ValueTag;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Koto value variant")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        ValueTag::from_index(v).ok_or_else(|| {
+            de::Error::invalid_value(de::Unexpected::Unsigned(v), &"a variant index 0 <= i < 8")
+        })
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        VALUE_VARIANTS
+            .iter()
+            .position(|variant| *variant == v)
+            .and_then(|index| ValueTag::from_index(index as u64))
+            .ok_or_else(|| de::Error::unknown_variant(v, VALUE_VARIANTS))
+    }
+}
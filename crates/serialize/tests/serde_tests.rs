@@ -0,0 +1,262 @@
+use koto_runtime::{KList, KMap, KRange, KValue};
+use koto_serialize::{from_koto_value, to_koto_value};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Config {
+    name: String,
+    size: Size,
+    scale: f64,
+    tags: Vec<String>,
+    limit: Option<u32>,
+    mode: Mode,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Size {
+    width: i64,
+    height: i64,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+enum Mode {
+    Fast,
+    Custom { iterations: u8 },
+}
+
+fn make_config(limit: Option<u32>, mode: Mode) -> Config {
+    Config {
+        name: "test".into(),
+        size: Size {
+            width: 640,
+            height: 480,
+        },
+        scale: 1.5,
+        tags: vec!["a".into(), "b".into()],
+        limit,
+        mode,
+    }
+}
+
+fn round_trip(config: Config) {
+    let value = to_koto_value(&config).unwrap();
+    let result: Config = from_koto_value(&value).unwrap();
+    assert_eq!(result, config);
+}
+
+#[test]
+fn round_trip_struct() {
+    round_trip(make_config(Some(42), Mode::Fast));
+}
+
+#[test]
+fn round_trip_struct_with_none_and_struct_variant() {
+    round_trip(make_config(None, Mode::Custom { iterations: 9 }));
+}
+
+#[test]
+fn struct_serialized_as_map() {
+    let value = to_koto_value(&make_config(None, Mode::Fast)).unwrap();
+    let KValue::Map(map) = value else {
+        panic!("expected a map");
+    };
+
+    assert!(matches!(map.get("name"), Some(KValue::Str(s)) if s == "test"));
+    assert!(matches!(map.get("limit"), Some(KValue::Null)));
+    assert!(matches!(map.get("mode"), Some(KValue::Str(s)) if s == "Fast"));
+    assert!(matches!(map.get("tags"), Some(KValue::List(l)) if l.len() == 2));
+}
+
+#[test]
+fn deserialize_from_koto_values() {
+    let size = KMap::from_iter([("width", 10), ("height", 20)]);
+    let value = KValue::from(KMap::from_iter([
+        ("name", KValue::from("koto")),
+        ("size", size.into()),
+        // Integers can be deserialized as floats
+        ("scale", 2.into()),
+        ("tags", KList::from_iter(["x"]).into()),
+        ("mode", "Fast".into()),
+    ]));
+
+    let config: Config = from_koto_value(&value).unwrap();
+    assert_eq!(
+        config.size,
+        Size {
+            width: 10,
+            height: 20
+        }
+    );
+    assert_eq!(config.scale, 2.0);
+    assert_eq!(config.tags, ["x"]);
+    // Missing optional fields are deserialized as None
+    assert_eq!(config.limit, None);
+}
+
+#[test]
+fn missing_field() {
+    let value = KValue::from(KMap::from_iter([("width", 10)]));
+    let error = from_koto_value::<Size>(&value).unwrap_err();
+    assert_eq!(error.to_string(), "missing field `height`");
+}
+
+#[test]
+fn field_with_wrong_type() {
+    let value = KValue::from(KMap::from_iter([
+        ("width", KValue::from(10)),
+        ("height", "tall".into()),
+    ]));
+    let error = from_koto_value::<Size>(&value).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        r#"invalid type: string "tall", expected i64"#
+    );
+}
+
+#[test]
+fn unsupported_value() {
+    let value = KValue::from(KRange::from(0..10));
+    let error = from_koto_value::<Size>(&value).unwrap_err();
+    assert_eq!(error.to_string(), "unsupported value type 'Range'");
+}
+
+mod value_serialization {
+    use super::*;
+    use koto_runtime::{KTuple, KotoVm, ValueKey, ValueMap};
+    use koto_serialize::{DeserializableValue, SerializableValue, UnsupportedValues};
+
+    fn display(value: &KValue) -> String {
+        KotoVm::default().value_to_string(value).unwrap()
+    }
+
+    fn nested_value() -> KValue {
+        let inner = KMap::from_iter([
+            ("x", KValue::from(1)),
+            ("y", 2.5.into()),
+            ("z", KValue::Null),
+        ]);
+        let list = KList::from_iter([
+            KValue::from(true),
+            (-42).into(),
+            3.0.into(),
+            "hello".into(),
+            inner.into(),
+        ]);
+        KMap::from_iter([
+            ("name", KValue::from("koto")),
+            ("items", list.into()),
+            ("empty", KList::default().into()),
+        ])
+        .into()
+    }
+
+    fn json_round_trip(value: &KValue) -> KValue {
+        let json = serde_json::to_string(&SerializableValue::new(value)).unwrap();
+        serde_json::from_str::<DeserializableValue>(&json)
+            .unwrap()
+            .into()
+    }
+
+    fn bincode_round_trip(value: &KValue) -> KValue {
+        let bytes = bincode::serialize(&SerializableValue::new(value)).unwrap();
+        bincode::deserialize::<DeserializableValue>(&bytes)
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn json_nested_round_trip() {
+        let value = nested_value();
+        assert_eq!(display(&json_round_trip(&value)), display(&value));
+    }
+
+    #[test]
+    fn bincode_nested_round_trip() {
+        let value = nested_value();
+        assert_eq!(display(&bincode_round_trip(&value)), display(&value));
+    }
+
+    #[test]
+    fn json_numbers() {
+        let value: KValue = serde_json::from_str::<DeserializableValue>("[1, 1.0, 1e3, -7]")
+            .unwrap()
+            .into();
+        assert_eq!(display(&value), "[1, 1.0, 1000.0, -7]");
+
+        // Integers that don't fit in an i64 are deserialized as floats
+        let value: KValue = serde_json::from_str::<DeserializableValue>("18446744073709551615")
+            .unwrap()
+            .into();
+        assert!(matches!(value, KValue::Number(n) if n.is_f64()));
+    }
+
+    #[test]
+    fn json_tuples_and_map_keys() {
+        let mut map = ValueMap::default();
+        map.insert(
+            ValueKey::from(1),
+            KTuple::from(vec![1.into(), 2.into()]).into(),
+        );
+        let value = KMap::with_data(map).into();
+
+        // Tuples are serialized as sequences, and map keys as strings
+        assert_eq!(
+            serde_json::to_string(&SerializableValue::new(&value)).unwrap(),
+            r#"{"1":[1,2]}"#
+        );
+        assert_eq!(display(&json_round_trip(&value)), "{'1': [1, 2]}");
+    }
+
+    #[test]
+    fn bincode_tuples_and_map_keys() {
+        let mut map = ValueMap::default();
+        map.insert(
+            ValueKey::from(1),
+            KTuple::from(vec![1.into(), 2.0.into()]).into(),
+        );
+        map.insert(ValueKey::from(2.5), "x".into());
+        map.insert(
+            ValueKey::try_from(KValue::from(KTuple::from(vec![true.into(), "y".into()]))).unwrap(),
+            KValue::Null,
+        );
+        let value = KMap::with_data(map).into();
+
+        // The tagged binary representation preserves tuples and the types of map keys
+        let result = bincode_round_trip(&value);
+        assert_eq!(
+            display(&result),
+            "{1: (1, 2.0), 2.5: 'x', (true, 'y'): null}"
+        );
+    }
+
+    #[test]
+    fn unsupported_values() {
+        let value = KValue::from(KList::from_iter([
+            KValue::from(1),
+            KRange::from(0..10).into(),
+        ]));
+
+        let serialize = |unsupported| {
+            serde_json::to_string(&SerializableValue::new(&value).unsupported_values(unsupported))
+        };
+
+        assert_eq!(serialize(UnsupportedValues::Null).unwrap(), "[1,null]");
+        assert_eq!(
+            serialize(UnsupportedValues::Placeholder).unwrap(),
+            r#"[1,{"unsupported":"Range"}]"#
+        );
+        assert_eq!(
+            serialize(UnsupportedValues::Error).unwrap_err().to_string(),
+            "unsupported value type 'Range'"
+        );
+
+        let placeholder = bincode::serialize(
+            &SerializableValue::new(&value).unsupported_values(UnsupportedValues::Placeholder),
+        )
+        .unwrap();
+        let result: KValue = bincode::deserialize::<DeserializableValue>(&placeholder)
+            .unwrap()
+            .into();
+        assert_eq!(display(&result), "[1, {unsupported: 'Range'}]");
+    }
+}
@@ -0,0 +1,212 @@
+use crate::{hash_components, Vec3};
+use koto_runtime::{derive::*, prelude::*, Result};
+use std::{fmt, hash::Hasher};
+
+type Inner = nannou_core::geom::DVec3;
+
+/// A 3D axis-aligned bounding box
+#[derive(Copy, Clone, PartialEq, KotoCopy, KotoType)]
+#[koto(use_copy)]
+pub struct Aabb {
+    min: Inner,
+    max: Inner,
+}
+
+#[koto_impl(runtime = koto_runtime)]
+impl Aabb {
+    /// Initializes a box with corners located at the two given points
+    ///
+    /// The points can be provided in any order, the resulting box will always have a
+    /// non-negative size.
+    pub fn new(a: Vec3, b: Vec3) -> Self {
+        let (a, b) = (a.inner(), b.inner());
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    /// Initializes a box with the given center position and size
+    ///
+    /// Negative sizes are normalized, producing a box with a non-negative size.
+    pub fn from_center_size(center: Vec3, size: Vec3) -> Self {
+        let (center, half_size) = (center.inner(), size.inner() / 2.0);
+        Self::new((center - half_size).into(), (center + half_size).into())
+    }
+
+    pub fn min(&self) -> Vec3 {
+        self.min.into()
+    }
+
+    pub fn max(&self) -> Vec3 {
+        self.max.into()
+    }
+
+    pub fn center(&self) -> Vec3 {
+        ((self.min + self.max) / 2.0).into()
+    }
+
+    pub fn size(&self) -> Vec3 {
+        (self.max - self.min).into()
+    }
+
+    /// Returns true if the point is within the box's bounds
+    ///
+    /// Points located on the box's faces are considered to be contained by the box.
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        let p = point.inner();
+        p.cmpge(self.min).all() && p.cmple(self.max).all()
+    }
+
+    /// Returns true if `other` is entirely within the box's bounds
+    ///
+    /// Faces are inclusive, so a box contains itself.
+    pub fn contains_aabb(&self, other: &Aabb) -> bool {
+        other.min.cmpge(self.min).all() && other.max.cmple(self.max).all()
+    }
+
+    /// Returns true if the boxes overlap
+    ///
+    /// Boxes that touch at their faces are considered to be intersecting.
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+    }
+
+    /// Returns the smallest box that contains both boxes
+    pub fn union(&self, other: &Aabb) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// Returns a copy of the box with each face moved outwards by the given amounts
+    ///
+    /// Negative amounts shrink the box. If an amount shrinks the box by more than its size on
+    /// that axis, then the box collapses to a size of zero on that axis, centered on the box's
+    /// original center.
+    pub fn expand(&self, amount: Vec3) -> Self {
+        let size = (self.size().inner() + amount.inner() * 2.0).max(Inner::ZERO);
+        Self::from_center_size(self.center(), size.into())
+    }
+
+    #[koto_method(name = "center")]
+    fn koto_center(&self) -> KValue {
+        self.center().into()
+    }
+
+    #[koto_method]
+    fn contains(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(p)] if p.is_a::<Vec3>() => {
+                let p = p.cast::<Vec3>().unwrap();
+                Ok(self.contains_point(*p).into())
+            }
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.contains_aabb(&other).into())
+            }
+            unexpected => type_error_with_slice("a Vec3 or Aabb", unexpected),
+        }
+    }
+
+    #[koto_method(name = "expand")]
+    fn koto_expand(&self, args: &[KValue]) -> Result<KValue> {
+        use KValue::{Number, Object};
+
+        let amount = match args {
+            [Number(n)] => Vec3::new(n.into(), n.into(), n.into()),
+            [Number(x), Number(y), Number(z)] => Vec3::new(x.into(), y.into(), z.into()),
+            [Object(amount)] if amount.is_a::<Vec3>() => *amount.cast::<Vec3>().unwrap(),
+            unexpected => {
+                return type_error_with_slice("a Number, three Numbers, or a Vec3", unexpected)
+            }
+        };
+
+        Ok(self.expand(amount).into())
+    }
+
+    #[koto_method(name = "intersects")]
+    fn koto_intersects(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.intersects(&other).into())
+            }
+            unexpected => type_error_with_slice("an Aabb", unexpected),
+        }
+    }
+
+    #[koto_method(name = "max")]
+    fn koto_max(&self) -> KValue {
+        self.max().into()
+    }
+
+    #[koto_method(name = "min")]
+    fn koto_min(&self) -> KValue {
+        self.min().into()
+    }
+
+    #[koto_method(name = "size")]
+    fn koto_size(&self) -> KValue {
+        self.size().into()
+    }
+
+    #[koto_method(name = "union")]
+    fn koto_union(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.union(&other).into())
+            }
+            unexpected => type_error_with_slice("an Aabb", unexpected),
+        }
+    }
+}
+
+impl KotoObject for Aabb {
+    fn display(&self, ctx: &mut DisplayContext) -> Result<()> {
+        ctx.append(self.to_string());
+        Ok(())
+    }
+
+    fn equal(&self, rhs: &KValue) -> Result<bool> {
+        geometry_comparison_op!(self, rhs, ==)
+    }
+
+    fn not_equal(&self, rhs: &KValue) -> Result<bool> {
+        geometry_comparison_op!(self, rhs, !=)
+    }
+
+    // Bounding boxes with NaN components can't be used as map keys, given that NaN isn't equal to itself
+    fn is_hashable(&self) -> bool {
+        !(self.min.is_nan() || self.max.is_nan())
+    }
+
+    fn hash(&self, state: &mut dyn Hasher) {
+        hash_components(
+            &[
+                self.min.x, self.min.y, self.min.z, self.max.x, self.max.y, self.max.z,
+            ],
+            state,
+        )
+    }
+}
+
+impl From<(Vec3, Vec3)> for Aabb {
+    fn from((a, b): (Vec3, Vec3)) -> Self {
+        Self::new(a, b)
+    }
+}
+
+impl From<Aabb> for KValue {
+    fn from(aabb: Aabb) -> Self {
+        KObject::from(aabb).into()
+    }
+}
+
+impl fmt::Display for Aabb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Aabb{{min: {}, max: {}}}", self.min(), self.max())
+    }
+}
@@ -2,21 +2,164 @@
 
 #[macro_use]
 mod macros;
+mod aabb;
+mod mat3;
+mod mat4;
+mod quat;
 mod rect;
+mod segment;
+#[cfg(feature = "serde")]
+mod serialize;
 mod vec2;
 mod vec3;
 
+pub use aabb::Aabb;
+pub use mat3::Mat3;
+pub use mat4::Mat4;
+pub use quat::Quat;
 pub use rect::Rect;
+pub use segment::Segment;
 pub use vec2::Vec2;
 pub use vec3::Vec3;
 
 use koto_runtime::prelude::*;
+use std::hash::Hasher;
+
+/// The default tolerance used by the geometry types' `approx_eq` functions in Koto
+pub const DEFAULT_EPSILON: f64 = 1e-9;
 
 pub fn make_module() -> KMap {
-    use KValue::{Number, Object};
+    use KValue::{List, Map, Number, Object};
 
     let result = KMap::with_type("geometry");
 
+    result.add_fn("aabb", |ctx| {
+        let mut args = ArgParser::new("geometry.aabb", ctx.args());
+        let a = *args.object::<Vec3>("a")?;
+        let b = *args.object::<Vec3>("b")?;
+        args.finish()?;
+
+        Ok(Aabb::new(a, b).into())
+    });
+
+    result.add_fn("aabb_from_center", |ctx| {
+        let mut args = ArgParser::new("geometry.aabb_from_center", ctx.args());
+        let center = *args.object::<Vec3>("center")?;
+        let size = *args.object::<Vec3>("size")?;
+        args.finish()?;
+
+        Ok(Aabb::from_center_size(center, size).into())
+    });
+
+    result.add_fn("bounding_box", |ctx| match ctx.args() {
+        [List(points)] => {
+            let mut bounds: Option<(Vec2, Vec2)> = None;
+
+            for (i, point) in points.data().iter().enumerate() {
+                let point = point_from_list(i, point)?;
+                bounds = Some(match bounds {
+                    Some((min, max)) => (min.min(&point), max.max(&point)),
+                    None => (point, point),
+                });
+            }
+
+            match bounds {
+                Some((min, max)) => Ok(Rect::from_points(min, max).into()),
+                None => runtime_error!("expected at least one point (found an empty List)"),
+            }
+        }
+        unexpected => type_error_with_slice("a List of Vec2s", unexpected),
+    });
+
+    result.add_fn("mat3_identity", |_| Ok(Mat3::identity().into()));
+
+    result.add_fn("mat3_rotation", |ctx| {
+        let mut args = ArgParser::new("geometry.mat3_rotation", ctx.args());
+        let angle = args.number("angle")?;
+        args.finish()?;
+
+        Ok(Mat3::from_rotation(angle.into()).into())
+    });
+
+    result.add_fn("mat3_scale", |ctx| {
+        let scale = match ctx.args() {
+            [Number(n)] => Vec2::new(n.into(), n.into()),
+            [Number(x), Number(y)] => Vec2::new(x.into(), y.into()),
+            [Object(xy)] if xy.is_a::<Vec2>() => *xy.cast::<Vec2>().unwrap(),
+            unexpected => {
+                return type_error_with_slice("a Number, two Numbers, or a Vec2", unexpected)
+            }
+        };
+
+        Ok(Mat3::from_scale(scale).into())
+    });
+
+    result.add_fn("mat3_translation", |ctx| {
+        let translation = match ctx.args() {
+            [Number(x), Number(y)] => Vec2::new(x.into(), y.into()),
+            [Object(xy)] if xy.is_a::<Vec2>() => *xy.cast::<Vec2>().unwrap(),
+            unexpected => return type_error_with_slice("two Numbers or a Vec2", unexpected),
+        };
+
+        Ok(Mat3::from_translation(translation).into())
+    });
+
+    result.add_fn("mat4_identity", |_| Ok(Mat4::identity().into()));
+
+    result.add_fn("mat4_rotation", |ctx| {
+        let mut args = ArgParser::new("geometry.mat4_rotation", ctx.args());
+        let axis = *args.object::<Vec3>("axis")?;
+        let angle = args.number("angle")?;
+        args.finish()?;
+
+        Ok(Mat4::from_rotation(axis, angle.into()).into())
+    });
+
+    result.add_fn("mat4_scale", |ctx| {
+        let scale = match ctx.args() {
+            [Number(n)] => Vec3::new(n.into(), n.into(), n.into()),
+            [Number(x), Number(y), Number(z)] => Vec3::new(x.into(), y.into(), z.into()),
+            [Object(xyz)] if xyz.is_a::<Vec3>() => *xyz.cast::<Vec3>().unwrap(),
+            unexpected => {
+                return type_error_with_slice("a Number, three Numbers, or a Vec3", unexpected)
+            }
+        };
+
+        Ok(Mat4::from_scale(scale).into())
+    });
+
+    result.add_fn("mat4_translation", |ctx| {
+        let translation = match ctx.args() {
+            [Number(x), Number(y), Number(z)] => Vec3::new(x.into(), y.into(), z.into()),
+            [Object(xyz)] if xyz.is_a::<Vec3>() => *xyz.cast::<Vec3>().unwrap(),
+            unexpected => return type_error_with_slice("three Numbers or a Vec3", unexpected),
+        };
+
+        Ok(Mat4::from_translation(translation).into())
+    });
+
+    result.add_fn("quat_from_axis_angle", |ctx| {
+        let mut args = ArgParser::new("geometry.quat_from_axis_angle", ctx.args());
+        let axis = *args.object::<Vec3>("axis")?;
+        let angle = args.number("angle")?;
+        args.finish()?;
+
+        Ok(Quat::from_axis_angle(axis, angle.into()).into())
+    });
+
+    result.add_fn("quat_from_euler", |ctx| match ctx.args() {
+        [Number(x), Number(y), Number(z)] => {
+            Ok(Quat::from_euler(x.into(), y.into(), z.into()).into())
+        }
+        [Object(xyz)] if xyz.is_a::<Vec3>() => {
+            let xyz = xyz.cast::<Vec3>().unwrap().inner();
+            Ok(Quat::from_euler(xyz.x, xyz.y, xyz.z).into())
+        }
+        unexpected => type_error_with_slice("three Numbers or a Vec3", unexpected),
+    });
+
+    result.add_fn("quat_identity", |_| Ok(Quat::identity().into()));
+
     result.add_fn("rect", |ctx| {
         let (x, y, width, height) = match ctx.args() {
             [] => (0.0, 0.0, 0.0, 0.0),
@@ -28,12 +171,103 @@ pub fn make_module() -> KMap {
                 let size = size.cast::<Vec2>().unwrap().inner();
                 (xy.x, xy.y, size.x, size.y)
             }
-            unexpected => return type_error_with_slice("4 Numbers", unexpected),
+            [Object(xy), Number(width), Number(height)] if xy.is_a::<Vec2>() => {
+                let xy = xy.cast::<Vec2>().unwrap().inner();
+                (xy.x, xy.y, width.into(), height.into())
+            }
+            [Number(x), Number(y), Object(size)] if size.is_a::<Vec2>() => {
+                let size = size.cast::<Vec2>().unwrap().inner();
+                (x.into(), y.into(), size.x, size.y)
+            }
+            [List(list)] => components_from_list(list, true)?.into(),
+            [Map(map)] => components_from_map(map, ["x", "y", "width", "height"], true)?.into(),
+            unexpected => {
+                return type_error_with_slice(
+                    "4 Numbers, two Vec2s, a Vec2 and two Numbers, two Numbers and a Vec2, \
+                     a List, or a Map",
+                    unexpected,
+                )
+            }
         };
 
         Ok(Rect::from_x_y_w_h(x, y, width, height).into())
     });
 
+    result.add_fn("rect_from_center", |ctx| {
+        let mut args = ArgParser::new("geometry.rect_from_center", ctx.args());
+        let center = *args.object::<Vec2>("center")?;
+        let size = *args.object::<Vec2>("size")?;
+        args.finish()?;
+
+        Ok(Rect::from_center_size(center, size).into())
+    });
+
+    result.add_fn("rect_from_points", |ctx| {
+        let mut args = ArgParser::new("geometry.rect_from_points", ctx.args());
+        let a = *args.object::<Vec2>("a")?;
+        let b = *args.object::<Vec2>("b")?;
+        args.finish()?;
+
+        Ok(Rect::from_points(a, b).into())
+    });
+
+    result.add_fn("rect_zero", |_| {
+        Ok(Rect::from_x_y_w_h(0.0, 0.0, 0.0, 0.0).into())
+    });
+
+    result.add_fn("rotate_points", |ctx| {
+        let mut args = ArgParser::new("geometry.rotate_points", ctx.args());
+        let points = args.list("points")?;
+        let angle = f64::from(args.number("angle")?);
+        let pivot = args.optional_object::<Vec2>("pivot")?.map(|pivot| *pivot);
+        args.finish()?;
+
+        match pivot {
+            Some(pivot) => transform_points(points, |p| p.rotate_around(pivot, angle)),
+            None => transform_points(points, |p| p.rotate(angle)),
+        }
+    });
+
+    result.add_fn("scale_points", |ctx| {
+        let (points, factor, pivot) = match ctx.args() {
+            [List(points), factor] => (points, factor, Vec2::ZERO),
+            [List(points), factor, Object(pivot)] if pivot.is_a::<Vec2>() => {
+                (points, factor, *pivot.cast::<Vec2>().unwrap())
+            }
+            unexpected => {
+                return type_error_with_slice(
+                    "a List of Vec2s and a Number or Vec2, with an optional Vec2 pivot",
+                    unexpected,
+                )
+            }
+        };
+        let factor = match factor {
+            Number(n) => Vec2::new(n.into(), n.into()),
+            Object(factor) if factor.is_a::<Vec2>() => *factor.cast::<Vec2>().unwrap(),
+            unexpected => return type_error("a Number or Vec2 as the scale factor", unexpected),
+        };
+
+        transform_points(points, |p| pivot + (p - pivot) * factor)
+    });
+
+    result.add_fn("segment", |ctx| {
+        let mut args = ArgParser::new("geometry.segment", ctx.args());
+        let start = *args.object::<Vec2>("start")?;
+        let end = *args.object::<Vec2>("end")?;
+        args.finish()?;
+
+        Ok(Segment::new(start, end).into())
+    });
+
+    result.add_fn("translate_points", |ctx| {
+        let mut args = ArgParser::new("geometry.translate_points", ctx.args());
+        let points = args.list("points")?;
+        let offset = *args.object::<Vec2>("offset")?;
+        args.finish()?;
+
+        transform_points(points, |p| p + offset)
+    });
+
     result.add_fn("vec2", |ctx| {
         let (x, y) = match ctx.args() {
             [] => (0.0, 0.0),
@@ -42,12 +276,35 @@ pub fn make_module() -> KMap {
             [Object(vec2)] if vec2.is_a::<Vec2>() => {
                 return Ok((*vec2.cast::<Vec2>().unwrap()).into())
             }
-            unexpected => return type_error_with_slice("up to 2 Numbers", unexpected),
+            [List(list)] => components_from_list(list, false)?.into(),
+            [Map(map)] => components_from_map(map, ["x", "y"], false)?.into(),
+            unexpected => {
+                return type_error_with_slice(
+                    "up to 2 Numbers, a Vec2, a List, or a Map",
+                    unexpected,
+                )
+            }
         };
 
         Ok(Vec2::new(x, y).into())
     });
 
+    result.add_fn("vec2_from_angle", |ctx| {
+        let mut args = ArgParser::new("geometry.vec2_from_angle", ctx.args());
+        let angle = args.number("angle")?;
+        args.finish()?;
+
+        Ok(Vec2::from_angle(angle.into()).into())
+    });
+
+    result.add_fn("vec2_one", |_| Ok(Vec2::ONE.into()));
+
+    result.add_fn("vec2_x", |_| Ok(Vec2::X.into()));
+
+    result.add_fn("vec2_y", |_| Ok(Vec2::Y.into()));
+
+    result.add_fn("vec2_zero", |_| Ok(Vec2::ZERO.into()));
+
     result.add_fn("vec3", |ctx| {
         let (x, y, z) = match ctx.args() {
             [] => (0.0, 0.0, 0.0),
@@ -63,13 +320,249 @@ pub fn make_module() -> KMap {
                 (xy.inner().x, xy.inner().y, z.into())
             }
             [Object(v)] if v.is_a::<Vec3>() => return Ok((*v.cast::<Vec3>().unwrap()).into()),
+            [List(list)] => components_from_list(list, false)?.into(),
+            [Map(map)] => components_from_map(map, ["x", "y", "z"], false)?.into(),
             unexpected => {
-                return type_error_with_slice("up to 3 Numbers, a Vec2, or a Vec3", unexpected)
+                return type_error_with_slice(
+                    "up to 3 Numbers, a Vec2, a Vec3, a List, or a Map",
+                    unexpected,
+                )
             }
         };
 
         Ok(Vec3::new(x, y, z).into())
     });
 
+    result.add_fn("vec3_from_cylindrical", |ctx| {
+        let mut args = ArgParser::new("geometry.vec3_from_cylindrical", ctx.args());
+        let radius = args.number("radius")?;
+        let phi = args.number("phi")?;
+        let z = args.number("z")?;
+        args.finish()?;
+
+        Ok(Vec3::from_cylindrical(radius.into(), phi.into(), z.into()).into())
+    });
+
+    result.add_fn("vec3_from_spherical", |ctx| {
+        let mut args = ArgParser::new("geometry.vec3_from_spherical", ctx.args());
+        let radius = args.number("radius")?;
+        let theta = args.number("theta")?;
+        let phi = args.number("phi")?;
+        args.finish()?;
+
+        Ok(Vec3::from_spherical(radius.into(), theta.into(), phi.into()).into())
+    });
+
+    result.add_fn("vec3_one", |_| Ok(Vec3::ONE.into()));
+
+    result.add_fn("vec3_x", |_| Ok(Vec3::X.into()));
+
+    result.add_fn("vec3_y", |_| Ok(Vec3::Y.into()));
+
+    result.add_fn("vec3_z", |_| Ok(Vec3::Z.into()));
+
+    result.add_fn("vec3_zero", |_| Ok(Vec3::ZERO.into()));
+
     result
 }
+
+// Indexes into a geometry value's components, used by the types' KotoObject::index impls
+//
+// Negative indices count backwards from the last component, matching the behaviour of Lists and
+// Tuples. Indexing with a Range returns a List of the selected components.
+fn index_components(components: &[f64], index: &KValue) -> koto_runtime::Result<KValue> {
+    match index {
+        KValue::Number(n) => {
+            let size = components.len();
+            match n.as_index(size) {
+                Some(index) => Ok(components[index].into()),
+                None => runtime_error!(
+                    "index out of range (got {n}, should be in -{size}..={})",
+                    size - 1
+                ),
+            }
+        }
+        KValue::Range(range) => {
+            let components = components[range.indices(components.len())]
+                .iter()
+                .map(|component| KValue::from(*component))
+                .collect::<Vec<_>>();
+            Ok(KList::from_slice(&components).into())
+        }
+        unexpected => type_error("a Number or Range", unexpected),
+    }
+}
+
+// Reads the Vec2 at the given index in a List of points
+fn point_from_list(index: usize, value: &KValue) -> koto_runtime::Result<Vec2> {
+    match value {
+        KValue::Object(p) if p.is_a::<Vec2>() => Ok(*p.cast::<Vec2>().unwrap()),
+        unexpected => runtime_error!(
+            "expected a Vec2 at index {index} in List (found '{}')",
+            unexpected.type_as_string()
+        ),
+    }
+}
+
+// Applies a transform to each Vec2 in a List of points, returning a new List of the results
+//
+// The loop runs entirely in Rust, avoiding the overhead of calling a Vec2 function per point.
+fn transform_points(
+    points: &KList,
+    transform: impl Fn(Vec2) -> Vec2,
+) -> koto_runtime::Result<KValue> {
+    let result = points
+        .data()
+        .iter()
+        .enumerate()
+        .map(|(i, point)| point_from_list(i, point).map(|p| transform(p).into()))
+        .collect::<koto_runtime::Result<_>>()?;
+    Ok(KList::with_data(result).into())
+}
+
+// Reads a geometry value's components from a List of Numbers
+//
+// If `require_all` is false, then the List can be shorter than the number of components,
+// with the missing components set to 0.
+fn components_from_list<const N: usize>(
+    list: &KList,
+    require_all: bool,
+) -> koto_runtime::Result<[f64; N]> {
+    let data = list.data();
+    let len = data.len();
+    if len > N || (require_all && len < N) {
+        let expected = if require_all {
+            format!("{N}")
+        } else {
+            format!("up to {N}")
+        };
+        return runtime_error!("expected a List with {expected} Numbers (found {len} values)");
+    }
+
+    let mut result = [0.0; N];
+    for (i, (component, value)) in result.iter_mut().zip(data.iter()).enumerate() {
+        match value {
+            KValue::Number(n) => *component = n.into(),
+            unexpected => {
+                return runtime_error!(
+                    "expected a Number at index {i} in List (found '{}')",
+                    unexpected.type_as_string()
+                )
+            }
+        }
+    }
+    Ok(result)
+}
+
+// Reads a geometry value's components from a Map, using the given keys
+//
+// If `require_all` is false, then missing keys have their components set to 0.
+fn components_from_map<const N: usize>(
+    map: &KMap,
+    keys: [&str; N],
+    require_all: bool,
+) -> koto_runtime::Result<[f64; N]> {
+    let mut result = [0.0; N];
+    for (component, key) in result.iter_mut().zip(keys) {
+        match map.get(key) {
+            Some(KValue::Number(n)) => *component = n.into(),
+            Some(unexpected) => {
+                return runtime_error!(
+                    "expected a Number for '{key}' in Map (found '{}')",
+                    unexpected.type_as_string()
+                )
+            }
+            None if require_all => return runtime_error!("missing value for '{key}' in Map"),
+            None => {}
+        }
+    }
+    Ok(result)
+}
+
+// Reads the Number being assigned to one of a geometry value's components, used by the types'
+// KotoObject::access_assign impls
+fn assigned_component(key: &KString, value: &KValue) -> koto_runtime::Result<f64> {
+    match value {
+        KValue::Number(n) => Ok(n.into()),
+        unexpected => runtime_error!(
+            "'{key}' can only be assigned a Number (found '{}')",
+            unexpected.type_as_string()
+        ),
+    }
+}
+
+// Hashes a geometry value's components, used by the types' KotoObject::hash impls
+//
+// Negative zero is hashed as zero, so that hashing remains consistent with equality.
+fn hash_components(components: &[f64], state: &mut dyn Hasher) {
+    for component in components {
+        let component = if *component == 0.0 { 0.0 } else { *component };
+        state.write_u64(component.to_bits());
+    }
+}
+
+// The number of significant digits used when displaying a geometry value's components
+const DISPLAY_SIGNIFICANT_DIGITS: i32 = 15;
+
+// Formats one of a geometry value's components, used by the types' Display impls
+//
+// If a precision isn't provided, then the component is rounded to DISPLAY_SIGNIFICANT_DIGITS with
+// trailing zeros removed, which hides noise like the 4 in `0.30000000000000004`.
+//
+// Negative zero is displayed without a sign, including negative values that round to zero.
+fn format_component(n: f64, precision: Option<usize>) -> String {
+    let mut result = match precision {
+        Some(precision) => format!("{n:.precision$}"),
+        None if n.is_finite() && n != 0.0 => {
+            let magnitude = n.abs().log10().floor() as i32;
+            let decimal_places = (DISPLAY_SIGNIFICANT_DIGITS - 1 - magnitude).max(0) as usize;
+            let mut result = format!("{n:.decimal_places$}");
+            if result.contains('.') {
+                let trimmed_len = result.trim_end_matches('0').trim_end_matches('.').len();
+                result.truncate(trimmed_len);
+            }
+            result
+        }
+        None => n.to_string(),
+    };
+
+    if result.starts_with('-') && result[1..].chars().all(|c| matches!(c, '0' | '.')) {
+        result.remove(0);
+    }
+
+    result
+}
+
+// Returns the component indices for a swizzle like `yx` or `xy0`, used by the vector types'
+// KotoObject::access_fallback impls
+//
+// A swizzle contains two or three components, producing a Vec2 or Vec3 respectively. Each
+// component is either one of the given component names, or `0`, which is represented by `None`.
+//
+// `None` is returned if the key isn't a valid swizzle.
+fn swizzle_indices(key: &str, component_names: &[&str]) -> Option<Vec<Option<usize>>> {
+    if !(2..=3).contains(&key.len()) || key.chars().all(|c| c == '0') {
+        return None;
+    }
+
+    key.chars()
+        .map(|c| match c {
+            '0' => Some(None),
+            _ => component_names
+                .iter()
+                .position(|name| name.chars().eq([c]))
+                .map(Some),
+        })
+        .collect()
+}
+
+// Produces a swizzled vector from the given components, see `swizzle_indices`
+fn swizzle(components: &[f64], indices: &[Option<usize>]) -> KValue {
+    let component = |index: &Option<usize>| index.map_or(0.0, |i| components[i]);
+
+    match indices {
+        [x, y] => Vec2::new(component(x), component(y)).into(),
+        [x, y, z] => Vec3::new(component(x), component(y), component(z)).into(),
+        _ => unreachable!("swizzles contain two or three components"),
+    }
+}
@@ -79,6 +79,19 @@ macro_rules! geometry_arithmetic_op {
     }
 }
 
+#[macro_export]
+macro_rules! geometry_unsupported_rhs_op {
+    ($lhs:expr, $op:literal) => {
+        runtime_error!(
+            "'{} {} {}' isn't supported, the {} needs to be on the left-hand side",
+            $lhs.type_as_string(),
+            $op,
+            Self::type_static(),
+            Self::type_static()
+        )
+    };
+}
+
 #[macro_export]
 macro_rules! geometry_compound_assign_op {
     ($self:ident, $rhs:expr, $op:tt) => {
@@ -101,19 +114,51 @@ macro_rules! geometry_compound_assign_op {
     }
 }
 
+// Implements `access_fallback` for a vector type, providing swizzle functions like `v.yx()`
+//
+// The macro is provided with the names of the type's components, see `swizzle_indices` for
+// details of the supported swizzles.
+#[macro_export]
+macro_rules! geometry_swizzle_fallback {
+    ($($component:ident),+) => {
+        fn access_fallback(&self, key: &KString) -> Option<KValue> {
+            let indices = $crate::swizzle_indices(key, &[$(stringify!($component)),+])?;
+
+            let swizzle = move |ctx: &mut CallContext| {
+                use KValue::Object;
+
+                match ctx.instance_and_args(|i| matches!(i, Object(_)), Self::type_static())? {
+                    (Object(o), []) => {
+                        let v = o.cast::<Self>()?;
+                        Ok($crate::swizzle(&[$(v.0.$component),+], &indices))
+                    }
+                    (_, unexpected) => type_error_with_slice("no arguments", unexpected),
+                }
+            };
+
+            Some(KValue::NativeFunction(KNativeFunction::new(swizzle)))
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! geometry_comparison_op {
-    ($self:ident, $rhs:expr, $op:tt) => {
+    // Values of other types are never equal to geometry values
+    ($self:ident, $rhs:expr, ==) => {
+        geometry_comparison_op!($self, $rhs, ==, false)
+    };
+    ($self:ident, $rhs:expr, !=) => {
+        geometry_comparison_op!($self, $rhs, !=, true)
+    };
+    ($self:ident, $rhs:expr, $op:tt, $mismatched_result:expr) => {
         {
             match $rhs {
                 KValue::Object(rhs) if rhs.is_a::<Self>() => {
                     let rhs = rhs.cast::<Self>().unwrap();
                     Ok(*$self $op *rhs)
                 }
-                unexpected => {
-                    type_error(&format!("a {}", Self::type_static()), unexpected)
-                }
+                _ => Ok($mismatched_result),
             }
         }
-    }
+    };
 }
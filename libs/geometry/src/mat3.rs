@@ -0,0 +1,138 @@
+use crate::{hash_components, Vec2};
+use koto_runtime::{derive::*, prelude::*, Result};
+use std::{fmt, hash::Hasher, ops};
+
+type Inner = nannou_core::glam::DMat3;
+
+/// A 3x3 matrix representing a 2D affine transform
+#[derive(Copy, Clone, PartialEq, KotoCopy, KotoType)]
+#[koto(use_copy)]
+pub struct Mat3(Inner);
+
+#[koto_impl(runtime = koto_runtime)]
+impl Mat3 {
+    pub fn identity() -> Self {
+        Self(Inner::IDENTITY)
+    }
+
+    pub fn from_translation(translation: Vec2) -> Self {
+        Self(Inner::from_translation(translation.inner()))
+    }
+
+    /// Creates a counter-clockwise rotation, with the angle expressed in radians
+    pub fn from_rotation(angle: f64) -> Self {
+        Self(Inner::from_angle(angle))
+    }
+
+    pub fn from_scale(scale: Vec2) -> Self {
+        Self(Inner::from_scale(scale.inner()))
+    }
+
+    pub fn inner(&self) -> Inner {
+        self.0
+    }
+
+    /// Returns the matrix's elements in column-major order
+    pub fn to_cols_array(&self) -> [f64; 9] {
+        self.0.to_cols_array()
+    }
+
+    /// Transforms a point, applying the matrix's translation
+    pub fn transform_point(&self, point: Vec2) -> Vec2 {
+        self.0.transform_point2(point.inner()).into()
+    }
+
+    /// Transforms a vector, ignoring the matrix's translation
+    pub fn transform_vector(&self, vector: Vec2) -> Vec2 {
+        self.0.transform_vector2(vector.inner()).into()
+    }
+
+    #[koto_method(name = "transform_point")]
+    fn koto_transform_point(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(p)] if p.is_a::<Vec2>() => {
+                let p = p.cast::<Vec2>().unwrap();
+                Ok(self.transform_point(*p).into())
+            }
+            unexpected => type_error_with_slice("a Vec2", unexpected),
+        }
+    }
+
+    #[koto_method(name = "transform_vector")]
+    fn koto_transform_vector(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(v)] if v.is_a::<Vec2>() => {
+                let v = v.cast::<Vec2>().unwrap();
+                Ok(self.transform_vector(*v).into())
+            }
+            unexpected => type_error_with_slice("a Vec2", unexpected),
+        }
+    }
+}
+
+impl KotoObject for Mat3 {
+    fn display(&self, ctx: &mut DisplayContext) -> Result<()> {
+        ctx.append(self.to_string());
+        Ok(())
+    }
+
+    fn multiply(&self, rhs: &KValue) -> Result<KValue> {
+        match rhs {
+            KValue::Object(rhs) if rhs.is_a::<Self>() => {
+                let rhs = rhs.cast::<Self>().unwrap();
+                Ok((*self * *rhs).into())
+            }
+            unexpected => type_error("a Mat3", unexpected),
+        }
+    }
+
+    fn equal(&self, rhs: &KValue) -> Result<bool> {
+        geometry_comparison_op!(self, rhs, ==)
+    }
+
+    fn not_equal(&self, rhs: &KValue) -> Result<bool> {
+        geometry_comparison_op!(self, rhs, !=)
+    }
+
+    // Matrices with NaN components can't be used as map keys, given that NaN isn't equal to itself
+    fn is_hashable(&self) -> bool {
+        !self.0.is_nan()
+    }
+
+    fn hash(&self, state: &mut dyn Hasher) {
+        hash_components(&self.0.to_cols_array(), state)
+    }
+}
+
+impl ops::Mul for Mat3 {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self(self.0 * other.0)
+    }
+}
+
+impl From<Inner> for Mat3 {
+    fn from(m: Inner) -> Self {
+        Self(m)
+    }
+}
+
+impl From<Mat3> for KValue {
+    fn from(m: Mat3) -> Self {
+        KObject::from(m).into()
+    }
+}
+
+impl fmt::Display for Mat3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Mat3{{")?;
+        for (i, col) in self.to_cols_array().chunks(3).enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "[{}, {}, {}]", col[0], col[1], col[2])?;
+        }
+        write!(f, "}}")
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{hash_components, Vec3};
+use koto_runtime::{derive::*, prelude::*, Result};
+use std::{fmt, hash::Hasher, ops};
+
+type Inner = nannou_core::glam::DMat4;
+
+/// A 4x4 matrix representing a 3D affine transform
+#[derive(Copy, Clone, PartialEq, KotoCopy, KotoType)]
+#[koto(use_copy)]
+pub struct Mat4(Inner);
+
+#[koto_impl(runtime = koto_runtime)]
+impl Mat4 {
+    pub fn identity() -> Self {
+        Self(Inner::IDENTITY)
+    }
+
+    pub fn from_translation(translation: Vec3) -> Self {
+        Self(Inner::from_translation(translation.inner()))
+    }
+
+    /// Creates a rotation around the given axis, with the angle expressed in radians
+    ///
+    /// The axis doesn't need to be normalized.
+    pub fn from_rotation(axis: Vec3, angle: f64) -> Self {
+        Self(Inner::from_axis_angle(axis.normalize().inner(), angle))
+    }
+
+    pub fn from_scale(scale: Vec3) -> Self {
+        Self(Inner::from_scale(scale.inner()))
+    }
+
+    pub fn inner(&self) -> Inner {
+        self.0
+    }
+
+    /// Returns the matrix's elements in column-major order
+    pub fn to_cols_array(&self) -> [f64; 16] {
+        self.0.to_cols_array()
+    }
+
+    /// Transforms a point, applying the matrix's translation
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        self.0.transform_point3(point.inner()).into()
+    }
+
+    /// Transforms a vector, ignoring the matrix's translation
+    pub fn transform_vector(&self, vector: Vec3) -> Vec3 {
+        self.0.transform_vector3(vector.inner()).into()
+    }
+
+    #[koto_method(name = "transform_point")]
+    fn koto_transform_point(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(p)] if p.is_a::<Vec3>() => {
+                let p = p.cast::<Vec3>().unwrap();
+                Ok(self.transform_point(*p).into())
+            }
+            unexpected => type_error_with_slice("a Vec3", unexpected),
+        }
+    }
+
+    #[koto_method(name = "transform_vector")]
+    fn koto_transform_vector(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(v)] if v.is_a::<Vec3>() => {
+                let v = v.cast::<Vec3>().unwrap();
+                Ok(self.transform_vector(*v).into())
+            }
+            unexpected => type_error_with_slice("a Vec3", unexpected),
+        }
+    }
+}
+
+impl KotoObject for Mat4 {
+    fn display(&self, ctx: &mut DisplayContext) -> Result<()> {
+        ctx.append(self.to_string());
+        Ok(())
+    }
+
+    fn multiply(&self, rhs: &KValue) -> Result<KValue> {
+        match rhs {
+            KValue::Object(rhs) if rhs.is_a::<Self>() => {
+                let rhs = rhs.cast::<Self>().unwrap();
+                Ok((*self * *rhs).into())
+            }
+            unexpected => type_error("a Mat4", unexpected),
+        }
+    }
+
+    fn equal(&self, rhs: &KValue) -> Result<bool> {
+        geometry_comparison_op!(self, rhs, ==)
+    }
+
+    fn not_equal(&self, rhs: &KValue) -> Result<bool> {
+        geometry_comparison_op!(self, rhs, !=)
+    }
+
+    // Matrices with NaN components can't be used as map keys, given that NaN isn't equal to itself
+    fn is_hashable(&self) -> bool {
+        !self.0.is_nan()
+    }
+
+    fn hash(&self, state: &mut dyn Hasher) {
+        hash_components(&self.0.to_cols_array(), state)
+    }
+}
+
+impl ops::Mul for Mat4 {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self(self.0 * other.0)
+    }
+}
+
+impl From<Inner> for Mat4 {
+    fn from(m: Inner) -> Self {
+        Self(m)
+    }
+}
+
+impl From<Mat4> for KValue {
+    fn from(m: Mat4) -> Self {
+        KObject::from(m).into()
+    }
+}
+
+impl fmt::Display for Mat4 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Mat4{{")?;
+        for (i, col) in self.to_cols_array().chunks(4).enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "[{}, {}, {}, {}]", col[0], col[1], col[2], col[3])?;
+        }
+        write!(f, "}}")
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{hash_components, Vec3};
+use koto_runtime::{derive::*, prelude::*, Result};
+use nannou_core::glam::EulerRot;
+use std::{fmt, hash::Hasher, ops};
+
+type Inner = nannou_core::glam::DQuat;
+
+/// A quaternion representing a 3D rotation
+#[derive(Copy, Clone, PartialEq, KotoCopy, KotoType)]
+#[koto(use_copy)]
+pub struct Quat(Inner);
+
+#[koto_impl(runtime = koto_runtime)]
+impl Quat {
+    pub fn identity() -> Self {
+        Self(Inner::IDENTITY)
+    }
+
+    /// Creates a rotation around the given axis, with the angle expressed in radians
+    ///
+    /// The axis doesn't need to be normalized.
+    pub fn from_axis_angle(axis: Vec3, angle: f64) -> Self {
+        Self(Inner::from_axis_angle(axis.normalize().inner(), angle))
+    }
+
+    /// Creates a rotation from Euler angles, expressed in radians
+    ///
+    /// The rotations are intrinsic, applied around the X axis, then the Y axis, then the Z axis.
+    pub fn from_euler(x: f64, y: f64, z: f64) -> Self {
+        Self(Inner::from_euler(EulerRot::XYZ, x, y, z))
+    }
+
+    pub fn inner(&self) -> Inner {
+        self.0
+    }
+
+    pub fn x(&self) -> f64 {
+        self.0.x
+    }
+
+    pub fn y(&self) -> f64 {
+        self.0.y
+    }
+
+    pub fn z(&self) -> f64 {
+        self.0.z
+    }
+
+    pub fn w(&self) -> f64 {
+        self.0.w
+    }
+
+    /// Returns a quaternion representing the same rotation with a length of 1
+    pub fn normalize(&self) -> Self {
+        Self(self.0.normalize())
+    }
+
+    /// Returns the result of rotating the given vector by the quaternion
+    pub fn rotate_vec3(&self, v: Vec3) -> Vec3 {
+        (self.0 * v.inner()).into()
+    }
+
+    /// Spherically interpolates between the quaternion and `other`
+    ///
+    /// The interpolation always follows the shortest arc between the two rotations.
+    pub fn slerp(&self, other: &Self, t: f64) -> Self {
+        // q and -q represent the same rotation, so flip the target if needed to avoid taking the
+        // long way around.
+        let end = if self.0.dot(other.0) < 0.0 {
+            -other.0
+        } else {
+            other.0
+        };
+        Self(self.0.slerp(end, t))
+    }
+
+    /// Returns the rotation as Euler angles, expressed in radians
+    ///
+    /// See [Quat::from_euler] for the order in which the rotations are applied.
+    pub fn to_euler(&self) -> (f64, f64, f64) {
+        self.0.to_euler(EulerRot::XYZ)
+    }
+
+    #[koto_method(name = "normalize")]
+    fn koto_normalize(&self) -> KValue {
+        self.normalize().into()
+    }
+
+    #[koto_method(name = "rotate_vec3")]
+    fn koto_rotate_vec3(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(v)] if v.is_a::<Vec3>() => {
+                let v = v.cast::<Vec3>().unwrap();
+                Ok(self.rotate_vec3(*v).into())
+            }
+            unexpected => type_error_with_slice("a Vec3", unexpected),
+        }
+    }
+
+    #[koto_method(name = "slerp")]
+    fn koto_slerp(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other), KValue::Number(t)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.slerp(&other, t.into()).into())
+            }
+            unexpected => type_error_with_slice("a Quat and a Number", unexpected),
+        }
+    }
+
+    #[koto_method(name = "to_euler")]
+    fn koto_to_euler(&self) -> KValue {
+        let (x, y, z) = self.to_euler();
+        KValue::Tuple(vec![x.into(), y.into(), z.into()].into())
+    }
+
+    #[koto_method(name = "w")]
+    fn koto_w(&self) -> KValue {
+        self.w().into()
+    }
+
+    #[koto_method(name = "x")]
+    fn koto_x(&self) -> KValue {
+        self.x().into()
+    }
+
+    #[koto_method(name = "y")]
+    fn koto_y(&self) -> KValue {
+        self.y().into()
+    }
+
+    #[koto_method(name = "z")]
+    fn koto_z(&self) -> KValue {
+        self.z().into()
+    }
+}
+
+impl KotoObject for Quat {
+    fn display(&self, ctx: &mut DisplayContext) -> Result<()> {
+        ctx.append(self.to_string());
+        Ok(())
+    }
+
+    fn multiply(&self, rhs: &KValue) -> Result<KValue> {
+        match rhs {
+            KValue::Object(rhs) if rhs.is_a::<Self>() => {
+                let rhs = rhs.cast::<Self>().unwrap();
+                Ok((*self * *rhs).into())
+            }
+            unexpected => type_error("a Quat", unexpected),
+        }
+    }
+
+    fn equal(&self, rhs: &KValue) -> Result<bool> {
+        geometry_comparison_op!(self, rhs, ==)
+    }
+
+    fn not_equal(&self, rhs: &KValue) -> Result<bool> {
+        geometry_comparison_op!(self, rhs, !=)
+    }
+
+    // Quaternions with NaN components can't be used as map keys, given that NaN isn't equal to itself
+    fn is_hashable(&self) -> bool {
+        !self.0.is_nan()
+    }
+
+    fn hash(&self, state: &mut dyn Hasher) {
+        hash_components(&<[f64; 4]>::from(self.0), state)
+    }
+}
+
+impl ops::Mul for Quat {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self(self.0 * other.0)
+    }
+}
+
+impl From<Inner> for Quat {
+    fn from(q: Inner) -> Self {
+        Self(q)
+    }
+}
+
+impl From<Quat> for KValue {
+    fn from(q: Quat) -> Self {
+        KObject::from(q).into()
+    }
+}
+
+impl fmt::Display for Quat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Quat{{x: {}, y: {}, z: {}, w: {}}}",
+            self.x(),
+            self.y(),
+            self.z(),
+            self.w()
+        )
+    }
+}
@@ -1,6 +1,8 @@
-use crate::Vec2;
+use crate::{
+    assigned_component, format_component, hash_components, index_components, Vec2, DEFAULT_EPSILON,
+};
 use koto_runtime::{derive::*, prelude::*, Result};
-use std::fmt;
+use std::{fmt, hash::Hasher, ops};
 
 type Inner = nannou_core::geom::Rect<f64>;
 
@@ -14,6 +16,271 @@ impl Rect {
         Inner::from_x_y_w_h(x, y, width, height).into()
     }
 
+    /// Initializes a rect with corners located at the two given points
+    ///
+    /// The points can be provided in any order, the resulting rect will always have a
+    /// non-negative width and height.
+    pub fn from_points(a: Vec2, b: Vec2) -> Self {
+        let (a, b) = (a.inner(), b.inner());
+        Self::from_edges(a.x.min(b.x), a.x.max(b.x), a.y.min(b.y), a.y.max(b.y))
+    }
+
+    /// Initializes a rect with the given center position and size
+    pub fn from_center_size(center: Vec2, size: Vec2) -> Self {
+        let (center, size) = (center.inner(), size.inner());
+        Self::from_x_y_w_h(center.x, center.y, size.x, size.y)
+    }
+
+    fn from_edges(left: f64, right: f64, bottom: f64, top: f64) -> Self {
+        Self::from_x_y_w_h(
+            (left + right) / 2.0,
+            (bottom + top) / 2.0,
+            right - left,
+            top - bottom,
+        )
+    }
+
+    pub fn inner(&self) -> Inner {
+        self.0
+    }
+
+    /// Returns true if each component is within `epsilon` of the matching component in `other`
+    ///
+    /// `false` is returned if any of the components are NaN.
+    pub fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        let (a, b) = (&self.0, &other.0);
+        [
+            (a.x(), b.x()),
+            (a.y(), b.y()),
+            (a.w(), b.w()),
+            (a.h(), b.h()),
+        ]
+        .iter()
+        .all(|(a, b)| (a - b).abs() <= epsilon)
+    }
+
+    /// Returns the ratio of the rect's width to its height
+    ///
+    /// `None` is returned if the rect has a height of zero.
+    pub fn aspect_ratio(&self) -> Option<f64> {
+        let r = &self.0;
+        (r.h() != 0.0).then(|| r.w() / r.h())
+    }
+
+    /// Returns true if the rect's edges are in order
+    ///
+    /// A rect created with a negative width or height has its edges reversed on that axis.
+    /// The rect's size and edge positions are reported as if the rect was normalized, but it won't
+    /// compare as equal to its normalized equivalent, see [Rect::normalized].
+    pub fn is_normalized(&self) -> bool {
+        let r = &self.0;
+        r.x.start <= r.x.end && r.y.start <= r.y.end
+    }
+
+    /// Returns an equivalent rect with its edges in order
+    ///
+    /// The normalized rect has the same center, with a non-negative width and height.
+    pub fn normalized(&self) -> Self {
+        Self(self.0.absolute())
+    }
+
+    /// Returns true if the point is within the rect's bounds
+    ///
+    /// Points located on the rect's edges are considered to be contained by the rect.
+    pub fn contains_point(&self, point: Vec2) -> bool {
+        let p = point.inner();
+        let r = &self.0;
+        p.x >= r.left() && p.x <= r.right() && p.y >= r.bottom() && p.y <= r.top()
+    }
+
+    /// Returns true if `other` is entirely within the rect's bounds
+    ///
+    /// Edges are inclusive, so a rect contains itself, along with any rects that share its edges.
+    pub fn contains_rect(&self, other: &Rect) -> bool {
+        let (a, b) = (&self.0, &other.0);
+        b.left() >= a.left()
+            && b.right() <= a.right()
+            && b.bottom() >= a.bottom()
+            && b.top() <= a.top()
+    }
+
+    /// Returns the overlapping region of the two rects
+    ///
+    /// `None` is returned if the rects don't overlap. Rects that touch at their edges
+    /// produce an intersection with zero width or height.
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let (a, b) = (&self.0, &other.0);
+        let left = a.left().max(b.left());
+        let right = a.right().min(b.right());
+        let bottom = a.bottom().max(b.bottom());
+        let top = a.top().min(b.top());
+
+        if left <= right && bottom <= top {
+            Some(Self::from_edges(left, right, bottom, top))
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if the rects overlap
+    ///
+    /// This matches [Rect::intersection], so rects that touch at their edges are considered to be
+    /// intersecting.
+    pub fn intersects(&self, other: &Rect) -> bool {
+        let (a, b) = (&self.0, &other.0);
+        a.left() <= b.right()
+            && b.left() <= a.right()
+            && a.bottom() <= b.top()
+            && b.bottom() <= a.top()
+    }
+
+    /// Returns the smallest rect that contains both rects
+    pub fn union(&self, other: &Rect) -> Rect {
+        let (a, b) = (&self.0, &other.0);
+        Self::from_edges(
+            a.left().min(b.left()),
+            a.right().max(b.right()),
+            a.bottom().min(b.bottom()),
+            a.top().max(b.top()),
+        )
+    }
+
+    /// Returns a copy of the rect with each edge moved outwards by the given amounts
+    ///
+    /// Negative amounts shrink the rect, see [Rect::inset].
+    pub fn expand(&self, x: f64, y: f64) -> Self {
+        self.inset(-x, -y)
+    }
+
+    /// Returns a copy of the rect with each edge moved inwards by the given amounts
+    ///
+    /// If an amount is larger than half of the rect's size on that axis, then the rect
+    /// collapses to a size of zero on that axis, centered on the rect's original center.
+    pub fn inset(&self, x: f64, y: f64) -> Self {
+        let r = &self.0;
+        Self::from_x_y_w_h(
+            r.x(),
+            r.y(),
+            (r.w() - x * 2.0).max(0.0),
+            (r.h() - y * 2.0).max(0.0),
+        )
+    }
+
+    /// Linearly interpolates the position and size of the rect towards `other`
+    ///
+    /// Values of `t` outside of the range `0..=1` will extrapolate beyond the inputs.
+    pub fn lerp(&self, other: &Self, t: f64) -> Self {
+        let lerp = |a: f64, b: f64| a + (b - a) * t;
+        let (a, b) = (&self.0, &other.0);
+        Self::from_x_y_w_h(
+            lerp(a.x(), b.x()),
+            lerp(a.y(), b.y()),
+            lerp(a.w(), b.w()),
+            lerp(a.h(), b.h()),
+        )
+    }
+
+    /// Returns a rect with this rect's aspect ratio, scaled and centered to cover `other`
+    ///
+    /// `None` is returned if the rect has a width or height of zero.
+    pub fn fill(&self, other: &Rect) -> Option<Self> {
+        self.fit_with_scale(other, f64::max)
+    }
+
+    /// Returns a rect with this rect's aspect ratio, scaled and centered to fit inside `other`
+    ///
+    /// `None` is returned if the rect has a width or height of zero.
+    pub fn fit_into(&self, other: &Rect) -> Option<Self> {
+        self.fit_with_scale(other, f64::min)
+    }
+
+    // Scales the rect to match other's size, choosing between the per-axis scale factors
+    fn fit_with_scale(&self, other: &Rect, choose_scale: fn(f64, f64) -> f64) -> Option<Self> {
+        let (a, b) = (&self.0, &other.0);
+        if a.w() == 0.0 || a.h() == 0.0 {
+            return None;
+        }
+        let scale = choose_scale(b.w() / a.w(), b.h() / a.h());
+        Some(Self::from_x_y_w_h(
+            b.x(),
+            b.y(),
+            a.w() * scale,
+            a.h() * scale,
+        ))
+    }
+
+    /// Returns a copy of the rect scaled by the given factors around `pivot`
+    ///
+    /// Negative factors flip the rect around the pivot, with the result being normalized to have
+    /// a non-negative width and height.
+    pub fn scaled(&self, factor: Vec2, pivot: Vec2) -> Self {
+        let (factor, pivot) = (factor.inner(), pivot.inner());
+        let r = &self.0;
+        let scale = |value: f64, pivot: f64, factor: f64| pivot + (value - pivot) * factor;
+        Self::from_points(
+            Vec2::new(
+                scale(r.left(), pivot.x, factor.x),
+                scale(r.bottom(), pivot.y, factor.y),
+            ),
+            Vec2::new(
+                scale(r.right(), pivot.x, factor.x),
+                scale(r.top(), pivot.y, factor.y),
+            ),
+        )
+    }
+
+    /// Returns a copy of the rect moved by the given offset
+    pub fn translated(&self, offset: Vec2) -> Self {
+        let (r, offset) = (&self.0, offset.inner());
+        Self::from_x_y_w_h(r.x() + offset.x, r.y() + offset.y, r.w(), r.h())
+    }
+
+    /// Divides the rect into a grid of equally sized cells
+    ///
+    /// The cells are produced in row-major order, starting with the top-left cell.
+    pub fn grid(&self, columns: usize, rows: usize) -> impl Iterator<Item = Self> {
+        let r = self.0;
+        let column_edge = move |column: usize| r.left() + r.w() * column as f64 / columns as f64;
+        let row_edge = move |row: usize| r.top() - r.h() * row as f64 / rows as f64;
+
+        (0..rows).flat_map(move |row| {
+            (0..columns).map(move |column| {
+                Self::from_edges(
+                    column_edge(column),
+                    column_edge(column + 1),
+                    row_edge(row + 1),
+                    row_edge(row),
+                )
+            })
+        })
+    }
+
+    /// Splits the rect into left and right parts at the given x coordinate
+    ///
+    /// The coordinate is clamped to the rect's bounds, so splitting outside of the rect produces
+    /// a part with a width of zero.
+    pub fn split_horizontal(&self, x: f64) -> (Self, Self) {
+        let r = &self.0;
+        let x = x.max(r.left()).min(r.right());
+        (
+            Self::from_edges(r.left(), x, r.bottom(), r.top()),
+            Self::from_edges(x, r.right(), r.bottom(), r.top()),
+        )
+    }
+
+    /// Splits the rect into bottom and top parts at the given y coordinate
+    ///
+    /// The coordinate is clamped to the rect's bounds, so splitting outside of the rect produces
+    /// a part with a height of zero.
+    pub fn split_vertical(&self, y: f64) -> (Self, Self) {
+        let r = &self.0;
+        let y = y.max(r.bottom()).min(r.top());
+        (
+            Self::from_edges(r.left(), r.right(), r.bottom(), y),
+            Self::from_edges(r.left(), r.right(), y, r.top()),
+        )
+    }
+
     #[koto_method]
     fn left(&self) -> KValue {
         self.0.left().into()
@@ -49,6 +316,36 @@ impl Rect {
         Vec2::from(self.0.xy()).into()
     }
 
+    #[koto_method]
+    fn position(&self) -> KValue {
+        Vec2::new(self.0.x(), self.0.y()).into()
+    }
+
+    #[koto_method]
+    fn size(&self) -> KValue {
+        Vec2::new(self.0.w(), self.0.h()).into()
+    }
+
+    #[koto_method]
+    fn top_left(&self) -> KValue {
+        Vec2::new(self.0.left(), self.0.top()).into()
+    }
+
+    #[koto_method]
+    fn top_right(&self) -> KValue {
+        Vec2::new(self.0.right(), self.0.top()).into()
+    }
+
+    #[koto_method]
+    fn bottom_left(&self) -> KValue {
+        Vec2::new(self.0.left(), self.0.bottom()).into()
+    }
+
+    #[koto_method]
+    fn bottom_right(&self) -> KValue {
+        Vec2::new(self.0.right(), self.0.bottom()).into()
+    }
+
     #[koto_method]
     fn x(&self) -> KValue {
         self.0.x().into()
@@ -59,15 +356,197 @@ impl Rect {
         self.0.y().into()
     }
 
+    #[koto_method(name = "approx_eq")]
+    fn koto_approx_eq(&self, args: &[KValue]) -> Result<KValue> {
+        use KValue::{Number, Object};
+
+        match args {
+            [Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.approx_eq(&other, DEFAULT_EPSILON).into())
+            }
+            [Object(other), Number(epsilon)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.approx_eq(&other, epsilon.into()).into())
+            }
+            unexpected => type_error_with_slice("a Rect, with an optional Number", unexpected),
+        }
+    }
+
+    #[koto_method(name = "aspect_ratio")]
+    fn koto_aspect_ratio(&self) -> Result<KValue> {
+        match self.aspect_ratio() {
+            Some(ratio) => Ok(ratio.into()),
+            None => runtime_error!("the aspect ratio of a rect with a height of zero is undefined"),
+        }
+    }
+
     #[koto_method]
     fn contains(&self, args: &[KValue]) -> Result<KValue> {
         match args {
             [KValue::Object(p)] if p.is_a::<Vec2>() => {
                 let p = p.cast::<Vec2>().unwrap();
-                let result = self.0.contains(p.inner());
-                Ok(result.into())
+                Ok(self.contains_point(*p).into())
+            }
+            [KValue::Object(r)] if r.is_a::<Rect>() => {
+                let r = r.cast::<Rect>().unwrap();
+                Ok(self.contains_rect(&r).into())
+            }
+            unexpected => type_error_with_slice("a Vec2 or Rect", unexpected),
+        }
+    }
+
+    #[koto_method(name = "expand")]
+    fn koto_expand(&self, args: &[KValue]) -> Result<KValue> {
+        let (x, y) = xy_amounts_from_args(args)?;
+        Ok(self.expand(x, y).into())
+    }
+
+    #[koto_method(name = "fill")]
+    fn koto_fill(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                fit_result(self.fill(&other))
+            }
+            unexpected => type_error_with_slice("a Rect", unexpected),
+        }
+    }
+
+    #[koto_method(name = "fit_into")]
+    fn koto_fit_into(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                fit_result(self.fit_into(&other))
+            }
+            unexpected => type_error_with_slice("a Rect", unexpected),
+        }
+    }
+
+    #[koto_method(name = "grid")]
+    fn koto_grid(&self, args: &[KValue]) -> Result<KValue> {
+        use KValue::Number;
+
+        let is_count = |n: &KNumber| *n >= 1.0 && f64::from(n).fract() == 0.0;
+
+        match args {
+            [Number(columns), Number(rows)] if is_count(columns) && is_count(rows) => {
+                let cells = self
+                    .grid(columns.into(), rows.into())
+                    .map(KValue::from)
+                    .collect();
+                Ok(KList::with_data(cells).into())
+            }
+            unexpected => type_error_with_slice("two positive integers", unexpected),
+        }
+    }
+
+    #[koto_method(name = "inset")]
+    fn koto_inset(&self, args: &[KValue]) -> Result<KValue> {
+        let (x, y) = xy_amounts_from_args(args)?;
+        Ok(self.inset(x, y).into())
+    }
+
+    #[koto_method(name = "intersection")]
+    fn koto_intersection(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.intersection(&other).map_or(KValue::Null, KValue::from))
             }
-            unexpected => type_error_with_slice("Vec2", unexpected),
+            unexpected => type_error_with_slice("a Rect", unexpected),
+        }
+    }
+
+    #[koto_method(name = "intersects")]
+    fn koto_intersects(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.intersects(&other).into())
+            }
+            unexpected => type_error_with_slice("a Rect", unexpected),
+        }
+    }
+
+    #[koto_method(name = "is_normalized")]
+    fn koto_is_normalized(&self) -> KValue {
+        self.is_normalized().into()
+    }
+
+    #[koto_method(name = "lerp")]
+    fn koto_lerp(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other), KValue::Number(t)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.lerp(&other, t.into()).into())
+            }
+            unexpected => type_error_with_slice("a Rect and a Number", unexpected),
+        }
+    }
+
+    #[koto_method(name = "normalized")]
+    fn koto_normalized(&self) -> KValue {
+        self.normalized().into()
+    }
+
+    #[koto_method(name = "scaled")]
+    fn koto_scaled(&self, args: &[KValue]) -> Result<KValue> {
+        use KValue::Object;
+
+        // An optional pivot can follow the scale factor
+        let (factor_args, pivot) = match args {
+            [factor @ .., Object(pivot)] if !factor.is_empty() && pivot.is_a::<Vec2>() => {
+                (factor, *pivot.cast::<Vec2>().unwrap())
+            }
+            _ => (args, Vec2::new(0.0, 0.0)),
+        };
+
+        match xy_amounts(factor_args) {
+            Some((x, y)) => Ok(self.scaled(Vec2::new(x, y), pivot).into()),
+            None => type_error_with_slice(
+                "a Number, two Numbers, or a Vec2, followed by an optional Vec2 pivot",
+                args,
+            ),
+        }
+    }
+
+    #[koto_method(name = "split_horizontal")]
+    fn koto_split_horizontal(&self, args: &[KValue]) -> Result<KValue> {
+        let x = split_position_from_args(args, self.0.left(), self.0.w())?;
+        let (left, right) = self.split_horizontal(x);
+        Ok(KList::from_slice(&[left.into(), right.into()]).into())
+    }
+
+    #[koto_method(name = "split_vertical")]
+    fn koto_split_vertical(&self, args: &[KValue]) -> Result<KValue> {
+        let y = split_position_from_args(args, self.0.bottom(), self.0.h())?;
+        let (bottom, top) = self.split_vertical(y);
+        Ok(KList::from_slice(&[bottom.into(), top.into()]).into())
+    }
+
+    #[koto_method(name = "translated")]
+    fn koto_translated(&self, args: &[KValue]) -> Result<KValue> {
+        use KValue::{Number, Object};
+
+        let offset = match args {
+            [Number(x), Number(y)] => Vec2::new(x.into(), y.into()),
+            [Object(offset)] if offset.is_a::<Vec2>() => *offset.cast::<Vec2>().unwrap(),
+            unexpected => return type_error_with_slice("two Numbers or a Vec2", unexpected),
+        };
+
+        Ok(self.translated(offset).into())
+    }
+
+    #[koto_method(name = "union")]
+    fn koto_union(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.union(&other).into())
+            }
+            unexpected => type_error_with_slice("a Rect", unexpected),
         }
     }
 
@@ -89,6 +568,99 @@ impl Rect {
         // Return a clone of the Rect instance
         ctx.instance_result()
     }
+
+    #[koto_method]
+    fn with_center(&self, args: &[KValue]) -> Result<KValue> {
+        use KValue::{Number, Object};
+
+        let (x, y) = match args {
+            [Number(x), Number(y)] => (x.into(), y.into()),
+            [Object(p)] if p.is_a::<Vec2>() => {
+                let p = p.cast::<Vec2>().unwrap();
+                (p.inner().x, p.inner().y)
+            }
+            unexpected => return type_error_with_slice("two Numbers or a Vec2", unexpected),
+        };
+
+        Ok(Self::from_x_y_w_h(x, y, self.0.w(), self.0.h()).into())
+    }
+
+    #[koto_method(name = "to_list")]
+    fn koto_to_list(&self) -> KValue {
+        KList::from_slice(&[
+            self.0.x().into(),
+            self.0.y().into(),
+            self.0.w().into(),
+            self.0.h().into(),
+        ])
+        .into()
+    }
+
+    #[koto_method(name = "to_map")]
+    fn koto_to_map(&self) -> KValue {
+        let result = KMap::with_capacity(4);
+        result.insert("x", self.0.x());
+        result.insert("y", self.0.y());
+        result.insert("width", self.0.w());
+        result.insert("height", self.0.h());
+        result.into()
+    }
+
+    #[koto_method(name = "to_string")]
+    fn koto_to_string(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [] => Ok(self.to_string().into()),
+            [KValue::Number(precision)] if *precision >= 0.0 => {
+                let precision = usize::from(precision);
+                Ok(format!("{self:.precision$}").into())
+            }
+            unexpected => type_error_with_slice("an optional non-negative Number", unexpected),
+        }
+    }
+}
+
+// Parses per-axis amounts from a single Number, two Numbers, or a Vec2
+fn xy_amounts_from_args(args: &[KValue]) -> Result<(f64, f64)> {
+    match xy_amounts(args) {
+        Some(amounts) => Ok(amounts),
+        None => type_error_with_slice("a Number, two Numbers, or a Vec2", args),
+    }
+}
+
+fn xy_amounts(args: &[KValue]) -> Option<(f64, f64)> {
+    use KValue::{Number, Object};
+
+    match args {
+        [Number(n)] => Some((n.into(), n.into())),
+        [Number(x), Number(y)] => Some((x.into(), y.into())),
+        [Object(xy)] if xy.is_a::<Vec2>() => {
+            let xy = xy.cast::<Vec2>().unwrap().inner();
+            Some((xy.x, xy.y))
+        }
+        _ => None,
+    }
+}
+
+// Converts the result of Rect::fill or Rect::fit_into into a Koto result
+fn fit_result(rect: Option<Rect>) -> Result<KValue> {
+    match rect {
+        Some(rect) => Ok(rect.into()),
+        None => runtime_error!("unable to fit a rect with a width or height of zero"),
+    }
+}
+
+// Parses a split position, either as a fraction of the rect's size, or as a coordinate
+//
+// Fractions are clamped to the range `0..=1`. Passing `true` as the second argument causes the
+// first argument to be treated as a coordinate.
+fn split_position_from_args(args: &[KValue], start: f64, size: f64) -> Result<f64> {
+    use KValue::{Bool, Number};
+
+    match args {
+        [Number(t)] | [Number(t), Bool(false)] => Ok(start + size * f64::from(t).clamp(0.0, 1.0)),
+        [Number(position), Bool(true)] => Ok(position.into()),
+        unexpected => type_error_with_slice("a Number, with an optional Bool", unexpected),
+    }
 }
 
 impl KotoObject for Rect {
@@ -105,6 +677,90 @@ impl KotoObject for Rect {
         geometry_comparison_op!(self, rhs, !=)
     }
 
+    // Rects with NaN components can't be used as map keys, given that NaN isn't equal to itself
+    fn is_hashable(&self) -> bool {
+        !(self.0.x.start.is_nan()
+            || self.0.x.end.is_nan()
+            || self.0.y.start.is_nan()
+            || self.0.y.end.is_nan())
+    }
+
+    fn hash(&self, state: &mut dyn Hasher) {
+        hash_components(
+            &[self.0.x.start, self.0.x.end, self.0.y.start, self.0.y.end],
+            state,
+        )
+    }
+
+    fn negate(&self, _vm: &mut KotoVm) -> Result<KValue> {
+        runtime_error!(
+            "'-{0}' isn't supported, a {0} can't be negated",
+            Self::type_static()
+        )
+    }
+
+    fn add(&self, rhs: &KValue) -> Result<KValue> {
+        match rhs {
+            KValue::Object(rhs) if rhs.is_a::<Vec2>() => {
+                let rhs = rhs.cast::<Vec2>().unwrap();
+                Ok((*self + *rhs).into())
+            }
+            unexpected => type_error("a Vec2", unexpected),
+        }
+    }
+
+    fn subtract(&self, rhs: &KValue) -> Result<KValue> {
+        match rhs {
+            KValue::Object(rhs) if rhs.is_a::<Vec2>() => {
+                let rhs = rhs.cast::<Vec2>().unwrap();
+                Ok((*self - *rhs).into())
+            }
+            unexpected => type_error("a Vec2", unexpected),
+        }
+    }
+
+    fn add_assign(&mut self, rhs: &KValue) -> Result<()> {
+        match rhs {
+            KValue::Object(rhs) if rhs.is_a::<Vec2>() => {
+                *self += *rhs.cast::<Vec2>().unwrap();
+                Ok(())
+            }
+            unexpected => type_error("a Vec2", unexpected),
+        }
+    }
+
+    fn subtract_assign(&mut self, rhs: &KValue) -> Result<()> {
+        match rhs {
+            KValue::Object(rhs) if rhs.is_a::<Vec2>() => {
+                *self -= *rhs.cast::<Vec2>().unwrap();
+                Ok(())
+            }
+            unexpected => type_error("a Vec2", unexpected),
+        }
+    }
+
+    fn access_assign(&mut self, key: &KString, value: &KValue) -> Result<()> {
+        let r = &self.0;
+        let (mut x, mut y, mut width, mut height) = (r.x(), r.y(), r.w(), r.h());
+        match key.as_str() {
+            "x" => x = assigned_component(key, value)?,
+            "y" => y = assigned_component(key, value)?,
+            "width" => width = assigned_component(key, value)?,
+            "height" => height = assigned_component(key, value)?,
+            _ => return runtime_error!("'{key}' can't be assigned in '{}'", Self::type_static()),
+        }
+        self.0 = Inner::from_x_y_w_h(x, y, width, height);
+        Ok(())
+    }
+
+    fn index(&self, index: &KValue) -> Result<KValue> {
+        index_components(&[self.0.x(), self.0.y(), self.0.w(), self.0.h()], index)
+    }
+
+    fn size(&self) -> Option<usize> {
+        Some(4)
+    }
+
     fn is_iterable(&self) -> IsIterable {
         IsIterable::Iterable
     }
@@ -127,6 +783,35 @@ impl KotoObject for Rect {
     }
 }
 
+// Adding or subtracting a Vec2 translates the rect
+impl ops::Add<Vec2> for Rect {
+    type Output = Self;
+
+    fn add(self, offset: Vec2) -> Self {
+        self.translated(offset)
+    }
+}
+
+impl ops::Sub<Vec2> for Rect {
+    type Output = Self;
+
+    fn sub(self, offset: Vec2) -> Self {
+        self.translated(-offset)
+    }
+}
+
+impl ops::AddAssign<Vec2> for Rect {
+    fn add_assign(&mut self, offset: Vec2) {
+        *self = *self + offset;
+    }
+}
+
+impl ops::SubAssign<Vec2> for Rect {
+    fn sub_assign(&mut self, offset: Vec2) {
+        *self = *self - offset;
+    }
+}
+
 impl From<Inner> for Rect {
     fn from(r: Inner) -> Self {
         Self(r)
@@ -148,13 +833,14 @@ impl From<Rect> for KValue {
 impl fmt::Display for Rect {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let r = &self.0;
+        let precision = f.precision();
         write!(
             f,
             "Rect{{x: {}, y: {}, width: {}, height: {}}}",
-            r.x(),
-            r.y(),
-            r.w(),
-            r.h()
+            format_component(r.x(), precision),
+            format_component(r.y(), precision),
+            format_component(r.w(), precision),
+            format_component(r.h(), precision)
         )
     }
 }
@@ -0,0 +1,203 @@
+use crate::{hash_components, Vec2};
+use koto_runtime::{derive::*, prelude::*, Result};
+use std::{fmt, hash::Hasher};
+
+type Inner = nannou_core::geom::DVec2;
+
+#[derive(Copy, Clone, PartialEq, KotoCopy, KotoType)]
+#[koto(use_copy)]
+pub struct Segment {
+    start: Inner,
+    end: Inner,
+}
+
+#[koto_impl(runtime = koto_runtime)]
+impl Segment {
+    pub fn new(start: Vec2, end: Vec2) -> Self {
+        Self {
+            start: start.inner(),
+            end: end.inner(),
+        }
+    }
+
+    pub fn start(&self) -> Vec2 {
+        self.start.into()
+    }
+
+    pub fn end(&self) -> Vec2 {
+        self.end.into()
+    }
+
+    /// Returns the point on the segment that's closest to `p`
+    pub fn closest_point(&self, p: Vec2) -> Vec2 {
+        let d = self.end - self.start;
+        let length_squared = d.length_squared();
+        if length_squared == 0.0 {
+            return self.start();
+        }
+        let t = ((p.inner() - self.start).dot(d) / length_squared).clamp(0.0, 1.0);
+        (self.start + d * t).into()
+    }
+
+    /// Returns a unit vector pointing from the segment's start to its end
+    ///
+    /// A zero vector is returned if the segment has a length of zero.
+    pub fn direction(&self) -> Vec2 {
+        (self.end - self.start).normalize_or_zero().into()
+    }
+
+    /// Returns the point where the two segments intersect
+    ///
+    /// Segments that touch at their endpoints are considered to be intersecting.
+    ///
+    /// If the segments are collinear and overlapping, then the point in the overlapping region
+    /// that's closest to this segment's start is returned.
+    ///
+    /// `None` is returned if the segments don't intersect.
+    pub fn intersection(&self, other: &Segment) -> Option<Vec2> {
+        let r = self.end - self.start;
+        let s = other.end - other.start;
+
+        // Degenerate segments are treated as points
+        if r.length_squared() == 0.0 {
+            return (other.closest_point(self.start()).inner() == self.start).then(|| self.start());
+        }
+        if s.length_squared() == 0.0 {
+            return (self.closest_point(other.start()).inner() == other.start)
+                .then(|| other.start());
+        }
+
+        let start_offset = other.start - self.start;
+        let denominator = r.perp_dot(s);
+
+        if denominator == 0.0 {
+            // The segments are parallel, check for collinearity
+            if start_offset.perp_dot(r) != 0.0 {
+                return None;
+            }
+
+            // Find the overlapping region by projecting the other segment onto this one
+            let length_squared = r.length_squared();
+            let t0 = start_offset.dot(r) / length_squared;
+            let t1 = (other.end - self.start).dot(r) / length_squared;
+            let overlap_start = t0.min(t1).max(0.0);
+            let overlap_end = t0.max(t1).min(1.0);
+
+            (overlap_start <= overlap_end).then(|| (self.start + r * overlap_start).into())
+        } else {
+            let t = start_offset.perp_dot(s) / denominator;
+            let u = start_offset.perp_dot(r) / denominator;
+
+            ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u))
+                .then(|| (self.start + r * t).into())
+        }
+    }
+
+    pub fn length(&self) -> f64 {
+        self.start.distance(self.end)
+    }
+
+    pub fn midpoint(&self) -> Vec2 {
+        ((self.start + self.end) / 2.0).into()
+    }
+
+    #[koto_method(name = "closest_point")]
+    fn koto_closest_point(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(p)] if p.is_a::<Vec2>() => {
+                let p = p.cast::<Vec2>().unwrap();
+                Ok(self.closest_point(*p).into())
+            }
+            unexpected => type_error_with_slice("a Vec2", unexpected),
+        }
+    }
+
+    #[koto_method(name = "direction")]
+    fn koto_direction(&self) -> KValue {
+        self.direction().into()
+    }
+
+    #[koto_method(name = "end")]
+    fn koto_end(&self) -> KValue {
+        self.end().into()
+    }
+
+    #[koto_method(name = "intersection")]
+    fn koto_intersection(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.intersection(&other).map_or(KValue::Null, KValue::from))
+            }
+            unexpected => type_error_with_slice("a Segment", unexpected),
+        }
+    }
+
+    #[koto_method]
+    fn intersects(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.intersection(&other).is_some().into())
+            }
+            unexpected => type_error_with_slice("a Segment", unexpected),
+        }
+    }
+
+    #[koto_method(name = "length")]
+    fn koto_length(&self) -> KValue {
+        self.length().into()
+    }
+
+    #[koto_method(name = "midpoint")]
+    fn koto_midpoint(&self) -> KValue {
+        self.midpoint().into()
+    }
+
+    #[koto_method(name = "start")]
+    fn koto_start(&self) -> KValue {
+        self.start().into()
+    }
+}
+
+impl KotoObject for Segment {
+    fn display(&self, ctx: &mut DisplayContext) -> Result<()> {
+        ctx.append(self.to_string());
+        Ok(())
+    }
+
+    fn equal(&self, rhs: &KValue) -> Result<bool> {
+        geometry_comparison_op!(self, rhs, ==)
+    }
+
+    fn not_equal(&self, rhs: &KValue) -> Result<bool> {
+        geometry_comparison_op!(self, rhs, !=)
+    }
+
+    // Segments with NaN components can't be used as map keys, given that NaN isn't equal to itself
+    fn is_hashable(&self) -> bool {
+        !(self.start.is_nan() || self.end.is_nan())
+    }
+
+    fn hash(&self, state: &mut dyn Hasher) {
+        hash_components(&[self.start.x, self.start.y, self.end.x, self.end.y], state)
+    }
+}
+
+impl From<(Vec2, Vec2)> for Segment {
+    fn from((start, end): (Vec2, Vec2)) -> Self {
+        Self::new(start, end)
+    }
+}
+
+impl From<Segment> for KValue {
+    fn from(segment: Segment) -> Self {
+        KObject::from(segment).into()
+    }
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Segment{{start: {}, end: {}}}", self.start(), self.end())
+    }
+}
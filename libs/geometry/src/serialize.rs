@@ -0,0 +1,89 @@
+//! Serde support for the geometry types, enabled with the `serde` feature
+//!
+//! The types are serialized via intermediate structs rather than by deriving the impls directly,
+//! which keeps the field names stable regardless of how the types are represented internally.
+
+use crate::{Rect, Vec2, Vec3};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Vec2")]
+struct Vec2Fields {
+    x: f64,
+    y: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Vec3")]
+struct Vec3Fields {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Rect")]
+struct RectFields {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Serialize for Vec2 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let v = self.inner();
+        Vec2Fields { x: v.x, y: v.y }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Vec2 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let Vec2Fields { x, y } = Vec2Fields::deserialize(deserializer)?;
+        Ok(Self::new(x, y))
+    }
+}
+
+impl Serialize for Vec3 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let v = self.inner();
+        Vec3Fields {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Vec3 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let Vec3Fields { x, y, z } = Vec3Fields::deserialize(deserializer)?;
+        Ok(Self::new(x, y, z))
+    }
+}
+
+impl Serialize for Rect {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let r = self.inner();
+        RectFields {
+            x: r.x(),
+            y: r.y(),
+            width: r.w(),
+            height: r.h(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rect {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let RectFields {
+            x,
+            y,
+            width,
+            height,
+        } = RectFields::deserialize(deserializer)?;
+        Ok(Self::from_x_y_w_h(x, y, width, height))
+    }
+}
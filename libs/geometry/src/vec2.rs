@@ -1,5 +1,8 @@
+use crate::{
+    assigned_component, format_component, hash_components, index_components, DEFAULT_EPSILON,
+};
 use koto_runtime::{derive::*, prelude::*, Result};
-use std::{fmt, ops};
+use std::{fmt, hash::Hasher, ops};
 
 type Inner = nannou_core::geom::DVec2;
 
@@ -9,22 +12,434 @@ pub struct Vec2(Inner);
 
 #[koto_impl(runtime = koto_runtime)]
 impl Vec2 {
+    pub const ZERO: Self = Self(Inner::ZERO);
+    pub const ONE: Self = Self(Inner::ONE);
+    pub const X: Self = Self(Inner::X);
+    pub const Y: Self = Self(Inner::Y);
+
     pub fn new(x: f64, y: f64) -> Self {
         Self(Inner::new(x, y))
     }
 
+    /// Returns a unit vector pointing in the direction of the given angle
+    ///
+    /// The angle is expressed in radians, counter-clockwise from the positive x axis.
+    pub fn from_angle(angle: f64) -> Self {
+        Self::new(angle.cos(), angle.sin())
+    }
+
     pub fn inner(&self) -> Inner {
         self.0
     }
 
-    #[koto_method]
-    fn angle(&self) -> KValue {
-        Inner::X.angle_between(self.0).into()
+    /// Returns a vector containing the absolute value of each component
+    pub fn abs(&self) -> Self {
+        Self(self.0.abs())
     }
 
-    #[koto_method]
-    fn length(&self) -> KValue {
-        self.0.length().into()
+    /// Returns the angle of the vector, expressed in radians
+    ///
+    /// The angle is measured counter-clockwise from the positive x axis, in the range `-π..=π`.
+    /// A vector with a length of zero has an angle of zero.
+    pub fn angle(&self) -> f64 {
+        // atan2 returns ±π for signed zeros like (-0, 0), so zero vectors are handled explicitly
+        if self.0 == Inner::ZERO {
+            return 0.0;
+        }
+        self.0.y.atan2(self.0.x)
+    }
+
+    /// Returns the signed angle from the vector to `other`, expressed in radians
+    ///
+    /// The result is in the range `-π..=π`, with positive values indicating that `other` is
+    /// counter-clockwise from the vector. If either vector has a length of zero then the result is
+    /// zero.
+    pub fn angle_between(&self, other: &Self) -> f64 {
+        if self.0 == Inner::ZERO || other.0 == Inner::ZERO {
+            return 0.0;
+        }
+        self.0.perp_dot(other.0).atan2(self.0.dot(other.0))
+    }
+
+    /// Returns true if each component is within `epsilon` of the matching component in `other`
+    ///
+    /// `false` is returned if any of the components are NaN.
+    pub fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        (self.0 - other.0).abs().cmple(Inner::splat(epsilon)).all()
+    }
+
+    /// Returns a vector with each component rounded up to the nearest integer
+    ///
+    /// Negative zero results are normalized to zero, matching `number.ceil`.
+    pub fn ceil(&self) -> Self {
+        // Adding zero converts -0.0 to 0.0
+        Self(self.0.ceil() + 0.0)
+    }
+
+    /// Returns the result of dividing the vector component-wise by `divisor`
+    ///
+    /// `None` is returned if any of the divisor's components are zero, rather than the infinite
+    /// or NaN components that would be produced by the `/` operator.
+    pub fn checked_div(&self, divisor: &Self) -> Option<Self> {
+        (!divisor.0.to_array().contains(&0.0)).then(|| Self(self.0 / divisor.0))
+    }
+
+    /// Returns a vector with each component clamped to the range defined by `lo` and `hi`
+    ///
+    /// If a component of `lo` is greater than the matching component in `hi`,
+    /// then the bounds for that component are swapped.
+    pub fn clamp(&self, lo: &Self, hi: &Self) -> Self {
+        Self(self.0.max(lo.0.min(hi.0)).min(lo.0.max(hi.0)))
+    }
+
+    pub fn distance(&self, other: &Self) -> f64 {
+        self.0.distance(other.0)
+    }
+
+    pub fn distance_squared(&self, other: &Self) -> f64 {
+        self.0.distance_squared(other.0)
+    }
+
+    pub fn dot(&self, other: &Self) -> f64 {
+        self.0.dot(other.0)
+    }
+
+    /// Returns a vector with each component rounded down to the nearest integer
+    ///
+    /// Negative zero results are normalized to zero, matching `number.floor`.
+    pub fn floor(&self) -> Self {
+        // Adding zero converts -0.0 to 0.0
+        Self(self.0.floor() + 0.0)
+    }
+
+    pub fn length(&self) -> f64 {
+        self.0.length()
+    }
+
+    pub fn length_squared(&self) -> f64 {
+        self.0.length_squared()
+    }
+
+    /// Linearly interpolates between the vector and `other`
+    ///
+    /// Values of `t` outside of the range `0..=1` will extrapolate beyond the inputs.
+    pub fn lerp(&self, other: &Self, t: f64) -> Self {
+        Self(self.0.lerp(other.0, t))
+    }
+
+    /// Returns a vector containing the largest value of each component
+    pub fn max(&self, other: &Self) -> Self {
+        Self(self.0.max(other.0))
+    }
+
+    /// Returns a vector containing the smallest value of each component
+    pub fn min(&self, other: &Self) -> Self {
+        Self(self.0.min(other.0))
+    }
+
+    /// Returns a vector with the same direction and a length of 1
+    ///
+    /// A zero vector is returned if the vector's length is zero.
+    pub fn normalize(&self) -> Self {
+        Self(self.0.normalize_or_zero())
+    }
+
+    /// Returns the vector rotated by 90° counter-clockwise
+    pub fn perpendicular(&self) -> Self {
+        Self(self.0.perp())
+    }
+
+    /// Returns the projection of the vector onto `other`
+    ///
+    /// A zero vector is returned if `other` has a length of zero.
+    pub fn project(&self, other: &Self) -> Self {
+        let length_squared = other.length_squared();
+        if length_squared > 0.0 {
+            Self(other.0 * (self.dot(other) / length_squared))
+        } else {
+            Self(Inner::ZERO)
+        }
+    }
+
+    /// Returns the vector reflected about a surface with the given normal
+    ///
+    /// The normal doesn't need to be normalized, if it has a length of zero then
+    /// the vector is returned unchanged.
+    pub fn reflect(&self, normal: &Self) -> Self {
+        let normal = normal.normalize();
+        Self(self.0 - normal.0 * (2.0 * self.dot(&normal)))
+    }
+
+    /// Returns the vector rotated counter-clockwise around the origin
+    ///
+    /// The angle is expressed in radians.
+    pub fn rotate(&self, angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(
+            self.0.x * cos - self.0.y * sin,
+            self.0.x * sin + self.0.y * cos,
+        )
+    }
+
+    /// Returns the vector rotated counter-clockwise around the given pivot point
+    ///
+    /// The angle is expressed in radians.
+    pub fn rotate_around(&self, pivot: Vec2, angle: f64) -> Self {
+        Self(self.0 - pivot.0).rotate(angle) + pivot
+    }
+
+    /// Returns a vector with each component rounded to the nearest integer
+    ///
+    /// Half-way values are rounded away from zero, and negative zero results are normalized to
+    /// zero, matching `number.round`.
+    pub fn round(&self) -> Self {
+        // Adding zero converts -0.0 to 0.0
+        Self(self.0.round() + 0.0)
+    }
+
+    /// Returns the vector's polar coordinates as a `(radius, angle)` pair
+    ///
+    /// The angle is expressed in radians, counter-clockwise from the positive x axis,
+    /// in the range `-π..=π`.
+    pub fn to_polar(&self) -> (f64, f64) {
+        (self.length(), self.angle())
+    }
+
+    #[koto_method(name = "abs")]
+    fn koto_abs(&self) -> KValue {
+        self.abs().into()
+    }
+
+    #[koto_method(name = "angle")]
+    fn koto_angle(&self) -> KValue {
+        self.angle().into()
+    }
+
+    #[koto_method(name = "angle_between")]
+    fn koto_angle_between(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.angle_between(&other).into())
+            }
+            unexpected => type_error_with_slice("a Vec2", unexpected),
+        }
+    }
+
+    #[koto_method(name = "approx_eq")]
+    fn koto_approx_eq(&self, args: &[KValue]) -> Result<KValue> {
+        use KValue::{Number, Object};
+
+        match args {
+            [Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.approx_eq(&other, DEFAULT_EPSILON).into())
+            }
+            [Object(other), Number(epsilon)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.approx_eq(&other, epsilon.into()).into())
+            }
+            unexpected => type_error_with_slice("a Vec2, with an optional Number", unexpected),
+        }
+    }
+
+    #[koto_method(name = "ceil")]
+    fn koto_ceil(&self) -> KValue {
+        self.ceil().into()
+    }
+
+    #[koto_method(name = "checked_div")]
+    fn koto_checked_div(&self, args: &[KValue]) -> Result<KValue> {
+        let divisor = match args {
+            [KValue::Object(divisor)] if divisor.is_a::<Self>() => *divisor.cast::<Self>().unwrap(),
+            [KValue::Number(n)] => Self(Inner::splat(n.into())),
+            unexpected => return type_error_with_slice("a Vec2 or Number", unexpected),
+        };
+        Ok(self
+            .checked_div(&divisor)
+            .map_or(KValue::Null, KValue::from))
+    }
+
+    #[koto_method(name = "clamp")]
+    fn koto_clamp(&self, args: &[KValue]) -> Result<KValue> {
+        use KValue::{Number, Object};
+
+        match args {
+            [Object(lo), Object(hi)] if lo.is_a::<Self>() && hi.is_a::<Self>() => {
+                let lo = lo.cast::<Self>().unwrap();
+                let hi = hi.cast::<Self>().unwrap();
+                Ok(self.clamp(&lo, &hi).into())
+            }
+            [Number(lo), Number(hi)] => {
+                let lo = Self(Inner::splat(lo.into()));
+                let hi = Self(Inner::splat(hi.into()));
+                Ok(self.clamp(&lo, &hi).into())
+            }
+            unexpected => type_error_with_slice("two Vec2s or two Numbers", unexpected),
+        }
+    }
+
+    #[koto_method(name = "distance")]
+    fn koto_distance(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.distance(&other).into())
+            }
+            unexpected => type_error_with_slice("a Vec2", unexpected),
+        }
+    }
+
+    #[koto_method(name = "distance_squared")]
+    fn koto_distance_squared(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.distance_squared(&other).into())
+            }
+            unexpected => type_error_with_slice("a Vec2", unexpected),
+        }
+    }
+
+    #[koto_method(name = "dot")]
+    fn koto_dot(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(rhs)] if rhs.is_a::<Self>() => {
+                let rhs = rhs.cast::<Self>().unwrap();
+                Ok(self.dot(&rhs).into())
+            }
+            unexpected => type_error_with_slice("a Vec2", unexpected),
+        }
+    }
+
+    #[koto_method(name = "floor")]
+    fn koto_floor(&self) -> KValue {
+        self.floor().into()
+    }
+
+    #[koto_method(name = "length")]
+    fn koto_length(&self) -> KValue {
+        self.length().into()
+    }
+
+    #[koto_method(name = "length_squared")]
+    fn koto_length_squared(&self) -> KValue {
+        self.length_squared().into()
+    }
+
+    #[koto_method(name = "lerp")]
+    fn koto_lerp(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other), KValue::Number(t)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.lerp(&other, t.into()).into())
+            }
+            unexpected => type_error_with_slice("a Vec2 and a Number", unexpected),
+        }
+    }
+
+    #[koto_method(name = "max")]
+    fn koto_max(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.max(&other).into())
+            }
+            unexpected => type_error_with_slice("a Vec2", unexpected),
+        }
+    }
+
+    #[koto_method(name = "min")]
+    fn koto_min(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.min(&other).into())
+            }
+            unexpected => type_error_with_slice("a Vec2", unexpected),
+        }
+    }
+
+    #[koto_method(name = "normalize")]
+    fn koto_normalize(&self) -> KValue {
+        self.normalize().into()
+    }
+
+    #[koto_method(name = "perpendicular")]
+    fn koto_perpendicular(&self) -> KValue {
+        self.perpendicular().into()
+    }
+
+    #[koto_method(name = "project")]
+    fn koto_project(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.project(&other).into())
+            }
+            unexpected => type_error_with_slice("a Vec2", unexpected),
+        }
+    }
+
+    #[koto_method(name = "reflect")]
+    fn koto_reflect(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(normal)] if normal.is_a::<Self>() => {
+                let normal = normal.cast::<Self>().unwrap();
+                Ok(self.reflect(&normal).into())
+            }
+            unexpected => type_error_with_slice("a Vec2", unexpected),
+        }
+    }
+
+    #[koto_method(name = "rotate")]
+    fn koto_rotate(&self, args: &[KValue]) -> Result<KValue> {
+        use KValue::{Number, Object};
+
+        match args {
+            [Number(angle)] => Ok(self.rotate(angle.into()).into()),
+            [Object(pivot), Number(angle)] if pivot.is_a::<Self>() => {
+                let pivot = pivot.cast::<Self>().unwrap();
+                Ok(self.rotate_around(*pivot, angle.into()).into())
+            }
+            unexpected => type_error_with_slice("a Number, or a Vec2 and a Number", unexpected),
+        }
+    }
+
+    #[koto_method(name = "round")]
+    fn koto_round(&self) -> KValue {
+        self.round().into()
+    }
+
+    #[koto_method(name = "to_list")]
+    fn koto_to_list(&self) -> KValue {
+        KList::from_slice(&[self.0.x.into(), self.0.y.into()]).into()
+    }
+
+    #[koto_method(name = "to_map")]
+    fn koto_to_map(&self) -> KValue {
+        let result = KMap::with_capacity(2);
+        result.insert("x", self.0.x);
+        result.insert("y", self.0.y);
+        result.into()
+    }
+
+    #[koto_method(name = "to_polar")]
+    fn koto_to_polar(&self) -> KValue {
+        let (radius, angle) = self.to_polar();
+        KValue::Tuple(vec![radius.into(), angle.into()].into())
+    }
+
+    #[koto_method(name = "to_string")]
+    fn koto_to_string(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [] => Ok(self.to_string().into()),
+            [KValue::Number(precision)] if *precision >= 0.0 => {
+                let precision = usize::from(precision);
+                Ok(format!("{self:.precision$}").into())
+            }
+            unexpected => type_error_with_slice("an optional non-negative Number", unexpected),
+        }
     }
 
     #[koto_method]
@@ -64,6 +479,25 @@ impl KotoObject for Vec2 {
         geometry_arithmetic_op!(self, rhs, /)
     }
 
+    fn add_rhs(&self, lhs: &KValue) -> Result<KValue> {
+        geometry_unsupported_rhs_op!(lhs, "+")
+    }
+
+    fn subtract_rhs(&self, lhs: &KValue) -> Result<KValue> {
+        geometry_unsupported_rhs_op!(lhs, "-")
+    }
+
+    fn multiply_rhs(&self, lhs: &KValue) -> Result<KValue> {
+        match lhs {
+            KValue::Number(n) => Ok((*self * f64::from(n)).into()),
+            unexpected => type_error("a Number", unexpected),
+        }
+    }
+
+    fn divide_rhs(&self, lhs: &KValue) -> Result<KValue> {
+        geometry_unsupported_rhs_op!(lhs, "/")
+    }
+
     fn add_assign(&mut self, rhs: &KValue) -> Result<()> {
         geometry_compound_assign_op!(self, rhs, +=)
     }
@@ -88,15 +522,32 @@ impl KotoObject for Vec2 {
         geometry_comparison_op!(self, rhs, !=)
     }
 
-    fn index(&self, index: &KValue) -> Result<KValue> {
-        match index {
-            KValue::Number(n) => match usize::from(n) {
-                0 => Ok(self.x()),
-                1 => Ok(self.y()),
-                other => runtime_error!("index out of range (got {other}, should be <= 1)"),
-            },
-            unexpected => type_error("Number", unexpected),
+    fn access_assign(&mut self, key: &KString, value: &KValue) -> Result<()> {
+        match key.as_str() {
+            "x" => self.0.x = assigned_component(key, value)?,
+            "y" => self.0.y = assigned_component(key, value)?,
+            _ => return runtime_error!("'{key}' can't be assigned in '{}'", Self::type_static()),
         }
+        Ok(())
+    }
+
+    geometry_swizzle_fallback!(x, y);
+
+    // Vectors with NaN components can't be used as map keys, given that NaN isn't equal to itself
+    fn is_hashable(&self) -> bool {
+        !self.0.is_nan()
+    }
+
+    fn hash(&self, state: &mut dyn Hasher) {
+        hash_components(&[self.0.x, self.0.y], state)
+    }
+
+    fn index(&self, index: &KValue) -> Result<KValue> {
+        index_components(&[self.0.x, self.0.y], index)
+    }
+
+    fn size(&self) -> Option<usize> {
+        Some(2)
     }
 
     fn is_iterable(&self) -> IsIterable {
@@ -139,7 +590,13 @@ impl From<(f64, f64)> for Vec2 {
 
 impl fmt::Display for Vec2 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Vec2{{x: {}, y: {}}}", self.0.x, self.0.y)
+        let precision = f.precision();
+        write!(
+            f,
+            "Vec2{{x: {}, y: {}}}",
+            format_component(self.0.x, precision),
+            format_component(self.0.y, precision)
+        )
     }
 }
 
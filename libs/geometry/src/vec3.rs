@@ -1,6 +1,9 @@
+use crate::{
+    assigned_component, format_component, hash_components, index_components, Vec2, DEFAULT_EPSILON,
+};
 use koto_runtime::{derive::*, prelude::*, Result};
 use nannou_core::geom::DVec3;
-use std::{fmt, ops};
+use std::{fmt, hash::Hasher, ops};
 
 #[derive(Copy, Clone, PartialEq, KotoCopy, KotoType)]
 #[koto(use_copy)]
@@ -8,10 +11,437 @@ pub struct Vec3(DVec3);
 
 #[koto_impl(runtime = koto_runtime)]
 impl Vec3 {
+    pub const ZERO: Self = Self(DVec3::ZERO);
+    pub const ONE: Self = Self(DVec3::ONE);
+    pub const X: Self = Self(DVec3::X);
+    pub const Y: Self = Self(DVec3::Y);
+    pub const Z: Self = Self(DVec3::Z);
+
     pub fn new(x: f64, y: f64, z: f64) -> Self {
         Self(DVec3::new(x, y, z))
     }
 
+    /// Initializes a vector from cylindrical coordinates
+    ///
+    /// `radius` is the distance from the z axis, and `phi` is the azimuthal angle in radians,
+    /// measured counter-clockwise from the positive x axis in the xy plane.
+    pub fn from_cylindrical(radius: f64, phi: f64, z: f64) -> Self {
+        Self::new(radius * phi.cos(), radius * phi.sin(), z)
+    }
+
+    /// Initializes a vector from spherical coordinates
+    ///
+    /// `theta` is the polar angle in radians, measured from the positive z axis, and `phi` is the
+    /// azimuthal angle in radians, measured counter-clockwise from the positive x axis in the
+    /// xy plane.
+    pub fn from_spherical(radius: f64, theta: f64, phi: f64) -> Self {
+        let (sin_theta, cos_theta) = theta.sin_cos();
+        Self::new(
+            radius * sin_theta * phi.cos(),
+            radius * sin_theta * phi.sin(),
+            radius * cos_theta,
+        )
+    }
+
+    pub fn inner(&self) -> DVec3 {
+        self.0
+    }
+
+    /// Returns a vector containing the absolute value of each component
+    pub fn abs(&self) -> Self {
+        Self(self.0.abs())
+    }
+
+    /// Returns a unit vector that's perpendicular to the vector
+    ///
+    /// The result is useful when building a coordinate frame around the vector, with no
+    /// guarantees made about which of the perpendicular vectors is returned.
+    /// [Vec3::X] is returned if the vector's length is zero.
+    pub fn any_orthonormal(&self) -> Self {
+        let v = self.0;
+        let a = v.abs();
+        // Crossing with the axis that's least aligned with the vector avoids a degenerate result
+        let axis = if a.x <= a.y && a.x <= a.z {
+            DVec3::X
+        } else if a.y <= a.z {
+            DVec3::Y
+        } else {
+            DVec3::Z
+        };
+        Self(v.cross(axis).try_normalize().unwrap_or(DVec3::X))
+    }
+
+    /// Returns true if each component is within `epsilon` of the matching component in `other`
+    ///
+    /// `false` is returned if any of the components are NaN.
+    pub fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        (self.0 - other.0).abs().cmple(DVec3::splat(epsilon)).all()
+    }
+
+    /// Returns a vector with each component rounded up to the nearest integer
+    ///
+    /// Negative zero results are normalized to zero, matching `number.ceil`.
+    pub fn ceil(&self) -> Self {
+        // Adding zero converts -0.0 to 0.0
+        Self(self.0.ceil() + 0.0)
+    }
+
+    /// Returns the result of dividing the vector component-wise by `divisor`
+    ///
+    /// `None` is returned if any of the divisor's components are zero, rather than the infinite
+    /// or NaN components that would be produced by the `/` operator.
+    pub fn checked_div(&self, divisor: &Self) -> Option<Self> {
+        (!divisor.0.to_array().contains(&0.0)).then(|| Self(self.0 / divisor.0))
+    }
+
+    /// Returns a vector with each component clamped to the range defined by `lo` and `hi`
+    ///
+    /// If a component of `lo` is greater than the matching component in `hi`,
+    /// then the bounds for that component are swapped.
+    pub fn clamp(&self, lo: &Self, hi: &Self) -> Self {
+        Self(self.0.max(lo.0.min(hi.0)).min(lo.0.max(hi.0)))
+    }
+
+    pub fn cross(&self, other: &Self) -> Self {
+        Self(self.0.cross(other.0))
+    }
+
+    pub fn distance(&self, other: &Self) -> f64 {
+        self.0.distance(other.0)
+    }
+
+    pub fn distance_squared(&self, other: &Self) -> f64 {
+        self.0.distance_squared(other.0)
+    }
+
+    pub fn dot(&self, other: &Self) -> f64 {
+        self.0.dot(other.0)
+    }
+
+    /// Returns a vector with each component rounded down to the nearest integer
+    ///
+    /// Negative zero results are normalized to zero, matching `number.floor`.
+    pub fn floor(&self) -> Self {
+        // Adding zero converts -0.0 to 0.0
+        Self(self.0.floor() + 0.0)
+    }
+
+    pub fn length(&self) -> f64 {
+        self.0.length()
+    }
+
+    pub fn length_squared(&self) -> f64 {
+        self.0.length_squared()
+    }
+
+    /// Linearly interpolates between the vector and `other`
+    ///
+    /// Values of `t` outside of the range `0..=1` will extrapolate beyond the inputs.
+    pub fn lerp(&self, other: &Self, t: f64) -> Self {
+        Self(self.0.lerp(other.0, t))
+    }
+
+    /// Returns a vector containing the largest value of each component
+    pub fn max(&self, other: &Self) -> Self {
+        Self(self.0.max(other.0))
+    }
+
+    /// Returns a vector containing the smallest value of each component
+    pub fn min(&self, other: &Self) -> Self {
+        Self(self.0.min(other.0))
+    }
+
+    /// Returns a vector with the same direction and a length of 1
+    ///
+    /// A zero vector is returned if the vector's length is zero.
+    pub fn normalize(&self) -> Self {
+        Self(self.0.normalize_or_zero())
+    }
+
+    /// Returns the projection of the vector onto `other`
+    ///
+    /// A zero vector is returned if `other` has a length of zero.
+    pub fn project(&self, other: &Self) -> Self {
+        let length_squared = other.length_squared();
+        if length_squared > 0.0 {
+            Self(other.0 * (self.dot(other) / length_squared))
+        } else {
+            Self(DVec3::ZERO)
+        }
+    }
+
+    /// Returns the vector reflected about a surface with the given normal
+    ///
+    /// The normal doesn't need to be normalized, if it has a length of zero then
+    /// the vector is returned unchanged.
+    pub fn reflect(&self, normal: &Self) -> Self {
+        let normal = normal.normalize();
+        Self(self.0 - normal.0 * (2.0 * self.dot(&normal)))
+    }
+
+    /// Returns a vector with each component rounded to the nearest integer
+    ///
+    /// Half-way values are rounded away from zero, and negative zero results are normalized to
+    /// zero, matching `number.round`.
+    pub fn round(&self) -> Self {
+        // Adding zero converts -0.0 to 0.0
+        Self(self.0.round() + 0.0)
+    }
+
+    /// Returns the vector's cylindrical coordinates as a `(radius, phi, z)` tuple
+    ///
+    /// See [Vec3::from_cylindrical] for the conventions used, with `phi` in the range `-π..=π`.
+    /// `phi` is `0` for vectors that lie on the z axis.
+    pub fn to_cylindrical(&self) -> (f64, f64, f64) {
+        (self.0.x.hypot(self.0.y), self.azimuth(), self.0.z)
+    }
+
+    /// Returns the vector's spherical coordinates as a `(radius, theta, phi)` tuple
+    ///
+    /// See [Vec3::from_spherical] for the conventions used, with `theta` in the range `0..=π`,
+    /// and `phi` in the range `-π..=π`.
+    ///
+    /// `phi` is `0` for vectors that lie on the z axis, and all coordinates are `0` for a vector
+    /// with a length of zero.
+    pub fn to_spherical(&self) -> (f64, f64, f64) {
+        let radius = self.length();
+        if radius == 0.0 {
+            return (0.0, 0.0, 0.0);
+        }
+        let theta = (self.0.z / radius).clamp(-1.0, 1.0).acos();
+        (radius, theta, self.azimuth())
+    }
+
+    // The angle of the vector's projection onto the xy plane, with 0 for vectors on the z axis
+    fn azimuth(&self) -> f64 {
+        Vec2::new(self.0.x, self.0.y).angle()
+    }
+
+    #[koto_method(name = "abs")]
+    fn koto_abs(&self) -> KValue {
+        self.abs().into()
+    }
+
+    #[koto_method(name = "any_orthonormal")]
+    fn koto_any_orthonormal(&self) -> KValue {
+        self.any_orthonormal().into()
+    }
+
+    #[koto_method(name = "approx_eq")]
+    fn koto_approx_eq(&self, args: &[KValue]) -> Result<KValue> {
+        use KValue::{Number, Object};
+
+        match args {
+            [Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.approx_eq(&other, DEFAULT_EPSILON).into())
+            }
+            [Object(other), Number(epsilon)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.approx_eq(&other, epsilon.into()).into())
+            }
+            unexpected => type_error_with_slice("a Vec3, with an optional Number", unexpected),
+        }
+    }
+
+    #[koto_method(name = "ceil")]
+    fn koto_ceil(&self) -> KValue {
+        self.ceil().into()
+    }
+
+    #[koto_method(name = "checked_div")]
+    fn koto_checked_div(&self, args: &[KValue]) -> Result<KValue> {
+        let divisor = match args {
+            [KValue::Object(divisor)] if divisor.is_a::<Self>() => *divisor.cast::<Self>().unwrap(),
+            [KValue::Number(n)] => Self(DVec3::splat(n.into())),
+            unexpected => return type_error_with_slice("a Vec3 or Number", unexpected),
+        };
+        Ok(self
+            .checked_div(&divisor)
+            .map_or(KValue::Null, KValue::from))
+    }
+
+    #[koto_method(name = "clamp")]
+    fn koto_clamp(&self, args: &[KValue]) -> Result<KValue> {
+        use KValue::{Number, Object};
+
+        match args {
+            [Object(lo), Object(hi)] if lo.is_a::<Self>() && hi.is_a::<Self>() => {
+                let lo = lo.cast::<Self>().unwrap();
+                let hi = hi.cast::<Self>().unwrap();
+                Ok(self.clamp(&lo, &hi).into())
+            }
+            [Number(lo), Number(hi)] => {
+                let lo = Self(DVec3::splat(lo.into()));
+                let hi = Self(DVec3::splat(hi.into()));
+                Ok(self.clamp(&lo, &hi).into())
+            }
+            unexpected => type_error_with_slice("two Vec3s or two Numbers", unexpected),
+        }
+    }
+
+    #[koto_method(name = "cross")]
+    fn koto_cross(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(rhs)] if rhs.is_a::<Self>() => {
+                let rhs = rhs.cast::<Self>().unwrap();
+                Ok(self.cross(&rhs).into())
+            }
+            unexpected => type_error_with_slice("a Vec3", unexpected),
+        }
+    }
+
+    #[koto_method(name = "distance")]
+    fn koto_distance(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.distance(&other).into())
+            }
+            unexpected => type_error_with_slice("a Vec3", unexpected),
+        }
+    }
+
+    #[koto_method(name = "distance_squared")]
+    fn koto_distance_squared(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.distance_squared(&other).into())
+            }
+            unexpected => type_error_with_slice("a Vec3", unexpected),
+        }
+    }
+
+    #[koto_method(name = "dot")]
+    fn koto_dot(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(rhs)] if rhs.is_a::<Self>() => {
+                let rhs = rhs.cast::<Self>().unwrap();
+                Ok(self.dot(&rhs).into())
+            }
+            unexpected => type_error_with_slice("a Vec3", unexpected),
+        }
+    }
+
+    #[koto_method(name = "floor")]
+    fn koto_floor(&self) -> KValue {
+        self.floor().into()
+    }
+
+    #[koto_method(name = "length")]
+    fn koto_length(&self) -> KValue {
+        self.length().into()
+    }
+
+    #[koto_method(name = "length_squared")]
+    fn koto_length_squared(&self) -> KValue {
+        self.length_squared().into()
+    }
+
+    #[koto_method(name = "lerp")]
+    fn koto_lerp(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other), KValue::Number(t)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.lerp(&other, t.into()).into())
+            }
+            unexpected => type_error_with_slice("a Vec3 and a Number", unexpected),
+        }
+    }
+
+    #[koto_method(name = "max")]
+    fn koto_max(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.max(&other).into())
+            }
+            unexpected => type_error_with_slice("a Vec3", unexpected),
+        }
+    }
+
+    #[koto_method(name = "min")]
+    fn koto_min(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.min(&other).into())
+            }
+            unexpected => type_error_with_slice("a Vec3", unexpected),
+        }
+    }
+
+    #[koto_method(name = "normalize")]
+    fn koto_normalize(&self) -> KValue {
+        self.normalize().into()
+    }
+
+    #[koto_method(name = "project")]
+    fn koto_project(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(other)] if other.is_a::<Self>() => {
+                let other = other.cast::<Self>().unwrap();
+                Ok(self.project(&other).into())
+            }
+            unexpected => type_error_with_slice("a Vec3", unexpected),
+        }
+    }
+
+    #[koto_method(name = "reflect")]
+    fn koto_reflect(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [KValue::Object(normal)] if normal.is_a::<Self>() => {
+                let normal = normal.cast::<Self>().unwrap();
+                Ok(self.reflect(&normal).into())
+            }
+            unexpected => type_error_with_slice("a Vec3", unexpected),
+        }
+    }
+
+    #[koto_method(name = "round")]
+    fn koto_round(&self) -> KValue {
+        self.round().into()
+    }
+
+    #[koto_method(name = "to_cylindrical")]
+    fn koto_to_cylindrical(&self) -> KValue {
+        let (radius, phi, z) = self.to_cylindrical();
+        KValue::Tuple(vec![radius.into(), phi.into(), z.into()].into())
+    }
+
+    #[koto_method(name = "to_list")]
+    fn koto_to_list(&self) -> KValue {
+        KList::from_slice(&[self.0.x.into(), self.0.y.into(), self.0.z.into()]).into()
+    }
+
+    #[koto_method(name = "to_map")]
+    fn koto_to_map(&self) -> KValue {
+        let result = KMap::with_capacity(3);
+        result.insert("x", self.0.x);
+        result.insert("y", self.0.y);
+        result.insert("z", self.0.z);
+        result.into()
+    }
+
+    #[koto_method(name = "to_spherical")]
+    fn koto_to_spherical(&self) -> KValue {
+        let (radius, theta, phi) = self.to_spherical();
+        KValue::Tuple(vec![radius.into(), theta.into(), phi.into()].into())
+    }
+
+    #[koto_method(name = "to_string")]
+    fn koto_to_string(&self, args: &[KValue]) -> Result<KValue> {
+        match args {
+            [] => Ok(self.to_string().into()),
+            [KValue::Number(precision)] if *precision >= 0.0 => {
+                let precision = usize::from(precision);
+                Ok(format!("{self:.precision$}").into())
+            }
+            unexpected => type_error_with_slice("an optional non-negative Number", unexpected),
+        }
+    }
+
     #[koto_method]
     fn x(&self) -> KValue {
         self.0.x.into()
@@ -54,6 +484,25 @@ impl KotoObject for Vec3 {
         geometry_arithmetic_op!(self, rhs, /)
     }
 
+    fn add_rhs(&self, lhs: &KValue) -> Result<KValue> {
+        geometry_unsupported_rhs_op!(lhs, "+")
+    }
+
+    fn subtract_rhs(&self, lhs: &KValue) -> Result<KValue> {
+        geometry_unsupported_rhs_op!(lhs, "-")
+    }
+
+    fn multiply_rhs(&self, lhs: &KValue) -> Result<KValue> {
+        match lhs {
+            KValue::Number(n) => Ok((*self * f64::from(n)).into()),
+            unexpected => type_error("a Number", unexpected),
+        }
+    }
+
+    fn divide_rhs(&self, lhs: &KValue) -> Result<KValue> {
+        geometry_unsupported_rhs_op!(lhs, "/")
+    }
+
     fn add_assign(&mut self, rhs: &KValue) -> Result<()> {
         geometry_compound_assign_op!(self, rhs, +=)
     }
@@ -78,16 +527,33 @@ impl KotoObject for Vec3 {
         geometry_comparison_op!(self, rhs, !=)
     }
 
-    fn index(&self, index: &KValue) -> Result<KValue> {
-        match index {
-            KValue::Number(n) => match usize::from(n) {
-                0 => Ok(self.x()),
-                1 => Ok(self.y()),
-                2 => Ok(self.z()),
-                other => runtime_error!("index out of range (got {other}, should be <= 2)"),
-            },
-            unexpected => type_error("Number", unexpected),
+    fn access_assign(&mut self, key: &KString, value: &KValue) -> Result<()> {
+        match key.as_str() {
+            "x" => self.0.x = assigned_component(key, value)?,
+            "y" => self.0.y = assigned_component(key, value)?,
+            "z" => self.0.z = assigned_component(key, value)?,
+            _ => return runtime_error!("'{key}' can't be assigned in '{}'", Self::type_static()),
         }
+        Ok(())
+    }
+
+    geometry_swizzle_fallback!(x, y, z);
+
+    // Vectors with NaN components can't be used as map keys, given that NaN isn't equal to itself
+    fn is_hashable(&self) -> bool {
+        !self.0.is_nan()
+    }
+
+    fn hash(&self, state: &mut dyn Hasher) {
+        hash_components(&[self.0.x, self.0.y, self.0.z], state)
+    }
+
+    fn index(&self, index: &KValue) -> Result<KValue> {
+        index_components(&[self.0.x, self.0.y, self.0.z], index)
+    }
+
+    fn size(&self) -> Option<usize> {
+        Some(3)
     }
 
     fn is_iterable(&self) -> IsIterable {
@@ -131,10 +597,13 @@ impl From<Vec3> for KValue {
 
 impl fmt::Display for Vec3 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let precision = f.precision();
         write!(
             f,
             "Vec3{{x: {}, y: {}, z: {}}}",
-            self.0.x, self.0.y, self.0.z
+            format_component(self.0.x, precision),
+            format_component(self.0.y, precision),
+            format_component(self.0.z, precision)
         )
     }
 }
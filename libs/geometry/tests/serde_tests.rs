@@ -0,0 +1,49 @@
+#![cfg(feature = "serde")]
+
+use koto_geometry::{Rect, Vec2, Vec3};
+use serde_json::json;
+
+#[test]
+fn vec2_round_trip() {
+    let v = Vec2::new(1.5, -2.0);
+    let serialized = serde_json::to_value(v).unwrap();
+    assert_eq!(serialized, json!({"x": 1.5, "y": -2.0}));
+    let deserialized: Vec2 = serde_json::from_value(serialized).unwrap();
+    assert!(deserialized == v);
+}
+
+#[test]
+fn vec3_round_trip() {
+    let v = Vec3::new(1.0, 2.0, -3.5);
+    let serialized = serde_json::to_value(v).unwrap();
+    assert_eq!(serialized, json!({"x": 1.0, "y": 2.0, "z": -3.5}));
+    let deserialized: Vec3 = serde_json::from_value(serialized).unwrap();
+    assert!(deserialized == v);
+}
+
+#[test]
+fn rect_round_trip() {
+    let r = Rect::from_x_y_w_h(10.0, 20.0, 30.0, 40.0);
+    let serialized = serde_json::to_value(r).unwrap();
+    assert_eq!(
+        serialized,
+        json!({"x": 10.0, "y": 20.0, "width": 30.0, "height": 40.0})
+    );
+    let deserialized: Rect = serde_json::from_value(serialized).unwrap();
+    assert!(deserialized == r);
+}
+
+#[test]
+fn round_trip_through_string() {
+    let points = vec![Vec2::new(0.0, 1.0), Vec2::new(-1.0, 0.25)];
+    let serialized = serde_json::to_string(&points).unwrap();
+    assert_eq!(serialized, r#"[{"x":0.0,"y":1.0},{"x":-1.0,"y":0.25}]"#);
+    let deserialized: Vec<Vec2> = serde_json::from_str(&serialized).unwrap();
+    assert!(deserialized == points);
+}
+
+#[test]
+fn missing_field() {
+    let result = serde_json::from_value::<Vec3>(json!({"x": 1.0, "y": 2.0}));
+    assert!(result.is_err());
+}
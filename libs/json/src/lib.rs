@@ -56,7 +56,7 @@ pub fn make_module() -> KMap {
     });
 
     result.add_fn("to_string", |ctx| match ctx.args() {
-        [value] => match serde_json::to_string_pretty(&SerializableValue(value)) {
+        [value] => match serde_json::to_string_pretty(&SerializableValue::new(value)) {
             Ok(result) => Ok(result.into()),
             Err(e) => runtime_error!("json.to_string: {e}"),
         },
@@ -42,7 +42,7 @@ pub fn make_module() -> KMap {
     });
 
     result.add_fn("to_string", |ctx| match ctx.args() {
-        [value] => match toml::to_string_pretty(&SerializableValue(value)) {
+        [value] => match toml::to_string_pretty(&SerializableValue::new(value)) {
             Ok(result) => Ok(result.into()),
             Err(e) => runtime_error!("toml.to_string: {e}"),
         },
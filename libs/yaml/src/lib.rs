@@ -57,7 +57,7 @@ pub fn make_module() -> KMap {
     });
 
     result.add_fn("to_string", |ctx| match ctx.args() {
-        [value] => match serde_yaml::to_string(&SerializableValue(value)) {
+        [value] => match serde_yaml::to_string(&SerializableValue::new(value)) {
             Ok(result) => Ok(result.into()),
             Err(e) => runtime_error!("yaml.to_string: {}", e),
         },